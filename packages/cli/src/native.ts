@@ -29,6 +29,9 @@ import { readCursorMessagesFromCache } from "./cursor.js";
 
 interface NativeGraphOptions {
   homeDir?: string;
+  extraHomeDirs?: string[];
+  sourcePaths?: Record<string, string[]>;
+  archives?: string[];
   sources?: string[];
   since?: string;
   until?: string;
@@ -101,6 +104,18 @@ interface NativeGraphMeta {
   dateRangeStart: string;
   dateRangeEnd: string;
   processingTimeMs: number;
+  currency: string;
+  duplicateCount: number;
+}
+
+interface NativeScanDiagnostic {
+  source: string;
+  path: string;
+  kind: string;
+  message: string;
+  line?: number;
+  skippedLines: number;
+  schemaVersion?: string;
 }
 
 interface NativeGraphResult {
@@ -108,6 +123,8 @@ interface NativeGraphResult {
   summary: NativeDataSummary;
   years: NativeYearSummary[];
   contributions: NativeDailyContribution[];
+  unpricedModels?: NativeUnpricedModel[];
+  diagnostics?: NativeScanDiagnostic[];
 }
 
 // Types for pricing-aware APIs
@@ -118,16 +135,28 @@ interface NativePricingEntry {
     outputCostPerToken: number;
     cacheReadInputTokenCost?: number;
     cacheCreationInputTokenCost?: number;
+    outputCostPerReasoningToken?: number;
+    inputCostPerTokenAbove200kTokens?: number;
+    outputCostPerTokenAbove200kTokens?: number;
+    cacheReadInputTokenCostAbove200kTokens?: number;
+    cacheCreationInputTokenCostAbove200kTokens?: number;
   };
+  /** Date these rates took effect (YYYY-MM-DD, UTC) */
+  effectiveFrom?: string;
 }
 
 interface NativeReportOptions {
   homeDir?: string;
+  extraHomeDirs?: string[];
+  sourcePaths?: Record<string, string[]>;
+  archives?: string[];
   sources?: string[];
   pricing: NativePricingEntry[];
   since?: string;
   until?: string;
   year?: string;
+  currency?: string;
+  exchangeRates?: string;
 }
 
 interface NativeModelUsage {
@@ -141,16 +170,29 @@ interface NativeModelUsage {
   reasoning: number;
   messageCount: number;
   cost: number;
+  pricingRule?: string;
+  pricingKey?: string;
+  matchKind: string;
+}
+
+interface NativeUnpricedModel {
+  model: string;
+  sources: string[];
+  tokens: NativeTokenBreakdown;
+  totalTokens: number;
+  messageCount: number;
 }
 
 interface NativeModelReport {
   entries: NativeModelUsage[];
+  unpricedModels: NativeUnpricedModel[];
   totalInput: number;
   totalOutput: number;
   totalCacheRead: number;
   totalCacheWrite: number;
   totalMessages: number;
   totalCost: number;
+  currency: string;
   processingTimeMs: number;
 }
 
@@ -167,7 +209,9 @@ interface NativeMonthlyUsage {
 
 interface NativeMonthlyReport {
   entries: NativeMonthlyUsage[];
+  unpricedModels: NativeUnpricedModel[];
   totalCost: number;
+  currency: string;
   processingTimeMs: number;
 }
 
@@ -185,6 +229,8 @@ interface NativeParsedMessage {
   reasoning: number;
   sessionId: string;
   agent?: string;
+  root?: string;
+  recordedCost?: number;
 }
 
 interface NativeParsedMessages {
@@ -195,11 +241,15 @@ interface NativeParsedMessages {
   geminiCount: number;
   ampCount: number;
   droidCount?: number;
+  diagnostics?: NativeScanDiagnostic[];
   processingTimeMs: number;
 }
 
 interface NativeLocalParseOptions {
   homeDir?: string;
+  extraHomeDirs?: string[];
+  sourcePaths?: Record<string, string[]>;
+  archives?: string[];
   sources?: string[];
   since?: string;
   until?: string;
@@ -208,12 +258,16 @@ interface NativeLocalParseOptions {
 
 interface NativeFinalizeReportOptions {
   homeDir?: string;
+  extraHomeDirs?: string[];
+  sourcePaths?: Record<string, string[]>;
   localMessages: NativeParsedMessages;
   pricing: NativePricingEntry[];
   includeCursor: boolean;
   since?: string;
   until?: string;
   year?: string;
+  currency?: string;
+  exchangeRates?: string;
 }
 
 interface NativeCore {
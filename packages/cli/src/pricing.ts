@@ -78,6 +78,7 @@ export interface LiteLLMModelPricing {
   output_cost_per_token?: number;
   cache_creation_input_token_cost?: number;
   cache_read_input_token_cost?: number;
+  output_cost_per_reasoning_token?: number;
   input_cost_per_token_above_200k_tokens?: number;
   output_cost_per_token_above_200k_tokens?: number;
   cache_creation_input_token_cost_above_200k_tokens?: number;
@@ -102,7 +103,14 @@ export interface PricingEntry {
     outputCostPerToken: number;
     cacheReadInputTokenCost?: number;
     cacheCreationInputTokenCost?: number;
+    outputCostPerReasoningToken?: number;
+    inputCostPerTokenAbove200kTokens?: number;
+    outputCostPerTokenAbove200kTokens?: number;
+    cacheReadInputTokenCostAbove200kTokens?: number;
+    cacheCreationInputTokenCostAbove200kTokens?: number;
   };
+  /** Date these rates took effect (YYYY-MM-DD, UTC) */
+  effectiveFrom?: string;
 }
 
 function getCacheDir(): string {
@@ -213,6 +221,13 @@ export class PricingFetcher {
         // napi-rs expects undefined (not null) for Option<T> fields
         cacheReadInputTokenCost: pricing.cache_read_input_token_cost,
         cacheCreationInputTokenCost: pricing.cache_creation_input_token_cost,
+        outputCostPerReasoningToken: pricing.output_cost_per_reasoning_token,
+        inputCostPerTokenAbove200kTokens: pricing.input_cost_per_token_above_200k_tokens,
+        outputCostPerTokenAbove200kTokens: pricing.output_cost_per_token_above_200k_tokens,
+        cacheReadInputTokenCostAbove200kTokens:
+          pricing.cache_read_input_token_cost_above_200k_tokens,
+        cacheCreationInputTokenCostAbove200kTokens:
+          pricing.cache_creation_input_token_cost_above_200k_tokens,
       },
     }));
   }
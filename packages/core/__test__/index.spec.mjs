@@ -66,6 +66,19 @@ testFn("scanSessions finds Claude files in fixtures", (t) => {
   t.true(stats.claudeFiles >= 1, "Should find at least 1 Claude file");
 });
 
+testFn("scanSessions finds Copilot chat sessions in fixtures", (t) => {
+  const fixturesDir = join(__dirname, "fixtures");
+  
+  if (!existsSync(fixturesDir)) {
+    t.pass("Fixtures directory not found, skipping");
+    return;
+  }
+
+  // workspace.json next to chatSessions/ is not a session file
+  const stats = nativeModule.scanSessions(fixturesDir, ["copilot"]);
+  t.is(stats.sourceFiles.copilot, 2, "Should find the CLI and VS Code sessions");
+});
+
 testFn("generateGraph returns valid structure", (t) => {
   const tmpDir = join(__dirname, "tmp-graph-test-" + Date.now());
   setupMockOpenCodeSession(tmpDir);
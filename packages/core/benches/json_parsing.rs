@@ -1,9 +1,12 @@
 //! Benchmark: simd-json vs serde_json parsing performance
 //!
-//! Run with: cargo bench --features noop
+//! Run with: cargo bench --no-default-features
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tokscale_core::{read_jsonl_lines_from, LineFilter};
 
 /// Generate session JSON data of varying message counts
 fn generate_session_json(message_count: usize) -> String {
@@ -54,6 +57,69 @@ fn generate_jsonl(line_count: usize) -> String {
         .join("\n")
 }
 
+/// Generate a Claude Code session log: each turn has a user prompt, a
+/// tool call, a large tool result and a final answer, like real histories
+/// where most bytes are prompts and tool output rather than usage
+fn generate_claude_session(turn_count: usize) -> String {
+    let filler = "lorem ipsum dolor sit amet ".repeat(80);
+    let mut lines = Vec::with_capacity(turn_count * 4);
+    for i in 0..turn_count {
+        let ts = format!("2025-06-15T{:02}:{:02}:00Z", (i / 60) % 24, i % 60);
+        lines.push(format!(
+            r#"{{"parentUuid":"u-{i}","type":"user","timestamp":"{ts}","message":{{"role":"user","content":"Prompt {i}: {filler}"}},"uuid":"u-{i}-p"}}"#
+        ));
+        lines.push(format!(
+            r#"{{"parentUuid":"u-{i}-p","type":"assistant","timestamp":"{ts}","requestId":"req_{i}a","message":{{"id":"msg_{i}a","model":"claude-sonnet-4-20250514","content":[{{"type":"tool_use","name":"Read","input":{{"file_path":"/src/lib.rs"}}}}],"usage":{{"input_tokens":{},"output_tokens":{},"cache_read_input_tokens":{},"cache_creation_input_tokens":12}}}},"uuid":"u-{i}-a"}}"#,
+            10 + i,
+            40 + i,
+            2000 + i
+        ));
+        lines.push(format!(
+            r#"{{"parentUuid":"u-{i}-a","type":"user","timestamp":"{ts}","message":{{"role":"user","content":[{{"type":"tool_result","content":"{filler}{filler}{filler}"}}]}},"uuid":"u-{i}-r"}}"#
+        ));
+        lines.push(format!(
+            r#"{{"parentUuid":"u-{i}-r","type":"assistant","timestamp":"{ts}","requestId":"req_{i}b","message":{{"id":"msg_{i}b","model":"claude-sonnet-4-20250514","content":[{{"type":"text","text":"Answer {i}: {filler}"}}],"usage":{{"input_tokens":{},"output_tokens":{},"cache_read_input_tokens":{},"cache_creation_input_tokens":0}}}},"uuid":"u-{i}-b"}}"#,
+            20 + i,
+            300 + i,
+            4000 + i
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Generate a Codex CLI rollout: session metadata, then per turn a
+/// `turn_context`, response items and a `token_count` event
+fn generate_codex_session(turn_count: usize) -> String {
+    let filler = "lorem ipsum dolor sit amet ".repeat(80);
+    let mut lines = Vec::with_capacity(turn_count * 4 + 1);
+    lines.push(
+        r#"{"timestamp":"2025-06-15T00:00:00Z","type":"session_meta","payload":{"id":"s-1","cwd":"/home/dev/project","cli_version":"0.46.0"}}"#
+            .to_string(),
+    );
+    for i in 0..turn_count {
+        let ts = format!("2025-06-15T{:02}:{:02}:00Z", (i / 60) % 24, i % 60);
+        lines.push(format!(
+            r#"{{"timestamp":"{ts}","type":"turn_context","payload":{{"cwd":"/home/dev/project","model":"gpt-5-codex","effort":"medium"}}}}"#
+        ));
+        lines.push(format!(
+            r#"{{"timestamp":"{ts}","type":"response_item","payload":{{"type":"message","role":"user","content":[{{"type":"input_text","text":"Prompt {i}: {filler}"}}]}}}}"#
+        ));
+        lines.push(format!(
+            r#"{{"timestamp":"{ts}","type":"response_item","payload":{{"type":"function_call_output","output":"{filler}{filler}{filler}"}}}}"#
+        ));
+        lines.push(format!(
+            r#"{{"timestamp":"{ts}","type":"event_msg","payload":{{"type":"token_count","info":{{"last_token_usage":{{"input_tokens":{},"cached_input_tokens":{},"output_tokens":{},"reasoning_output_tokens":{}}},"total_token_usage":{{"input_tokens":{},"cached_input_tokens":0,"output_tokens":{},"reasoning_output_tokens":0}}}}}}}}"#,
+            1000 + i,
+            800 + i,
+            200 + i,
+            50,
+            (1000 + i) * (i + 1),
+            (200 + i) * (i + 1)
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
 #[derive(Deserialize)]
 struct BenchSession {
     #[allow(dead_code)]
@@ -190,6 +256,111 @@ fn bench_jsonl_parsing(c: &mut Criterion) {
     group.finish();
 }
 
+/// Entry shape shared by Claude and Codex logs, enough to reach the usage
+#[derive(Deserialize)]
+struct StreamEntry {
+    #[serde(rename = "type")]
+    #[allow(dead_code)]
+    entry_type: String,
+    #[allow(dead_code)]
+    timestamp: Option<String>,
+    #[allow(dead_code)]
+    message: Option<StreamMessage>,
+    #[allow(dead_code)]
+    payload: Option<StreamPayload>,
+}
+
+#[derive(Deserialize)]
+struct StreamMessage {
+    #[allow(dead_code)]
+    model: Option<String>,
+    #[allow(dead_code)]
+    usage: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct StreamPayload {
+    #[serde(rename = "type")]
+    #[allow(dead_code)]
+    payload_type: Option<String>,
+    #[allow(dead_code)]
+    model: Option<String>,
+    #[allow(dead_code)]
+    info: Option<serde_json::Value>,
+}
+
+/// The previous approach: `BufRead::lines` allocates a `String` per line and
+/// `to_vec` copies it again for simd-json
+fn count_entries_with_lines(path: &Path) -> usize {
+    let reader = BufReader::new(std::fs::File::open(path).unwrap());
+    let mut count = 0;
+    for line in reader.lines() {
+        let line = line.unwrap();
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let mut bytes = trimmed.as_bytes().to_vec();
+        if simd_json::from_slice::<StreamEntry>(&mut bytes).is_ok() {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn count_entries_with_reader(path: &Path, filter: LineFilter) -> usize {
+    let mut count = 0;
+    read_jsonl_lines_from(path, 0, filter, |line| {
+        let ok = simd_json::from_slice::<StreamEntry>(line).is_ok();
+        count += ok as usize;
+        ok
+    })
+    .unwrap();
+    count
+}
+
+fn bench_jsonl_streaming(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jsonl_streaming");
+    let dir = tempfile::TempDir::new().unwrap();
+
+    // Same needles as the Claude and Codex parsers
+    let fixtures = [
+        (
+            "claude",
+            generate_claude_session(2000),
+            LineFilter::containing_any(&["\"usage\""]),
+        ),
+        (
+            "codex",
+            generate_codex_session(2000),
+            LineFilter::containing_any(&["token_count", "turn_context", "session_meta"]),
+        ),
+    ];
+
+    for (name, content, filter) in fixtures {
+        let path = dir.path().join(format!("{}.jsonl", name));
+        std::fs::write(&path, &content).unwrap();
+
+        group.throughput(Throughput::Bytes(content.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("lines_to_vec", name), &path, |b, path| {
+            b.iter(|| black_box(count_entries_with_lines(black_box(path))))
+        });
+
+        group.bench_with_input(BenchmarkId::new("shared_reader", name), &path, |b, path| {
+            b.iter(|| black_box(count_entries_with_reader(black_box(path), LineFilter::ALL)))
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("shared_reader_prefiltered", name),
+            &path,
+            |b, path| b.iter(|| black_box(count_entries_with_reader(black_box(path), filter))),
+        );
+    }
+
+    group.finish();
+}
+
 fn bench_token_extraction(c: &mut Criterion) {
     let mut group = c.benchmark_group("token_extraction");
 
@@ -234,6 +405,7 @@ criterion_group!(
     benches,
     bench_json_parsing,
     bench_jsonl_parsing,
+    bench_jsonl_streaming,
     bench_token_extraction
 );
 criterion_main!(benches);
@@ -1,5 +1,33 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+/**
+ * Pricing data loaded once in Rust and reused across calls. Pass it as the
+ * `pricing` argument of the report functions instead of `options.pricing`.
+ */
+export declare class PricingHandle {
+  /** Number of models with pricing */
+  modelCount(): number
+  /** Merge a local pricing overrides file (TOML, or JSON for `.json` paths) */
+  applyOverrides(path: string): void
+  /** Add the dated rates of a pricing snapshot file */
+  addSnapshots(path: string): void
+  /** Which rule prices a model ("base", or the matching override), if any */
+  pricingRule(modelId: string): string | null
+}
+
+/** Watches session files and reports usage deltas as they are written */
+export declare class UsageWatcher {
+  /**
+   * Parse all session files, then call `callback` with a `WatchEvent` whenever
+   * files change. Call `close()` to stop watching.
+   */
+  constructor(options: WatchOptions, callback: (arg: WatchEvent) => unknown, pricing?: PricingHandle | undefined | null)
+  /** Daily contributions of everything seen so far */
+  contributions(): Array<DailyContribution>
+  /** Stop watching session files */
+  close(): void
+}
+
 /** Daily contribution data */
 export interface DailyContribution {
   date: string
@@ -28,46 +56,121 @@ export interface DataSummary {
   models: Array<string>
 }
 
+/**
+ * Explain how a model id resolves to pricing: the matched key, the kind of
+ * match, the override rule and the resulting rates
+ */
+export declare function explainPricing(modelId: string, pricing: PricingHandle): PricingExplanation
+
 /** Finalize graph with pricing */
-export declare function finalizeGraph(options: FinalizeGraphOptions): GraphResult
+export declare function finalizeGraph(options: FinalizeGraphOptions, pricing?: PricingHandle | undefined | null): GraphResult
 
 /** Options for finalizing graph */
 export interface FinalizeGraphOptions {
   homeDir?: string
+  /**
+   * Additional home directories, e.g. logs synced from containers or other
+   * machines. Scanned with each tool's default layout; environment overrides
+   * such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+   */
+  extraHomeDirs?: Array<string>
+  /**
+   * Directories to scan per source name, replacing that source's default
+   * locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+   */
+  sourcePaths?: Record<string, Array<string>>
   localMessages: ParsedMessages
   pricing: Array<PricingEntry>
   includeCursor: boolean
   since?: string
   until?: string
   year?: string
+  /**
+   * Timezone for network-synced (Cursor) messages; local messages keep the
+   * dates assigned by `parse_local_sources`. Defaults to UTC.
+   */
+  timezone?: string
+  /**
+   * Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+   * Defaults to USD; other currencies need an exchange-rate table.
+   */
+  currency?: string
+  /** Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json) */
+  exchangeRates?: string
 }
 
 /** Options for finalizing monthly report */
 export interface FinalizeMonthlyOptions {
   homeDir?: string
+  /**
+   * Additional home directories, e.g. logs synced from containers or other
+   * machines. Scanned with each tool's default layout; environment overrides
+   * such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+   */
+  extraHomeDirs?: Array<string>
+  /**
+   * Directories to scan per source name, replacing that source's default
+   * locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+   */
+  sourcePaths?: Record<string, Array<string>>
   localMessages: ParsedMessages
   pricing: Array<PricingEntry>
   includeCursor: boolean
   since?: string
   until?: string
   year?: string
+  /**
+   * Timezone for network-synced (Cursor) messages; local messages keep the
+   * dates assigned by `parse_local_sources`. Defaults to UTC.
+   */
+  timezone?: string
+  /**
+   * Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+   * Defaults to USD; other currencies need an exchange-rate table.
+   */
+  currency?: string
+  /** Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json) */
+  exchangeRates?: string
 }
 
 /** Finalize monthly report with pricing */
-export declare function finalizeMonthlyReport(options: FinalizeMonthlyOptions): MonthlyReport
+export declare function finalizeMonthlyReport(options: FinalizeMonthlyOptions, pricing?: PricingHandle | undefined | null): MonthlyReport
 
 /** Finalize model report: apply pricing to local messages, add Cursor, aggregate */
-export declare function finalizeReport(options: FinalizeReportOptions): ModelReport
+export declare function finalizeReport(options: FinalizeReportOptions, pricing?: PricingHandle | undefined | null): ModelReport
 
 /** Options for finalizing report with pricing */
 export interface FinalizeReportOptions {
   homeDir?: string
+  /**
+   * Additional home directories, e.g. logs synced from containers or other
+   * machines. Scanned with each tool's default layout; environment overrides
+   * such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+   */
+  extraHomeDirs?: Array<string>
+  /**
+   * Directories to scan per source name, replacing that source's default
+   * locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+   */
+  sourcePaths?: Record<string, Array<string>>
   localMessages: ParsedMessages
   pricing: Array<PricingEntry>
   includeCursor: boolean
   since?: string
   until?: string
   year?: string
+  /**
+   * Timezone for network-synced (Cursor) messages; local messages keep the
+   * dates assigned by `parse_local_sources`. Defaults to UTC.
+   */
+  timezone?: string
+  /**
+   * Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+   * Defaults to USD; other currencies need an exchange-rate table.
+   */
+  currency?: string
+  /** Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json) */
+  exchangeRates?: string
 }
 
 /**
@@ -82,13 +185,19 @@ export interface FinalizeReportOptions {
 export declare function generateGraph(options: GraphOptions): GraphResult
 
 /** Generate graph data with pricing calculation */
-export declare function generateGraphWithPricing(options: ReportOptions): GraphResult
+export declare function generateGraphWithPricing(options: ReportOptions, pricing?: PricingHandle | undefined | null): GraphResult
 
 /** Get model usage report with pricing calculation */
-export declare function getModelReport(options: ReportOptions): ModelReport
+export declare function getModelReport(options: ReportOptions, pricing?: PricingHandle | undefined | null): ModelReport
+
+/** Get per-project usage report with pricing calculation */
+export declare function getProjectReport(options: ReportOptions, pricing?: PricingHandle | undefined | null): ProjectReport
+
+/** Get per-session usage report with pricing calculation */
+export declare function getSessionReport(options: SessionReportOptions, pricing?: PricingHandle | undefined | null): SessionReport
 
 /** Get monthly usage report with pricing calculation */
-export declare function getMonthlyReport(options: ReportOptions): MonthlyReport
+export declare function getMonthlyReport(options: ReportOptions, pricing?: PricingHandle | undefined | null): MonthlyReport
 
 /** Metadata about the graph generation */
 export interface GraphMeta {
@@ -97,13 +206,34 @@ export interface GraphMeta {
   dateRangeStart: string
   dateRangeEnd: string
   processingTimeMs: number
+  /** ISO 4217 code of the currency every cost is in */
+  currency: string
+  /** Number of duplicate messages dropped (e.g. Claude responses logged more than once) */
+  duplicateCount: number
 }
 
 /** Configuration options for graph generation */
 export interface GraphOptions {
   /** Home directory path (defaults to user's home) */
   homeDir?: string
-  /** Sources to include: "opencode", "claude", "codex", "gemini", "cursor", "amp" */
+  /**
+   * Additional home directories, e.g. logs synced from containers or other
+   * machines. Scanned with each tool's default layout; environment overrides
+   * such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+   */
+  extraHomeDirs?: Array<string>
+  /**
+   * Directories to scan per source name, replacing that source's default
+   * locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+   */
+  sourcePaths?: Record<string, Array<string>>
+  /**
+   * Session archives (`.tar`, `.tar.gz`/`.tgz` or `.zip` snapshots of tool
+   * directories), read without extracting. Their messages are tagged with
+   * the archive's file name.
+   */
+  archives?: Array<string>
+  /** Sources to include: "opencode", "claude", "codex", "gemini", "cursor", "amp", "droid", "copilot", "qwen", "kilo", or a source defined in `~/.config/tokscale/sources.toml` */
   sources?: Array<string>
   /** Start date filter (YYYY-MM-DD) */
   since?: string
@@ -111,8 +241,15 @@ export interface GraphOptions {
   until?: string
   /** Filter to specific year */
   year?: string
+  /**
+   * Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+   * Defaults to UTC; date filters apply to dates in this zone.
+   */
+  timezone?: string
   /** Number of parallel threads (defaults to CPU count) */
   threads?: number
+  /** Use the persistent parse cache (defaults to true) */
+  cache?: boolean
 }
 
 /** Complete graph result */
@@ -121,6 +258,10 @@ export interface GraphResult {
   summary: DataSummary
   years: Array<YearSummary>
   contributions: Array<DailyContribution>
+  /** Models without pricing, by token volume (empty when built without pricing data) */
+  unpricedModels: Array<UnpricedModel>
+  /** Files that were skipped or only partially parsed */
+  diagnostics: Array<ScanDiagnostic>
 }
 
 /** Simple health check to verify the native module is working */
@@ -129,29 +270,67 @@ export declare function healthCheck(): string
 /** Options for parsing local sources only (no Cursor) */
 export interface LocalParseOptions {
   homeDir?: string
+  /**
+   * Additional home directories, e.g. logs synced from containers or other
+   * machines. Scanned with each tool's default layout; environment overrides
+   * such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+   */
+  extraHomeDirs?: Array<string>
+  /**
+   * Directories to scan per source name, replacing that source's default
+   * locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+   */
+  sourcePaths?: Record<string, Array<string>>
+  /**
+   * Session archives (`.tar`, `.tar.gz`/`.tgz` or `.zip` snapshots of tool
+   * directories), read without extracting. Their messages are tagged with
+   * the archive's file name.
+   */
+  archives?: Array<string>
   sources?: Array<string>
   since?: string
   until?: string
   year?: string
+  /**
+   * Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+   * Defaults to UTC; date filters apply to dates in this zone.
+   */
+  timezone?: string
+  /** Use the persistent parse cache (defaults to true) */
+  cache?: boolean
 }
 
+/** Load LiteLLM's `model_prices_and_context_window.json` (or the CLI's cached copy of it) */
+export declare function loadPricingFile(path: string): PricingHandle
+
 /** Pricing data for a single model (passed from TypeScript) */
 export interface ModelPricing {
   inputCostPerToken: number
   outputCostPerToken: number
   cacheReadInputTokenCost?: number
   cacheCreationInputTokenCost?: number
+  /** Rate for reasoning tokens (defaults to the output rate) */
+  outputCostPerReasoningToken?: number
+  /** Rates for requests whose prompt (input plus cache tokens) exceeds 200k tokens */
+  inputCostPerTokenAbove200kTokens?: number
+  outputCostPerTokenAbove200kTokens?: number
+  cacheReadInputTokenCostAbove200kTokens?: number
+  cacheCreationInputTokenCostAbove200kTokens?: number
 }
 
 /** Model report result */
 export interface ModelReport {
   entries: Array<ModelUsage>
+  /** Models without pricing, by token volume */
+  unpricedModels: Array<UnpricedModel>
   totalInput: number
   totalOutput: number
   totalCacheRead: number
   totalCacheWrite: number
   totalMessages: number
   totalCost: number
+  /** ISO 4217 code of the currency every cost is in */
+  currency: string
   processingTimeMs: number
 }
 
@@ -167,12 +346,148 @@ export interface ModelUsage {
   reasoning: number
   messageCount: number
   cost: number
+  /**
+   * Pricing rule that priced the model: "base", or the local override
+   * (e.g. "override my-model", "glob ollama/*", "anthropic discount 20%");
+   * `None` if no pricing matched
+   */
+  pricingRule?: string
+  /** Pricing key the model resolved to (the pattern for pattern overrides) */
+  pricingKey?: string
+  /**
+   * How the model matched its pricing: "exact", "prefixed", "normalized",
+   * "fuzzy", "pattern" or "none"
+   */
+  matchKind: string
+}
+
+/** Project report result */
+export interface ProjectReport {
+  entries: Array<ProjectUsage>
+  /** Models without pricing, by token volume */
+  unpricedModels: Array<UnpricedModel>
+  totalInput: number
+  totalOutput: number
+  totalCacheRead: number
+  totalCacheWrite: number
+  totalMessages: number
+  totalCost: number
+  /** ISO 4217 code of the currency every cost is in */
+  currency: string
+  processingTimeMs: number
+}
+
+/** Project usage summary for reports */
+export interface ProjectUsage {
+  /** Project or working directory ("unknown" if the source does not record one) */
+  project: string
+  sources: Array<string>
+  models: Array<string>
+  input: number
+  output: number
+  cacheRead: number
+  cacheWrite: number
+  reasoning: number
+  messageCount: number
+  cost: number
+}
+
+/** Options for the per-session report */
+export interface SessionReportOptions {
+  /** Home directory path (defaults to user's home) */
+  homeDir?: string
+  /**
+   * Additional home directories, e.g. logs synced from containers or other
+   * machines. Scanned with each tool's default layout; environment overrides
+   * such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+   */
+  extraHomeDirs?: Array<string>
+  /**
+   * Directories to scan per source name, replacing that source's default
+   * locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+   */
+  sourcePaths?: Record<string, Array<string>>
+  /**
+   * Session archives (`.tar`, `.tar.gz`/`.tgz` or `.zip` snapshots of tool
+   * directories), read without extracting. Their messages are tagged with
+   * the archive's file name.
+   */
+  archives?: Array<string>
+  /** Sources to include (defaults to every registered source) */
+  sources?: Array<string>
+  /** Pricing data for cost calculation */
+  pricing: Array<PricingEntry>
+  /** Start date filter (YYYY-MM-DD) */
+  since?: string
+  /** End date filter (YYYY-MM-DD) */
+  until?: string
+  /** Filter to specific year */
+  year?: string
+  /**
+   * Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+   * Defaults to UTC; date filters apply to dates in this zone.
+   */
+  timezone?: string
+  /**
+   * Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+   * Defaults to USD; other currencies need an exchange-rate table.
+   */
+  currency?: string
+  /** Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json) */
+  exchangeRates?: string
+  /** Use the persistent parse cache (defaults to true) */
+  cache?: boolean
+  /** Sort key, descending: "cost" (default), "duration", "messages", "tokens" or "recent" */
+  sortBy?: string
+  /** Only return the first N sessions after sorting */
+  limit?: number
+  /** Drop sessions that cost less than this amount (in the report currency) */
+  minCost?: number
+}
+
+/** Session usage summary for reports */
+export interface SessionUsage {
+  source: string
+  sessionId: string
+  project?: string
+  /** Timestamp of the first message (Unix milliseconds) */
+  firstTimestamp: number
+  /** Timestamp of the last message (Unix milliseconds) */
+  lastTimestamp: number
+  /** Wall-clock time between the first and last message */
+  durationMs: number
+  models: Array<string>
+  input: number
+  output: number
+  cacheRead: number
+  cacheWrite: number
+  reasoning: number
+  messageCount: number
+  cost: number
+}
+
+/** Session report result */
+export interface SessionReport {
+  entries: Array<SessionUsage>
+  /** Models without pricing, by token volume (before the limit and minimum cost) */
+  unpricedModels: Array<UnpricedModel>
+  /** Number of sessions before the limit and minimum cost were applied */
+  totalSessions: number
+  totalMessages: number
+  totalCost: number
+  /** ISO 4217 code of the currency every cost is in */
+  currency: string
+  processingTimeMs: number
 }
 
 /** Monthly report result */
 export interface MonthlyReport {
   entries: Array<MonthlyUsage>
+  /** Models without pricing, by token volume */
+  unpricedModels: Array<UnpricedModel>
   totalCost: number
+  /** ISO 4217 code of the currency every cost is in */
+  currency: string
   processingTimeMs: number
 }
 
@@ -201,6 +516,14 @@ export interface ParsedMessage {
   cacheWrite: number
   reasoning: number
   agent?: string
+  project?: string
+  /** Home directory or source path the message was found under */
+  root?: string
+  /**
+   * Cost recorded by the tool itself (e.g. Amp credits), used for models
+   * without pricing data when the source's billing policy allows it
+   */
+  recordedCost?: number
 }
 
 /** Result of parsing local sources (excludes Cursor - it's network-synced) */
@@ -212,6 +535,12 @@ export interface ParsedMessages {
   geminiCount: number
   ampCount: number
   droidCount: number
+  /** Message counts keyed by source name (covers every registered source) */
+  sourceCounts?: Record<string, number>
+  /** Number of duplicate messages dropped (e.g. Claude responses logged more than once) */
+  duplicateCount?: number
+  /** Files that were skipped or only partially parsed */
+  diagnostics?: Array<ScanDiagnostic>
   processingTimeMs: number
 }
 
@@ -225,13 +554,55 @@ export declare function parseLocalSources(options: LocalParseOptions): ParsedMes
 export interface PricingEntry {
   modelId: string
   pricing: ModelPricing
+  /**
+   * Date these rates took effect (YYYY-MM-DD, UTC). Messages are priced with
+   * the latest dated entry on or before their timestamp; undated entries
+   * cover models without dated entries and messages before the first one.
+   */
+  effectiveFrom?: string
+}
+
+/** How a model id resolves against the pricing data */
+export interface PricingExplanation {
+  modelId: string
+  /** Pricing key the model resolved to (the pattern for pattern overrides) */
+  pricingKey?: string
+  /** "exact", "prefixed", "normalized", "fuzzy", "pattern" or "none" */
+  matchKind: string
+  /** "base", or the local override that set the rates */
+  pricingRule?: string
+  /** Base rates of the resolved entry (all 0 when unpriced) */
+  inputCostPerToken: number
+  outputCostPerToken: number
+  cacheReadInputTokenCost: number
+  cacheCreationInputTokenCost: number
+  outputCostPerReasoningToken: number
+  /** Prompt sizes above which long-context rates apply */
+  tierThresholds: Array<number>
 }
 
 /** Options for reports with pricing */
 export interface ReportOptions {
   /** Home directory path (defaults to user's home) */
   homeDir?: string
-  /** Sources to include: "opencode", "claude", "codex", "gemini", "cursor", "amp" */
+  /**
+   * Additional home directories, e.g. logs synced from containers or other
+   * machines. Scanned with each tool's default layout; environment overrides
+   * such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+   */
+  extraHomeDirs?: Array<string>
+  /**
+   * Directories to scan per source name, replacing that source's default
+   * locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+   */
+  sourcePaths?: Record<string, Array<string>>
+  /**
+   * Session archives (`.tar`, `.tar.gz`/`.tgz` or `.zip` snapshots of tool
+   * directories), read without extracting. Their messages are tagged with
+   * the archive's file name.
+   */
+  archives?: Array<string>
+  /** Sources to include: "opencode", "claude", "codex", "gemini", "cursor", "amp", "droid", "copilot", "qwen", "kilo", or a source defined in `~/.config/tokscale/sources.toml` */
   sources?: Array<string>
   /** Pricing data for cost calculation */
   pricing: Array<PricingEntry>
@@ -241,6 +612,35 @@ export interface ReportOptions {
   until?: string
   /** Filter to specific year */
   year?: string
+  /**
+   * Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+   * Defaults to UTC; date filters apply to dates in this zone.
+   */
+  timezone?: string
+  /**
+   * Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+   * Defaults to USD; other currencies need an exchange-rate table.
+   */
+  currency?: string
+  /** Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json) */
+  exchangeRates?: string
+  /** Use the persistent parse cache (defaults to true) */
+  cache?: boolean
+}
+
+/** A problem found while parsing a session file (see `sessions::Diagnostic`) */
+export interface ScanDiagnostic {
+  source: string
+  path: string
+  /** One of "io", "json", "malformed_lines", "unknown_schema", "config" */
+  kind: string
+  message: string
+  /** 1-based line number of the first bad line (line-based formats only) */
+  line?: number
+  /** Number of lines skipped (line-based formats only) */
+  skippedLines: number
+  /** Version marker found in a file with an unrecognised structure */
+  schemaVersion?: string
 }
 
 /** Scan for session files (for debugging/testing) */
@@ -256,6 +656,8 @@ export interface ScanStats {
   ampFiles: number
   droidFiles: number
   totalFiles: number
+  /** File counts keyed by source name (covers every registered source) */
+  sourceFiles: Record<string, number>
 }
 
 /** Source contribution for a specific day */
@@ -280,6 +682,52 @@ export interface TokenBreakdown {
 /** Version of the native module */
 export declare function version(): string
 
+/** Change in daily contributions caused by a batch of file changes */
+/** Model without pricing whose cost counts as 0 in a report */
+export interface UnpricedModel {
+  model: string
+  sources: Array<string>
+  tokens: TokenBreakdown
+  /** Sum of every token category */
+  totalTokens: number
+  messageCount: number
+}
+
+export interface WatchEvent {
+  /** Per-day deltas; usage from rewritten or deleted files shows up as negative values */
+  contributions: Array<DailyContribution>
+  /** Session files that were re-read */
+  changedFiles: Array<string>
+}
+
+/** Options for watching session files */
+export interface WatchOptions {
+  /** Home directory path (defaults to user's home) */
+  homeDir?: string
+  /**
+   * Additional home directories, e.g. logs synced from containers or other
+   * machines. Scanned with each tool's default layout; environment overrides
+   * such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+   */
+  extraHomeDirs?: Array<string>
+  /**
+   * Directories to scan per source name, replacing that source's default
+   * locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+   */
+  sourcePaths?: Record<string, Array<string>>
+  /** Sources to watch (defaults to every local source) */
+  sources?: Array<string>
+  /** Pricing data for cost calculation */
+  pricing: Array<PricingEntry>
+  /**
+   * Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+   * Defaults to UTC.
+   */
+  timezone?: string
+  /** Quiet period before a burst of file changes is processed (defaults to 200ms) */
+  debounceMs?: number
+}
+
 /** Year summary */
 export interface YearSummary {
   year: string
@@ -314,12 +314,20 @@ const {
   scanSessions,
   getModelReport,
   getMonthlyReport,
+  getProjectReport,
+  getSessionReport,
   generateGraphWithPricing,
   // Two-phase processing functions (parallel optimization)
   parseLocalSources,
   finalizeReport,
   finalizeMonthlyReport,
   finalizeGraph,
+  // Watch mode
+  UsageWatcher,
+  // Pricing loaded in Rust
+  PricingHandle,
+  loadPricingFile,
+  explainPricing,
 } = nativeBinding
 
 module.exports.version = version
@@ -328,9 +336,17 @@ module.exports.generateGraph = generateGraph
 module.exports.scanSessions = scanSessions
 module.exports.getModelReport = getModelReport
 module.exports.getMonthlyReport = getMonthlyReport
+module.exports.getProjectReport = getProjectReport
+module.exports.getSessionReport = getSessionReport
 module.exports.generateGraphWithPricing = generateGraphWithPricing
 // Two-phase processing functions (parallel optimization)
 module.exports.parseLocalSources = parseLocalSources
 module.exports.finalizeReport = finalizeReport
 module.exports.finalizeMonthlyReport = finalizeMonthlyReport
 module.exports.finalizeGraph = finalizeGraph
+// Watch mode
+module.exports.UsageWatcher = UsageWatcher
+// Pricing loaded in Rust
+module.exports.PricingHandle = PricingHandle
+module.exports.loadPricingFile = loadPricingFile
+module.exports.explainPricing = explainPricing
@@ -4,8 +4,8 @@
 
 use crate::sessions::UnifiedMessage;
 use crate::{
-    DailyContribution, DailyTotals, DataSummary, GraphMeta, GraphResult, SourceContribution,
-    TokenBreakdown, YearSummary,
+    DailyContribution, DailyTotals, DataSummary, GraphMeta, GraphResult, PricingData, ProjectUsage,
+    SessionUsage, SourceContribution, TokenBreakdown, UnpricedModel, YearSummary,
 };
 use rayon::prelude::*;
 use std::collections::HashMap;
@@ -17,7 +17,7 @@ pub fn aggregate_by_date(messages: Vec<UnifiedMessage>) -> Vec<DailyContribution
     }
 
     // Estimate unique days (typically 1-365) - use message count / 10 as heuristic
-    let estimated_days = (messages.len() / 10).max(30).min(400);
+    let estimated_days = (messages.len() / 10).clamp(30, 400);
 
     // Parallel aggregation using fold/reduce pattern
     let daily_map: HashMap<String, DayAccumulator> = messages
@@ -42,7 +42,11 @@ pub fn aggregate_by_date(messages: Vec<UnifiedMessage>) -> Vec<DailyContribution
 
     // Convert to sorted vector with pre-allocated capacity
     let mut contributions: Vec<DailyContribution> = Vec::with_capacity(daily_map.len());
-    contributions.extend(daily_map.into_iter().map(|(date, acc)| acc.into_contribution(date)));
+    contributions.extend(
+        daily_map
+            .into_iter()
+            .map(|(date, acc)| acc.into_contribution(date)),
+    );
 
     // Sort by date
     contributions.sort_by(|a, b| a.date.cmp(&b.date));
@@ -53,6 +57,61 @@ pub fn aggregate_by_date(messages: Vec<UnifiedMessage>) -> Vec<DailyContribution
     contributions
 }
 
+/// Net change in daily contributions from adding `added` and removing
+/// `removed` messages
+///
+/// Removals produce negative values. Days and source entries that net to
+/// zero are omitted, and intensities are left at 0 since a delta has no
+/// meaningful scale.
+pub fn aggregate_delta(
+    added: &[UnifiedMessage],
+    removed: &[UnifiedMessage],
+) -> Vec<DailyContribution> {
+    let accumulate = |messages: &[UnifiedMessage]| {
+        let mut days: HashMap<String, DayAccumulator> = HashMap::new();
+        for msg in messages {
+            days.entry(msg.date.clone()).or_default().add_message(msg);
+        }
+        days
+    };
+
+    let mut days = accumulate(added);
+    for (date, acc) in accumulate(removed) {
+        days.entry(date).or_default().merge(acc.negated());
+    }
+
+    let mut contributions: Vec<DailyContribution> = days
+        .into_iter()
+        .filter_map(|(date, mut acc)| {
+            acc.sources.retain(|_, source| {
+                source.messages != 0 || source.cost.abs() > 1e-12 || !is_zero(&source.tokens)
+            });
+            (!acc.sources.is_empty()).then(|| acc.into_contribution(date))
+        })
+        .collect();
+
+    contributions.sort_by(|a, b| a.date.cmp(&b.date));
+    contributions
+}
+
+fn is_zero(tokens: &TokenBreakdown) -> bool {
+    tokens.input == 0
+        && tokens.output == 0
+        && tokens.cache_read == 0
+        && tokens.cache_write == 0
+        && tokens.reasoning == 0
+}
+
+fn negate_tokens(tokens: &TokenBreakdown) -> TokenBreakdown {
+    TokenBreakdown {
+        input: -tokens.input,
+        output: -tokens.output,
+        cache_read: -tokens.cache_read,
+        cache_write: -tokens.cache_write,
+        reasoning: -tokens.reasoning,
+    }
+}
+
 /// Calculate summary statistics
 pub fn calculate_summary(contributions: &[DailyContribution]) -> DataSummary {
     let total_tokens: i64 = contributions.iter().map(|c| c.totals.tokens).sum();
@@ -144,13 +203,262 @@ pub fn generate_graph_result(
             date_range_start,
             date_range_end,
             processing_time_ms,
+            currency: crate::currency::BASE_CURRENCY.to_string(),
+            duplicate_count: 0,
         },
         summary,
         years,
         contributions,
+        unpriced_models: Vec::new(),
+        diagnostics: Vec::new(),
     }
 }
 
+/// Aggregate messages by project, sorted by cost descending
+pub fn aggregate_by_project(messages: Vec<UnifiedMessage>) -> Vec<ProjectUsage> {
+    let mut project_map: HashMap<String, ProjectAccumulator> = HashMap::new();
+
+    for msg in messages {
+        let project = msg.project.clone().unwrap_or_else(|| "unknown".to_string());
+        let entry = project_map.entry(project).or_default();
+
+        entry.sources.insert(msg.source.clone());
+        entry.models.insert(msg.model_id.clone());
+        entry.tokens.input = entry.tokens.input.saturating_add(msg.tokens.input);
+        entry.tokens.output = entry.tokens.output.saturating_add(msg.tokens.output);
+        entry.tokens.cache_read = entry
+            .tokens
+            .cache_read
+            .saturating_add(msg.tokens.cache_read);
+        entry.tokens.cache_write = entry
+            .tokens
+            .cache_write
+            .saturating_add(msg.tokens.cache_write);
+        entry.tokens.reasoning = entry.tokens.reasoning.saturating_add(msg.tokens.reasoning);
+        entry.message_count = entry.message_count.saturating_add(1);
+        entry.cost += msg.cost;
+    }
+
+    let mut entries: Vec<ProjectUsage> = project_map
+        .into_iter()
+        .map(|(project, acc)| {
+            let mut sources: Vec<String> = acc.sources.into_iter().collect();
+            sources.sort();
+            let mut models: Vec<String> = acc.models.into_iter().collect();
+            models.sort();
+            ProjectUsage {
+                project,
+                sources,
+                models,
+                input: acc.tokens.input,
+                output: acc.tokens.output,
+                cache_read: acc.tokens.cache_read,
+                cache_write: acc.tokens.cache_write,
+                reasoning: acc.tokens.reasoning,
+                message_count: acc.message_count,
+                cost: acc.cost,
+            }
+        })
+        .collect();
+
+    // Sort by cost descending (NaN values sorted to the end)
+    entries.sort_by(|a, b| match (a.cost.is_nan(), b.cost.is_nan()) {
+        (true, true) => std::cmp::Ordering::Equal,
+        (true, false) => std::cmp::Ordering::Greater,
+        (false, true) => std::cmp::Ordering::Less,
+        (false, false) => b
+            .cost
+            .partial_cmp(&a.cost)
+            .unwrap_or(std::cmp::Ordering::Equal),
+    });
+
+    entries
+}
+
+/// Models that no pricing matches and whose source recorded no cost either,
+/// sorted by token volume descending
+pub fn unpriced_models(messages: &[UnifiedMessage], pricing: &PricingData) -> Vec<UnpricedModel> {
+    let mut model_map: HashMap<&str, UnpricedAccumulator> = HashMap::new();
+
+    for msg in messages.iter().filter(|m| m.cost <= 0.0) {
+        let entry = model_map.entry(msg.model_id.as_str()).or_default();
+        entry.sources.insert(msg.source.clone());
+        add_tokens(&mut entry.tokens, &msg.tokens);
+        entry.message_count = entry.message_count.saturating_add(1);
+    }
+
+    let mut entries: Vec<UnpricedModel> = model_map
+        .into_iter()
+        .filter(|(model, _)| pricing.resolve(model).is_none())
+        .map(|(model, acc)| {
+            let mut sources: Vec<String> = acc.sources.into_iter().collect();
+            sources.sort();
+            let tokens = acc.tokens;
+            UnpricedModel {
+                model: model.to_string(),
+                sources,
+                total_tokens: tokens.input
+                    + tokens.output
+                    + tokens.cache_read
+                    + tokens.cache_write
+                    + tokens.reasoning,
+                tokens,
+                message_count: acc.message_count,
+            }
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        b.total_tokens
+            .cmp(&a.total_tokens)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    entries
+}
+
+fn add_tokens(total: &mut TokenBreakdown, tokens: &TokenBreakdown) {
+    total.input = total.input.saturating_add(tokens.input);
+    total.output = total.output.saturating_add(tokens.output);
+    total.cache_read = total.cache_read.saturating_add(tokens.cache_read);
+    total.cache_write = total.cache_write.saturating_add(tokens.cache_write);
+    total.reasoning = total.reasoning.saturating_add(tokens.reasoning);
+}
+
+/// Sort order for session reports (always descending)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionSort {
+    Cost,
+    Duration,
+    Messages,
+    Tokens,
+    Recent,
+}
+
+impl SessionSort {
+    pub fn parse(key: &str) -> Option<Self> {
+        match key {
+            "cost" => Some(Self::Cost),
+            "duration" => Some(Self::Duration),
+            "messages" => Some(Self::Messages),
+            "tokens" => Some(Self::Tokens),
+            "recent" => Some(Self::Recent),
+            _ => None,
+        }
+    }
+}
+
+/// Aggregate messages by (source, session_id), sorted by cost descending
+pub fn aggregate_by_session(messages: Vec<UnifiedMessage>) -> Vec<SessionUsage> {
+    let mut session_map: HashMap<(String, String), SessionAccumulator> = HashMap::new();
+
+    for msg in messages {
+        let key = (msg.source.clone(), msg.session_id.clone());
+        let entry = session_map
+            .entry(key)
+            .or_insert_with(|| SessionAccumulator {
+                first_timestamp: i64::MAX,
+                last_timestamp: i64::MIN,
+                ..Default::default()
+            });
+
+        entry.first_timestamp = entry.first_timestamp.min(msg.timestamp);
+        entry.last_timestamp = entry.last_timestamp.max(msg.timestamp);
+        if entry.project.is_none() {
+            entry.project = msg.project.clone();
+        }
+        entry.models.insert(msg.model_id.clone());
+        entry.tokens.input = entry.tokens.input.saturating_add(msg.tokens.input);
+        entry.tokens.output = entry.tokens.output.saturating_add(msg.tokens.output);
+        entry.tokens.cache_read = entry
+            .tokens
+            .cache_read
+            .saturating_add(msg.tokens.cache_read);
+        entry.tokens.cache_write = entry
+            .tokens
+            .cache_write
+            .saturating_add(msg.tokens.cache_write);
+        entry.tokens.reasoning = entry.tokens.reasoning.saturating_add(msg.tokens.reasoning);
+        entry.message_count = entry.message_count.saturating_add(1);
+        entry.cost += msg.cost;
+    }
+
+    let mut entries: Vec<SessionUsage> = session_map
+        .into_iter()
+        .map(|((source, session_id), acc)| {
+            let mut models: Vec<String> = acc.models.into_iter().collect();
+            models.sort();
+            SessionUsage {
+                source,
+                session_id,
+                project: acc.project,
+                first_timestamp: acc.first_timestamp,
+                last_timestamp: acc.last_timestamp,
+                duration_ms: acc.last_timestamp.saturating_sub(acc.first_timestamp),
+                models,
+                input: acc.tokens.input,
+                output: acc.tokens.output,
+                cache_read: acc.tokens.cache_read,
+                cache_write: acc.tokens.cache_write,
+                reasoning: acc.tokens.reasoning,
+                message_count: acc.message_count,
+                cost: acc.cost,
+            }
+        })
+        .collect();
+
+    sort_sessions(&mut entries, SessionSort::Cost);
+    entries
+}
+
+/// Apply the minimum cost filter, sort order and top-N limit to session rows
+pub fn select_sessions(
+    entries: &mut Vec<SessionUsage>,
+    sort: SessionSort,
+    limit: Option<usize>,
+    min_cost: Option<f64>,
+) {
+    if let Some(min_cost) = min_cost {
+        entries.retain(|e| e.cost >= min_cost);
+    }
+    sort_sessions(entries, sort);
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+    }
+}
+
+fn sort_sessions(entries: &mut [SessionUsage], sort: SessionSort) {
+    let total_tokens = |e: &SessionUsage| {
+        e.input
+            .saturating_add(e.output)
+            .saturating_add(e.cache_read)
+            .saturating_add(e.cache_write)
+            .saturating_add(e.reasoning)
+    };
+
+    entries.sort_by(|a, b| {
+        let order = match sort {
+            // NaN values sorted to the end
+            SessionSort::Cost => match (a.cost.is_nan(), b.cost.is_nan()) {
+                (true, true) => std::cmp::Ordering::Equal,
+                (true, false) => std::cmp::Ordering::Greater,
+                (false, true) => std::cmp::Ordering::Less,
+                (false, false) => b
+                    .cost
+                    .partial_cmp(&a.cost)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            },
+            SessionSort::Duration => b.duration_ms.cmp(&a.duration_ms),
+            SessionSort::Messages => b.message_count.cmp(&a.message_count),
+            SessionSort::Tokens => total_tokens(b).cmp(&total_tokens(a)),
+            SessionSort::Recent => b.last_timestamp.cmp(&a.last_timestamp),
+        };
+        // Stable tie-break so results are deterministic
+        order
+            .then_with(|| a.source.cmp(&b.source))
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+}
+
 // =============================================================================
 // Internal helpers
 // =============================================================================
@@ -173,7 +481,9 @@ impl Default for DayAccumulator {
 
 impl DayAccumulator {
     fn add_message(&mut self, msg: &UnifiedMessage) {
-        let total_tokens = msg.tokens.input
+        let total_tokens = msg
+            .tokens
+            .input
             .saturating_add(msg.tokens.output)
             .saturating_add(msg.tokens.cache_read)
             .saturating_add(msg.tokens.cache_write)
@@ -184,10 +494,22 @@ impl DayAccumulator {
         self.totals.messages = self.totals.messages.saturating_add(1);
 
         self.token_breakdown.input = self.token_breakdown.input.saturating_add(msg.tokens.input);
-        self.token_breakdown.output = self.token_breakdown.output.saturating_add(msg.tokens.output);
-        self.token_breakdown.cache_read = self.token_breakdown.cache_read.saturating_add(msg.tokens.cache_read);
-        self.token_breakdown.cache_write = self.token_breakdown.cache_write.saturating_add(msg.tokens.cache_write);
-        self.token_breakdown.reasoning = self.token_breakdown.reasoning.saturating_add(msg.tokens.reasoning);
+        self.token_breakdown.output = self
+            .token_breakdown
+            .output
+            .saturating_add(msg.tokens.output);
+        self.token_breakdown.cache_read = self
+            .token_breakdown
+            .cache_read
+            .saturating_add(msg.tokens.cache_read);
+        self.token_breakdown.cache_write = self
+            .token_breakdown
+            .cache_write
+            .saturating_add(msg.tokens.cache_write);
+        self.token_breakdown.reasoning = self
+            .token_breakdown
+            .reasoning
+            .saturating_add(msg.tokens.reasoning);
 
         // Update source contribution
         let key = format!("{}:{}", msg.source, msg.model_id);
@@ -205,8 +527,14 @@ impl DayAccumulator {
 
         source.tokens.input = source.tokens.input.saturating_add(msg.tokens.input);
         source.tokens.output = source.tokens.output.saturating_add(msg.tokens.output);
-        source.tokens.cache_read = source.tokens.cache_read.saturating_add(msg.tokens.cache_read);
-        source.tokens.cache_write = source.tokens.cache_write.saturating_add(msg.tokens.cache_write);
+        source.tokens.cache_read = source
+            .tokens
+            .cache_read
+            .saturating_add(msg.tokens.cache_read);
+        source.tokens.cache_write = source
+            .tokens
+            .cache_write
+            .saturating_add(msg.tokens.cache_write);
         source.tokens.reasoning = source.tokens.reasoning.saturating_add(msg.tokens.reasoning);
         source.cost += msg.cost;
         source.messages = source.messages.saturating_add(1);
@@ -217,11 +545,26 @@ impl DayAccumulator {
         self.totals.cost += other.totals.cost;
         self.totals.messages = self.totals.messages.saturating_add(other.totals.messages);
 
-        self.token_breakdown.input = self.token_breakdown.input.saturating_add(other.token_breakdown.input);
-        self.token_breakdown.output = self.token_breakdown.output.saturating_add(other.token_breakdown.output);
-        self.token_breakdown.cache_read = self.token_breakdown.cache_read.saturating_add(other.token_breakdown.cache_read);
-        self.token_breakdown.cache_write = self.token_breakdown.cache_write.saturating_add(other.token_breakdown.cache_write);
-        self.token_breakdown.reasoning = self.token_breakdown.reasoning.saturating_add(other.token_breakdown.reasoning);
+        self.token_breakdown.input = self
+            .token_breakdown
+            .input
+            .saturating_add(other.token_breakdown.input);
+        self.token_breakdown.output = self
+            .token_breakdown
+            .output
+            .saturating_add(other.token_breakdown.output);
+        self.token_breakdown.cache_read = self
+            .token_breakdown
+            .cache_read
+            .saturating_add(other.token_breakdown.cache_read);
+        self.token_breakdown.cache_write = self
+            .token_breakdown
+            .cache_write
+            .saturating_add(other.token_breakdown.cache_write);
+        self.token_breakdown.reasoning = self
+            .token_breakdown
+            .reasoning
+            .saturating_add(other.token_breakdown.reasoning);
 
         for (key, source) in other.sources {
             let entry = self
@@ -238,14 +581,36 @@ impl DayAccumulator {
 
             entry.tokens.input = entry.tokens.input.saturating_add(source.tokens.input);
             entry.tokens.output = entry.tokens.output.saturating_add(source.tokens.output);
-            entry.tokens.cache_read = entry.tokens.cache_read.saturating_add(source.tokens.cache_read);
-            entry.tokens.cache_write = entry.tokens.cache_write.saturating_add(source.tokens.cache_write);
-            entry.tokens.reasoning = entry.tokens.reasoning.saturating_add(source.tokens.reasoning);
+            entry.tokens.cache_read = entry
+                .tokens
+                .cache_read
+                .saturating_add(source.tokens.cache_read);
+            entry.tokens.cache_write = entry
+                .tokens
+                .cache_write
+                .saturating_add(source.tokens.cache_write);
+            entry.tokens.reasoning = entry
+                .tokens
+                .reasoning
+                .saturating_add(source.tokens.reasoning);
             entry.cost += source.cost;
             entry.messages = entry.messages.saturating_add(source.messages);
         }
     }
 
+    fn negated(mut self) -> Self {
+        self.totals.tokens = -self.totals.tokens;
+        self.totals.cost = -self.totals.cost;
+        self.totals.messages = -self.totals.messages;
+        self.token_breakdown = negate_tokens(&self.token_breakdown);
+        for source in self.sources.values_mut() {
+            source.tokens = negate_tokens(&source.tokens);
+            source.cost = -source.cost;
+            source.messages = -source.messages;
+        }
+        self
+    }
+
     fn into_contribution(self, date: String) -> DailyContribution {
         DailyContribution {
             date,
@@ -257,6 +622,33 @@ impl DayAccumulator {
     }
 }
 
+#[derive(Default)]
+struct ProjectAccumulator {
+    sources: std::collections::HashSet<String>,
+    models: std::collections::HashSet<String>,
+    tokens: TokenBreakdown,
+    message_count: i32,
+    cost: f64,
+}
+
+#[derive(Default)]
+struct UnpricedAccumulator {
+    sources: std::collections::HashSet<String>,
+    tokens: TokenBreakdown,
+    message_count: i32,
+}
+
+#[derive(Default)]
+struct SessionAccumulator {
+    first_timestamp: i64,
+    last_timestamp: i64,
+    project: Option<String>,
+    models: std::collections::HashSet<String>,
+    tokens: TokenBreakdown,
+    message_count: i32,
+    cost: f64,
+}
+
 #[derive(Default)]
 struct YearAccumulator {
     tokens: i64,
@@ -290,3 +682,149 @@ fn calculate_intensities(contributions: &mut [DailyContribution]) {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(project: Option<&str>, model: &str, cost: f64) -> UnifiedMessage {
+        session_message("ses_1", 1_750_000_000_000, model, cost)
+            .with_project(project.map(String::from))
+    }
+
+    fn session_message(session_id: &str, timestamp: i64, model: &str, cost: f64) -> UnifiedMessage {
+        UnifiedMessage::new(
+            "claude",
+            model,
+            "anthropic",
+            session_id,
+            timestamp,
+            TokenBreakdown {
+                input: 100,
+                output: 50,
+                cache_read: 0,
+                cache_write: 0,
+                reasoning: 0,
+            },
+            cost,
+        )
+    }
+
+    #[test]
+    fn test_unpriced_models() {
+        let mut pricing = PricingData::new();
+        pricing.add_model(
+            "claude-sonnet-4".to_string(),
+            crate::pricing::ModelPricing::default(),
+        );
+        pricing.finalize();
+
+        let mut codex = message(None, "local-llm", 0.0);
+        codex.source = "codex".to_string();
+        let unpriced = unpriced_models(
+            &[
+                // Priced, even though the rates are 0
+                message(None, "claude-sonnet-4", 0.0),
+                message(None, "local-llm", 0.0),
+                codex,
+                message(None, "tiny-llm", 0.0),
+                // The source recorded a cost
+                message(None, "recorded-llm", 0.5),
+            ],
+            &pricing,
+        );
+
+        let models: Vec<&str> = unpriced.iter().map(|u| u.model.as_str()).collect();
+        assert_eq!(models, ["local-llm", "tiny-llm"]);
+        assert_eq!(unpriced[0].sources, ["claude", "codex"]);
+        assert_eq!(unpriced[0].message_count, 2);
+        assert_eq!(unpriced[0].tokens.input, 200);
+        assert_eq!(unpriced[0].total_tokens, 300);
+        assert_eq!(unpriced[1].total_tokens, 150);
+    }
+
+    #[test]
+    fn test_aggregate_by_project() {
+        let entries = aggregate_by_project(vec![
+            message(Some("/work/a"), "claude-sonnet-4", 1.0),
+            message(Some("/work/a"), "claude-opus-4", 2.0),
+            message(Some("/work/b"), "claude-sonnet-4", 5.0),
+            message(None, "claude-sonnet-4", 0.5),
+        ]);
+
+        let projects: Vec<&str> = entries.iter().map(|e| e.project.as_str()).collect();
+        assert_eq!(projects, vec!["/work/b", "/work/a", "unknown"]);
+        assert_eq!(entries[1].message_count, 2);
+        assert_eq!(entries[1].input, 200);
+        assert_eq!(entries[1].models, vec!["claude-opus-4", "claude-sonnet-4"]);
+        assert!((entries[1].cost - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_aggregate_by_session() {
+        let entries = aggregate_by_session(vec![
+            session_message("ses_a", 1_000, "claude-sonnet-4", 1.0),
+            session_message("ses_a", 61_000, "claude-opus-4", 2.0),
+            session_message("ses_b", 5_000, "claude-sonnet-4", 10.0),
+        ]);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].session_id, "ses_b");
+        let a = &entries[1];
+        assert_eq!(a.first_timestamp, 1_000);
+        assert_eq!(a.last_timestamp, 61_000);
+        assert_eq!(a.duration_ms, 60_000);
+        assert_eq!(a.message_count, 2);
+        assert_eq!(a.models, vec!["claude-opus-4", "claude-sonnet-4"]);
+    }
+
+    #[test]
+    fn test_select_sessions() {
+        let mut entries = aggregate_by_session(vec![
+            session_message("ses_a", 1_000, "claude-sonnet-4", 1.0),
+            session_message("ses_a", 61_000, "claude-sonnet-4", 2.0),
+            session_message("ses_b", 5_000, "claude-sonnet-4", 60.0),
+            session_message("ses_c", 9_000, "claude-sonnet-4", 0.1),
+        ]);
+
+        let mut by_duration = entries.clone();
+        select_sessions(&mut by_duration, SessionSort::Duration, Some(1), None);
+        assert_eq!(by_duration.len(), 1);
+        assert_eq!(by_duration[0].session_id, "ses_a");
+
+        select_sessions(&mut entries, SessionSort::Cost, None, Some(50.0));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].session_id, "ses_b");
+
+        assert_eq!(SessionSort::parse("recent"), Some(SessionSort::Recent));
+        assert_eq!(SessionSort::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_aggregate_delta_nets_added_and_removed() {
+        // An updated message replaces its earlier, partial version
+        let partial = session_message("ses_1", 1_750_000_000_000, "claude-sonnet-4", 0.01);
+        let mut complete = partial.clone();
+        complete.tokens.output = 80;
+        complete.cost = 0.03;
+        let other_day = session_message("ses_1", 1_750_100_000_000, "claude-sonnet-4", 0.02);
+
+        let delta = aggregate_delta(&[complete, other_day], &[partial]);
+        assert_eq!(delta.len(), 2);
+
+        let updated = &delta[0];
+        assert_eq!(updated.totals.messages, 0);
+        assert_eq!(updated.totals.tokens, 30);
+        assert!((updated.totals.cost - 0.02).abs() < 1e-9);
+        assert_eq!(updated.sources.len(), 1);
+        assert_eq!(updated.sources[0].tokens.output, 30);
+
+        assert_eq!(delta[1].totals.messages, 1);
+        assert_eq!(delta[1].intensity, 0);
+
+        // Identical additions and removals cancel out
+        let msg = session_message("ses_1", 1_750_000_000_000, "claude-sonnet-4", 0.01);
+        let same = std::slice::from_ref(&msg);
+        assert!(aggregate_delta(same, same).is_empty());
+    }
+}
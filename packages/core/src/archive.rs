@@ -0,0 +1,347 @@
+//! Session archives
+//!
+//! Reads session logs from `.tar`, `.tar.gz`/`.tgz` and `.zip` snapshots of
+//! tool directories (e.g. `.claude` and `.codex` collected from CI runners)
+//! without extracting them. An entry belongs to a source when it sits below
+//! the source's default layout (`.claude/projects/`, `.codex/sessions/`, ...)
+//! at any depth and its file name matches the source's pattern, the same
+//! rules `scanner::scan_directory` applies on disk.
+
+use crate::scanner::matches_pattern;
+use crate::sessions::{Diagnostic, ParsedFile, SessionSource};
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Archive formats, told apart by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("Unsupported archive '{0}' (expected .tar, .tar.gz, .tgz or .zip)")]
+    UnsupportedFormat(String),
+
+    #[error("Failed to read archive {path}: {message}")]
+    Read { path: String, message: String },
+}
+
+impl ArchiveError {
+    fn read(path: &Path, error: impl std::fmt::Display) -> Self {
+        Self::Read {
+            path: path.display().to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// A session file read from an archive
+#[derive(Debug)]
+pub struct ArchiveFile {
+    pub source: String,
+    /// The archive's path joined with the entry's path, for diagnostics
+    pub path: PathBuf,
+    /// The archive's file name, given to its messages as their root
+    pub tag: String,
+    pub parsed: ParsedFile,
+}
+
+/// Parse the session files of the given sources from each archive, reading
+/// archives in parallel
+pub fn parse_archives(
+    archives: &[String],
+    sources: &[Arc<dyn SessionSource>],
+) -> Result<Vec<ArchiveFile>, ArchiveError> {
+    let results: Vec<Result<Vec<ArchiveFile>, ArchiveError>> = archives
+        .par_iter()
+        .map(|archive| parse_archive(Path::new(archive), sources))
+        .collect();
+
+    let mut files = Vec::new();
+    for result in results {
+        files.extend(result?);
+    }
+    Ok(files)
+}
+
+/// Parse the entries of one archive that belong to one of `sources`
+pub fn parse_archive(
+    path: &Path,
+    sources: &[Arc<dyn SessionSource>],
+) -> Result<Vec<ArchiveFile>, ArchiveError> {
+    let format = ArchiveFormat::from_path(path)
+        .ok_or_else(|| ArchiveError::UnsupportedFormat(path.display().to_string()))?;
+    let tag = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let layouts: Vec<(&dyn SessionSource, Vec<String>)> = sources
+        .iter()
+        .map(|source| (source.as_ref(), source_layouts(source.as_ref())))
+        .collect();
+
+    let mut files = Vec::new();
+    let mut visit = |entry_path: &str, reader: &mut dyn Read| -> Result<(), ArchiveError> {
+        let Some(source) = match_entry(entry_path, &layouts) else {
+            return Ok(());
+        };
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .map_err(|e| ArchiveError::read(path, e))?;
+
+        let parsed = source
+            .parse_bytes(Path::new(entry_path), &data)
+            .unwrap_or_else(|| {
+                ParsedFile::failed(Diagnostic::io(format!(
+                    "{} sessions cannot be read from an archive",
+                    source.name()
+                )))
+            });
+        files.push(ArchiveFile {
+            source: source.name().to_string(),
+            path: path.join(entry_path),
+            tag: tag.clone(),
+            parsed,
+        });
+        Ok(())
+    };
+
+    let file = File::open(path).map_err(|e| ArchiveError::read(path, e))?;
+    match format {
+        ArchiveFormat::Tar => walk_tar(path, BufReader::new(file), &mut visit)?,
+        ArchiveFormat::TarGz => walk_tar(
+            path,
+            flate2::read::GzDecoder::new(BufReader::new(file)),
+            &mut visit,
+        )?,
+        ArchiveFormat::Zip => walk_zip(path, BufReader::new(file), &mut visit)?,
+    }
+    Ok(files)
+}
+
+type Visit<'a> = dyn FnMut(&str, &mut dyn Read) -> Result<(), ArchiveError> + 'a;
+
+fn walk_tar(path: &Path, reader: impl Read, visit: &mut Visit) -> Result<(), ArchiveError> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(|e| ArchiveError::read(path, e))? {
+        let mut entry = entry.map_err(|e| ArchiveError::read(path, e))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let entry_path = entry
+            .path()
+            .map_err(|e| ArchiveError::read(path, e))?
+            .to_string_lossy()
+            .to_string();
+        visit(&entry_path, &mut entry)?;
+    }
+    Ok(())
+}
+
+fn walk_zip(
+    path: &Path,
+    reader: impl Read + std::io::Seek,
+    visit: &mut Visit,
+) -> Result<(), ArchiveError> {
+    let mut archive = zip::ZipArchive::new(reader).map_err(|e| ArchiveError::read(path, e))?;
+    for idx in 0..archive.len() {
+        let mut entry = archive
+            .by_index(idx)
+            .map_err(|e| ArchiveError::read(path, e))?;
+        if !entry.is_file() {
+            continue;
+        }
+        let entry_path = entry.name().to_string();
+        visit(&entry_path, &mut entry)?;
+    }
+    Ok(())
+}
+
+/// A source's default directories relative to a home directory, as
+/// `/.claude/projects/`
+fn source_layouts(source: &dyn SessionSource) -> Vec<String> {
+    source
+        .roots("")
+        .into_iter()
+        .map(|root| format!("/{}/", root.trim_matches('/')))
+        .collect()
+}
+
+/// The first source whose layout contains the entry and whose pattern
+/// matches its file name
+fn match_entry<'a>(
+    entry_path: &str,
+    layouts: &[(&'a dyn SessionSource, Vec<String>)],
+) -> Option<&'a dyn SessionSource> {
+    let normalized = format!(
+        "/{}",
+        entry_path.trim_start_matches("./").replace('\\', "/")
+    );
+    let file_name = normalized.rsplit('/').next().unwrap_or("");
+
+    layouts
+        .iter()
+        .find(|(source, roots)| {
+            matches_pattern(file_name, source.pattern())
+                && source.accepts(Path::new(&normalized))
+                && roots.iter().any(|root| normalized.contains(root.as_str()))
+        })
+        .map(|(source, _)| *source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::{all_sources, SourceRegistry};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    const CLAUDE_LINE: &str = r#"{"type":"assistant","timestamp":"2025-06-15T12:00:01Z","requestId":"req_1","message":{"id":"msg_1","model":"claude-sonnet-4-20250514","usage":{"input_tokens":10,"output_tokens":20}}}"#;
+    const CODEX_LINES: &str = concat!(
+        r#"{"type":"turn_context","timestamp":"2025-06-15T12:00:00Z","payload":{"model":"gpt-5-codex"}}"#,
+        "\n",
+        r#"{"type":"event_msg","timestamp":"2025-06-15T12:00:02Z","payload":{"type":"token_count","info":{"last_token_usage":{"input_tokens":100,"cached_input_tokens":40,"output_tokens":30,"reasoning_output_tokens":0,"total_tokens":130}}}}"#,
+        "\n"
+    );
+
+    fn entries() -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "runner-1/.claude/projects/-home-ci-app/session-a.jsonl",
+                format!("{}\n", CLAUDE_LINE),
+            ),
+            (
+                "./.codex/sessions/2025/06/15/rollout-b.jsonl",
+                CODEX_LINES.to_string(),
+            ),
+            // Outside any source layout
+            ("notes/session-c.jsonl", format!("{}\n", CLAUDE_LINE)),
+        ]
+    }
+
+    fn write_tar_gz(path: &Path) {
+        let encoder =
+            flate2::write::GzEncoder::new(File::create(path).unwrap(), Default::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in entries() {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn write_zip(path: &Path) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries() {
+            writer
+                .start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn assert_parsed(path: &Path, files: &[ArchiveFile]) {
+        assert_eq!(files.len(), 2);
+        let claude = files.iter().find(|f| f.source == "claude").unwrap();
+        assert_eq!(claude.tag, path.file_name().unwrap().to_str().unwrap());
+        assert!(claude.path.starts_with(path));
+        assert_eq!(claude.parsed.messages.len(), 1);
+        let msg = &claude.parsed.messages[0];
+        assert_eq!(msg.session_id, "session-a");
+        assert_eq!(msg.project.as_deref(), Some("-home-ci-app"));
+        assert_eq!(msg.tokens.output, 20);
+
+        let codex = files.iter().find(|f| f.source == "codex").unwrap();
+        assert_eq!(codex.parsed.messages.len(), 1);
+        assert_eq!(codex.parsed.messages[0].model_id, "gpt-5-codex");
+        assert_eq!(codex.parsed.messages[0].tokens.cache_read, 40);
+    }
+
+    #[test]
+    fn test_parse_tar_gz_archive() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("runner-1.tar.gz");
+        write_tar_gz(&path);
+
+        let files = parse_archive(&path, &all_sources()).unwrap();
+        assert_parsed(&path, &files);
+    }
+
+    #[test]
+    fn test_parse_zip_archive() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("runner-1.zip");
+        write_zip(&path);
+
+        let files = parse_archive(&path, &all_sources()).unwrap();
+        assert_parsed(&path, &files);
+
+        let codex = SourceRegistry::default().select(&["codex".to_string()]);
+        let only_codex = parse_archives(&[path.to_string_lossy().to_string()], &codex).unwrap();
+        assert_eq!(only_codex.len(), 1);
+        assert_eq!(only_codex[0].source, "codex");
+    }
+
+    #[test]
+    fn test_parse_archive_errors() {
+        let dir = TempDir::new().unwrap();
+        let unsupported = dir.path().join("logs.rar");
+        assert!(matches!(
+            parse_archive(&unsupported, &all_sources()),
+            Err(ArchiveError::UnsupportedFormat(_))
+        ));
+
+        let corrupt = dir.path().join("logs.zip");
+        std::fs::write(&corrupt, b"not a zip").unwrap();
+        assert!(matches!(
+            parse_archive(&corrupt, &all_sources()),
+            Err(ArchiveError::Read { .. })
+        ));
+    }
+
+    #[test]
+    fn test_match_entry_uses_layout_and_pattern() {
+        let sources = all_sources();
+        let layouts: Vec<_> = sources
+            .iter()
+            .map(|source| (source.as_ref(), source_layouts(source.as_ref())))
+            .collect();
+        let name = |entry: &str| match_entry(entry, &layouts).map(|s| s.name());
+
+        assert_eq!(name(".gemini/tmp/abc/chats/session-1.json"), Some("gemini"));
+        assert_eq!(
+            name("home/dev/.local/share/amp/threads/T-1.json"),
+            Some("amp")
+        );
+        assert_eq!(name(".gemini/tmp/abc/chats/other.json"), None);
+        assert_eq!(name("projects/session.jsonl"), None);
+    }
+}
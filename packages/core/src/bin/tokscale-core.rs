@@ -0,0 +1,726 @@
+//! Standalone command-line interface for tokscale-core
+//!
+//! Runs the same scans and reports as the NAPI exports without Node or Bun and
+//! prints them as tables or JSON. Build with
+//! `cargo build --no-default-features --features cli`.
+
+#[cfg(feature = "napi")]
+compile_error!(
+    "the tokscale-core binary cannot link the Node.js bindings; build it with --no-default-features --features cli"
+);
+
+use anyhow::Context;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tokscale_core::{
+    ModelUsage, PricingData, PricingOverrides, PricingSnapshots, ReportOptions,
+    SessionReportOptions, UnpricedModel,
+};
+
+fn cli() -> Command {
+    Command::new("tokscale-core")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Token usage reports for AI coding assistant sessions")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .args(global_args())
+        .subcommand(Command::new("scan").about("Count session files per source"))
+        .subcommand(Command::new("models").about("Usage and cost per model"))
+        .subcommand(Command::new("monthly").about("Usage and cost per month"))
+        .subcommand(Command::new("graph").about("Daily contributions"))
+        .subcommand(
+            Command::new("explain")
+                .about("Show how a model id resolves to pricing")
+                .arg(Arg::new("model").value_name("MODEL").required(true)),
+        )
+        .subcommand(
+            Command::new("sessions")
+                .about("Usage and cost per session")
+                .arg(
+                    Arg::new("sort")
+                        .long("sort")
+                        .value_name("KEY")
+                        .value_parser(["cost", "duration", "messages", "tokens", "recent"])
+                        .help("Sort key, descending (default: cost)"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(value_parser!(u32))
+                        .help("Only show the first N sessions"),
+                )
+                .arg(
+                    Arg::new("min-cost")
+                        .long("min-cost")
+                        .value_name("AMOUNT")
+                        .value_parser(value_parser!(f64))
+                        .help("Hide sessions that cost less than this"),
+                ),
+        )
+}
+
+fn global_args() -> Vec<Arg> {
+    let value = |name: &'static str, value_name: &'static str, help: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .value_name(value_name)
+            .help(help)
+            .global(true)
+    };
+    let flag = |name: &'static str, help: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .action(ArgAction::SetTrue)
+            .help(help)
+            .global(true)
+    };
+
+    vec![
+        value("home", "DIR", "Home directory to scan (default: $HOME)"),
+        value(
+            "extra-home",
+            "DIR",
+            "Also scan this home directory, e.g. logs synced from another machine (repeatable)",
+        )
+        .action(ArgAction::Append),
+        value(
+            "source-path",
+            "SOURCE=DIR",
+            "Scan DIR instead of the default locations of SOURCE (repeatable)",
+        )
+        .action(ArgAction::Append)
+        .value_parser(parse_source_path),
+        value(
+            "archive",
+            "FILE",
+            "Also read sessions from a .tar, .tar.gz or .zip archive without extracting it (repeatable)",
+        )
+        .action(ArgAction::Append),
+        value(
+            "source",
+            "NAME",
+            "Only include these sources (repeatable or comma-separated)",
+        )
+        .action(ArgAction::Append)
+        .value_delimiter(','),
+        value("since", "DATE", "Start date (YYYY-MM-DD)"),
+        value("until", "DATE", "End date (YYYY-MM-DD)"),
+        value("year", "YEAR", "Only include this year"),
+        value(
+            "timezone",
+            "ZONE",
+            "IANA name or UTC offset for day bucketing (default: UTC)",
+        ),
+        value(
+            "currency",
+            "CODE",
+            "Show costs in this currency, e.g. KRW or EUR (default: USD)",
+        ),
+        value(
+            "exchange-rates",
+            "FILE",
+            "Exchange-rate table (default: ~/.config/tokscale/exchange-rates.json)",
+        ),
+        value(
+            "pricing",
+            "FILE",
+            "LiteLLM model_prices JSON (default: the TypeScript CLI's cached copy)",
+        )
+        .value_parser(value_parser!(PathBuf)),
+        value(
+            "pricing-overrides",
+            "FILE",
+            "Local pricing overrides, TOML or JSON (default: ~/.config/tokscale/pricing.toml)",
+        )
+        .value_parser(value_parser!(PathBuf)),
+        value(
+            "pricing-history",
+            "FILE",
+            "Dated pricing snapshots (default: ~/.config/tokscale/pricing-history.json)",
+        )
+        .value_parser(value_parser!(PathBuf)),
+        flag("json", "Print JSON instead of a table"),
+        flag("no-cache", "Ignore the persistent parse cache"),
+    ]
+}
+
+fn main() -> ExitCode {
+    match run(&cli().get_matches()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(matches: &ArgMatches) -> anyhow::Result<()> {
+    let (command, args) = matches.subcommand().expect("subcommand is required");
+    let json = args.get_flag("json");
+
+    if command == "scan" {
+        let stats = tokscale_core::scan_sessions(string_arg(args, "home"), sources_arg(args))?;
+        if json {
+            return print_json(&stats);
+        }
+        let mut table = Table::new(&["Source", "Files"]);
+        let mut sources: Vec<_> = stats.source_files.iter().collect();
+        sources.sort();
+        for (source, files) in sources {
+            table.row(vec![source.clone(), format_int(*files as i64)]);
+        }
+        table.total(vec!["Total".into(), format_int(stats.total_files as i64)]);
+        table.print();
+        return Ok(());
+    }
+
+    let options = report_options(args);
+    let pricing = load_pricing(args)?;
+    match command {
+        "explain" => {
+            let model = args.get_one::<String>("model").expect("model is required");
+            let explanation = tokscale_core::explain_pricing(model, &pricing);
+            if json {
+                return print_json(&explanation);
+            }
+            let Some(key) = &explanation.pricing_key else {
+                println!("{}: no pricing matched", model);
+                return Ok(());
+            };
+            let rule = explanation.pricing_rule.unwrap_or_default();
+            let thresholds: Vec<String> = explanation
+                .tier_thresholds
+                .iter()
+                .map(|t| format_int(*t))
+                .collect();
+            let mut lines = vec![
+                ("Model", model.clone()),
+                (
+                    "Pricing key",
+                    format!("{} ({})", key, explanation.match_kind),
+                ),
+                ("Rule", rule),
+                ("Input", format_rate(explanation.input_cost_per_token)),
+                ("Output", format_rate(explanation.output_cost_per_token)),
+                (
+                    "Cache read",
+                    format_rate(explanation.cache_read_input_token_cost),
+                ),
+                (
+                    "Cache write",
+                    format_rate(explanation.cache_creation_input_token_cost),
+                ),
+                (
+                    "Reasoning",
+                    format_rate(explanation.output_cost_per_reasoning_token),
+                ),
+            ];
+            if !thresholds.is_empty() {
+                lines.push(("Long context above", thresholds.join(", ")));
+            }
+            for (label, value) in lines {
+                println!("{:<20}{}", format!("{}:", label), value);
+            }
+        }
+        "models" => {
+            let report = tokscale_core::get_model_report_with_pricing_data(options, &pricing)?;
+            if json {
+                return print_json(&report);
+            }
+            let mut table = Table::new(&[
+                "Source",
+                "Model",
+                "Input",
+                "Output",
+                "Cache Read",
+                "Cache Write",
+                "Reasoning",
+                "Messages",
+                "Cost",
+                "Pricing",
+            ]);
+            for e in &report.entries {
+                table.row(vec![
+                    e.source.clone(),
+                    e.model.clone(),
+                    format_int(e.input),
+                    format_int(e.output),
+                    format_int(e.cache_read),
+                    format_int(e.cache_write),
+                    format_int(e.reasoning),
+                    format_int(e.message_count as i64),
+                    format_cost(e.cost, &report.currency),
+                    pricing_label(e),
+                ]);
+            }
+            table.total(vec![
+                "Total".into(),
+                String::new(),
+                format_int(report.total_input),
+                format_int(report.total_output),
+                format_int(report.total_cache_read),
+                format_int(report.total_cache_write),
+                String::new(),
+                format_int(report.total_messages as i64),
+                format_cost(report.total_cost, &report.currency),
+                String::new(),
+            ]);
+            table.print();
+            warn_unpriced(&report.unpriced_models);
+        }
+        "monthly" => {
+            let report = tokscale_core::get_monthly_report_with_pricing_data(options, &pricing)?;
+            if json {
+                return print_json(&report);
+            }
+            let mut table = Table::new(&[
+                "Month",
+                "Models",
+                "Input",
+                "Output",
+                "Cache Read",
+                "Cache Write",
+                "Messages",
+                "Cost",
+            ]);
+            for e in &report.entries {
+                table.row(vec![
+                    e.month.clone(),
+                    e.models.len().to_string(),
+                    format_int(e.input),
+                    format_int(e.output),
+                    format_int(e.cache_read),
+                    format_int(e.cache_write),
+                    format_int(e.message_count as i64),
+                    format_cost(e.cost, &report.currency),
+                ]);
+            }
+            let mut total = vec![String::new(); 8];
+            total[0] = "Total".into();
+            total[7] = format_cost(report.total_cost, &report.currency);
+            table.total(total);
+            table.print();
+            warn_unpriced(&report.unpriced_models);
+        }
+        "graph" => {
+            let graph = tokscale_core::generate_graph_with_pricing_data(options, &pricing)?;
+            if json {
+                return print_json(&graph);
+            }
+            let mut table = Table::new(&["Date", "Tokens", "Messages", "Cost"]);
+            for day in &graph.contributions {
+                table.row(vec![
+                    day.date.clone(),
+                    format_int(day.totals.tokens),
+                    format_int(day.totals.messages as i64),
+                    format_cost(day.totals.cost, &graph.meta.currency),
+                ]);
+            }
+            table.total(vec![
+                format!("Total ({} days)", graph.summary.active_days),
+                format_int(graph.summary.total_tokens),
+                String::new(),
+                format_cost(graph.summary.total_cost, &graph.meta.currency),
+            ]);
+            table.print();
+            if !graph.diagnostics.is_empty() {
+                eprintln!(
+                    "{} files were skipped or only partially parsed (use --json for details)",
+                    graph.diagnostics.len()
+                );
+            }
+        }
+        "sessions" => {
+            let options = SessionReportOptions {
+                home_dir: options.home_dir,
+                extra_home_dirs: options.extra_home_dirs,
+                source_paths: options.source_paths,
+                archives: options.archives,
+                sources: options.sources,
+                pricing: options.pricing,
+                since: options.since,
+                until: options.until,
+                year: options.year,
+                timezone: options.timezone,
+                currency: options.currency,
+                exchange_rates: options.exchange_rates,
+                cache: options.cache,
+                sort_by: string_arg(args, "sort"),
+                limit: args.get_one::<u32>("limit").copied(),
+                min_cost: args.get_one::<f64>("min-cost").copied(),
+            };
+            let report = tokscale_core::get_session_report_with_pricing_data(options, &pricing)?;
+            if json {
+                return print_json(&report);
+            }
+            let mut table = Table::new(&[
+                "Source", "Session", "Project", "Duration", "Messages", "Tokens", "Cost",
+            ]);
+            for e in &report.entries {
+                let tokens = e.input + e.output + e.cache_read + e.cache_write + e.reasoning;
+                table.row(vec![
+                    e.source.clone(),
+                    e.session_id.clone(),
+                    e.project.clone().unwrap_or_default(),
+                    format_duration(e.duration_ms),
+                    format_int(e.message_count as i64),
+                    format_int(tokens),
+                    format_cost(e.cost, &report.currency),
+                ]);
+            }
+            table.total(vec![
+                format!("Total ({} sessions)", report.total_sessions),
+                String::new(),
+                String::new(),
+                String::new(),
+                format_int(report.total_messages as i64),
+                String::new(),
+                format_cost(report.total_cost, &report.currency),
+            ]);
+            table.print();
+            warn_unpriced(&report.unpriced_models);
+        }
+        _ => unreachable!("unknown subcommand {}", command),
+    }
+
+    Ok(())
+}
+
+fn report_options(args: &ArgMatches) -> ReportOptions {
+    ReportOptions {
+        home_dir: string_arg(args, "home"),
+        extra_home_dirs: args
+            .get_many::<String>("extra-home")
+            .map(|values| values.cloned().collect()),
+        source_paths: source_paths_arg(args),
+        archives: args
+            .get_many::<String>("archive")
+            .map(|values| values.cloned().collect()),
+        sources: sources_arg(args),
+        pricing: Vec::new(),
+        since: string_arg(args, "since"),
+        until: string_arg(args, "until"),
+        year: string_arg(args, "year"),
+        timezone: string_arg(args, "timezone"),
+        currency: string_arg(args, "currency"),
+        exchange_rates: string_arg(args, "exchange-rates"),
+        cache: Some(!args.get_flag("no-cache")),
+    }
+}
+
+fn string_arg(args: &ArgMatches, name: &str) -> Option<String> {
+    args.get_one::<String>(name).cloned()
+}
+
+/// Parse a `--source-path` value of the form `SOURCE=DIR`
+fn parse_source_path(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((source, dir)) if !source.is_empty() && !dir.is_empty() => {
+            Ok((source.to_string(), dir.to_string()))
+        }
+        _ => Err(format!("expected SOURCE=DIR, got '{}'", value)),
+    }
+}
+
+fn source_paths_arg(args: &ArgMatches) -> Option<HashMap<String, Vec<String>>> {
+    let values = args.get_many::<(String, String)>("source-path")?;
+    let mut paths: HashMap<String, Vec<String>> = HashMap::new();
+    for (source, dir) in values {
+        paths.entry(source.clone()).or_default().push(dir.clone());
+    }
+    Some(paths)
+}
+
+fn sources_arg(args: &ArgMatches) -> Option<Vec<String>> {
+    args.get_many::<String>("source")
+        .map(|values| values.cloned().collect())
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+// =============================================================================
+// Pricing
+// =============================================================================
+
+/// The `--pricing` file, or the pricing cache written by the TypeScript CLI
+fn pricing_path(args: &ArgMatches) -> Option<PathBuf> {
+    if let Some(path) = args.get_one::<PathBuf>("pricing") {
+        return Some(path.clone());
+    }
+
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    let cached = cache_home.join("tokscale").join("pricing.json");
+    cached.is_file().then_some(cached)
+}
+
+/// The `--pricing-overrides` file, or the overrides file in the config directory
+fn overrides_path(args: &ArgMatches) -> Option<PathBuf> {
+    if let Some(path) = args.get_one::<PathBuf>("pricing-overrides") {
+        return Some(path.clone());
+    }
+    PricingOverrides::default_path(home_arg(args)?)
+}
+
+/// The `--pricing-history` file, or the snapshot file in the config directory
+fn history_path(args: &ArgMatches) -> Option<PathBuf> {
+    if let Some(path) = args.get_one::<PathBuf>("pricing-history") {
+        return Some(path.clone());
+    }
+    PricingSnapshots::default_path(home_arg(args)?)
+}
+
+fn home_arg(args: &ArgMatches) -> Option<String> {
+    string_arg(args, "home").or_else(|| std::env::var("HOME").ok())
+}
+
+fn load_pricing(args: &ArgMatches) -> anyhow::Result<PricingData> {
+    let overrides = overrides_path(args);
+    let history = history_path(args);
+    let mut pricing = match pricing_path(args) {
+        Some(path) => PricingData::from_litellm_json(path)?,
+        None => {
+            if overrides.is_none() && history.is_none() {
+                eprintln!("warning: no pricing file found; pass --pricing to calculate costs");
+            }
+            PricingData::default()
+        }
+    };
+    if let Some(path) = history {
+        pricing
+            .add_snapshots_file(&path)
+            .with_context(|| format!("pricing history {}", path.display()))?;
+    }
+    if let Some(path) = overrides {
+        pricing
+            .apply_overrides_file(&path)
+            .with_context(|| format!("pricing overrides {}", path.display()))?;
+    }
+    Ok(pricing)
+}
+
+/// How a model was priced: the matched key for base pricing, otherwise the override rule
+fn pricing_label(usage: &ModelUsage) -> String {
+    match (&usage.pricing_rule, &usage.pricing_key) {
+        (Some(rule), Some(key)) if rule == "base" => format!("{} {}", usage.match_kind, key),
+        (Some(rule), _) => rule.clone(),
+        (None, _) => "unpriced".to_string(),
+    }
+}
+
+fn warn_unpriced(unpriced: &[UnpricedModel]) {
+    if unpriced.is_empty() {
+        return;
+    }
+    let models: Vec<String> = unpriced
+        .iter()
+        .map(|u| format!("{} ({} tokens)", u.model, format_int(u.total_tokens)))
+        .collect();
+    eprintln!(
+        "warning: no pricing for {} model(s), counted as $0: {}",
+        unpriced.len(),
+        models.join(", ")
+    );
+}
+
+// =============================================================================
+// Table output
+// =============================================================================
+
+/// Plain-text table; the first column is left-aligned, the rest right-aligned
+struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    total: Option<Vec<String>>,
+}
+
+impl Table {
+    fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+            total: None,
+        }
+    }
+
+    fn row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// Row printed below a separator after all other rows
+    fn total(&mut self, cells: Vec<String>) {
+        self.total = Some(cells);
+    }
+
+    fn render(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in self.rows.iter().chain(self.total.iter()) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let line = |cells: &[String]| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(i, (cell, width))| {
+                    if i == 0 {
+                        format!("{:<width$}", cell, width = width)
+                    } else {
+                        format!("{:>width$}", cell, width = width)
+                    }
+                })
+                .collect();
+            padded.join("  ").trim_end().to_string()
+        };
+        let separator = widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        let mut out = vec![line(&self.headers), separator.clone()];
+        out.extend(self.rows.iter().map(|row| line(row)));
+        if let Some(total) = &self.total {
+            out.push(separator);
+            out.push(line(total));
+        }
+        out.join("\n")
+    }
+
+    fn print(&self) {
+        println!("{}", self.render());
+    }
+}
+
+/// Integer with thousands separators (1234567 -> "1,234,567")
+fn format_int(value: i64) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if value < 0 {
+        out.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Cost in the report currency: "$1.50" for USD, whole units for currencies
+/// without minor units ("1,380 KRW"), otherwise two decimals ("1.38 EUR")
+fn format_cost(cost: f64, currency: &str) -> String {
+    match currency {
+        "USD" => format!("${:.2}", cost),
+        "JPY" | "KRW" => format!("{} {}", format_int(cost.round() as i64), currency),
+        _ => format!("{:.2} {}", cost, currency),
+    }
+}
+
+/// Per-token rate as dollars per million tokens
+fn format_rate(rate: f64) -> String {
+    let per_million = (rate * 1e6 * 1e6).round() / 1e6;
+    format!("${} / 1M tokens", per_million)
+}
+
+fn format_duration(duration_ms: i64) -> String {
+    let minutes = duration_ms / 60_000;
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        cli().debug_assert();
+
+        let matches = cli()
+            .try_get_matches_from([
+                "tokscale-core",
+                "sessions",
+                "--source",
+                "claude,codex",
+                "--limit",
+                "5",
+                "--source-path",
+                "claude=/mnt/a",
+                "--source-path",
+                "claude=/mnt/b",
+                "--archive",
+                "runner-1.tar.gz",
+            ])
+            .unwrap();
+        let (_, args) = matches.subcommand().unwrap();
+        assert_eq!(
+            sources_arg(args),
+            Some(vec!["claude".to_string(), "codex".to_string()])
+        );
+        assert_eq!(args.get_one::<u32>("limit"), Some(&5));
+        assert_eq!(
+            source_paths_arg(args),
+            Some(HashMap::from([(
+                "claude".to_string(),
+                vec!["/mnt/a".to_string(), "/mnt/b".to_string()]
+            )]))
+        );
+        assert_eq!(
+            report_options(args).archives,
+            Some(vec!["runner-1.tar.gz".to_string()])
+        );
+        assert!(cli()
+            .try_get_matches_from(["tokscale-core", "models", "--source-path", "/mnt/a"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_table_render_aligns_columns() {
+        let mut table = Table::new(&["Model", "Cost"]);
+        table.row(vec!["gpt-4o".into(), format_cost(1.5, "USD")]);
+        table.row(vec!["claude-sonnet-4".into(), format_cost(12.345, "USD")]);
+        table.total(vec!["Total".into(), format_cost(13.845, "USD")]);
+
+        assert_eq!(
+            table.render(),
+            [
+                "Model              Cost",
+                "---------------  ------",
+                "gpt-4o            $1.50",
+                "claude-sonnet-4  $12.35",
+                "---------------  ------",
+                "Total            $13.85",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_format_helpers() {
+        assert_eq!(format_int(0), "0");
+        assert_eq!(format_int(999), "999");
+        assert_eq!(format_int(1_234_567), "1,234,567");
+        assert_eq!(format_int(-12_345), "-12,345");
+        assert_eq!(format_duration(90_000), "1m");
+        assert_eq!(format_duration(3_900_000), "1h 05m");
+        assert_eq!(format_cost(1380.4, "KRW"), "1,380 KRW");
+        assert_eq!(format_cost(1.375, "EUR"), "1.38 EUR");
+        assert_eq!(format_rate(3e-6), "$3 / 1M tokens");
+        assert_eq!(format_rate(7.5e-8), "$0.075 / 1M tokens");
+    }
+}
@@ -0,0 +1,271 @@
+//! Node.js bindings
+//!
+//! Thin NAPI adapters over the crate's Rust API: each export forwards to the
+//! function of the same name and converts its `Error` into a JS exception.
+//! Functions that price messages take an optional `PricingHandle`, which
+//! replaces the `pricing` entries in their options.
+
+// napi-derive does not register exports in test builds
+#![cfg_attr(test, allow(dead_code))]
+
+use crate::{DailyContribution, PricingData, WatchHandle};
+use crate::{
+    FinalizeGraphOptions, FinalizeMonthlyOptions, FinalizeReportOptions, GraphOptions, GraphResult,
+    LocalParseOptions, ModelReport, MonthlyReport, ParsedMessages, PricingExplanation,
+    ProjectReport, ReportOptions, ScanStats, SessionReport, SessionReportOptions, WatchEvent,
+    WatchOptions,
+};
+use napi::bindgen_prelude::{ClassInstance, Unknown};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::Status;
+use napi_derive::napi;
+
+impl From<crate::Error> for napi::Error {
+    fn from(error: crate::Error) -> Self {
+        napi::Error::from_reason(error.to_string())
+    }
+}
+
+/// Pricing data loaded once in Rust and reused across calls. Pass it as the
+/// `pricing` argument of the report functions instead of `options.pricing`.
+#[napi]
+pub struct PricingHandle {
+    data: PricingData,
+}
+
+#[napi]
+impl PricingHandle {
+    /// Number of models with pricing
+    #[napi]
+    pub fn model_count(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    /// Merge a local pricing overrides file (TOML, or JSON for `.json` paths)
+    #[napi]
+    pub fn apply_overrides(&mut self, path: String) -> napi::Result<()> {
+        self.data
+            .apply_overrides_file(path)
+            .map_err(crate::Error::from)?;
+        Ok(())
+    }
+
+    /// Add the dated rates of a pricing snapshot file
+    #[napi]
+    pub fn add_snapshots(&mut self, path: String) -> napi::Result<()> {
+        self.data
+            .add_snapshots_file(path)
+            .map_err(crate::Error::from)?;
+        Ok(())
+    }
+
+    /// Which rule prices a model ("base", or the matching override), if any
+    #[napi]
+    pub fn pricing_rule(&self, model_id: String) -> Option<String> {
+        self.data.pricing_rule(&model_id)
+    }
+}
+
+/// Load LiteLLM's `model_prices_and_context_window.json` (or the CLI's cached copy of it)
+#[napi]
+pub fn load_pricing_file(path: String) -> napi::Result<PricingHandle> {
+    let data = PricingData::from_litellm_json(path).map_err(crate::Error::from)?;
+    Ok(PricingHandle { data })
+}
+
+/// Explain how a model id resolves to pricing: the matched key, the kind of
+/// match, the override rule and the resulting rates
+#[napi]
+pub fn explain_pricing(
+    model_id: String,
+    pricing: ClassInstance<PricingHandle>,
+) -> PricingExplanation {
+    crate::explain_pricing(&model_id, &pricing.data)
+}
+
+/// Version of the native module
+#[napi]
+pub fn version() -> String {
+    crate::version()
+}
+
+/// Simple health check to verify the native module is working
+#[napi]
+pub fn health_check() -> String {
+    "tokscale-core is healthy!".to_string()
+}
+
+/// Generate graph data from all session sources
+///
+/// This is the main entry point that orchestrates:
+/// 1. Parallel file scanning
+/// 2. Parallel session parsing
+/// 3. Date filtering
+/// 4. Parallel aggregation
+#[napi]
+pub fn generate_graph(options: GraphOptions) -> napi::Result<GraphResult> {
+    Ok(crate::generate_graph(options)?)
+}
+
+/// Scan for session files (for debugging/testing)
+#[napi]
+pub fn scan_sessions(
+    home_dir: Option<String>,
+    sources: Option<Vec<String>>,
+) -> napi::Result<ScanStats> {
+    Ok(crate::scan_sessions(home_dir, sources)?)
+}
+
+/// Get model usage report with pricing calculation
+#[napi]
+pub fn get_model_report(
+    options: ReportOptions,
+    pricing: Option<ClassInstance<PricingHandle>>,
+) -> napi::Result<ModelReport> {
+    Ok(match pricing {
+        Some(handle) => crate::get_model_report_with_pricing_data(options, &handle.data)?,
+        None => crate::get_model_report(options)?,
+    })
+}
+
+/// Get per-project usage report with pricing calculation
+#[napi]
+pub fn get_project_report(
+    options: ReportOptions,
+    pricing: Option<ClassInstance<PricingHandle>>,
+) -> napi::Result<ProjectReport> {
+    Ok(match pricing {
+        Some(handle) => crate::get_project_report_with_pricing_data(options, &handle.data)?,
+        None => crate::get_project_report(options)?,
+    })
+}
+
+/// Get per-session usage report with pricing calculation
+#[napi]
+pub fn get_session_report(
+    options: SessionReportOptions,
+    pricing: Option<ClassInstance<PricingHandle>>,
+) -> napi::Result<SessionReport> {
+    Ok(match pricing {
+        Some(handle) => crate::get_session_report_with_pricing_data(options, &handle.data)?,
+        None => crate::get_session_report(options)?,
+    })
+}
+
+/// Get monthly usage report with pricing calculation
+#[napi]
+pub fn get_monthly_report(
+    options: ReportOptions,
+    pricing: Option<ClassInstance<PricingHandle>>,
+) -> napi::Result<MonthlyReport> {
+    Ok(match pricing {
+        Some(handle) => crate::get_monthly_report_with_pricing_data(options, &handle.data)?,
+        None => crate::get_monthly_report(options)?,
+    })
+}
+
+/// Generate graph data with pricing calculation
+#[napi]
+pub fn generate_graph_with_pricing(
+    options: ReportOptions,
+    pricing: Option<ClassInstance<PricingHandle>>,
+) -> napi::Result<GraphResult> {
+    Ok(match pricing {
+        Some(handle) => crate::generate_graph_with_pricing_data(options, &handle.data)?,
+        None => crate::generate_graph_with_pricing(options)?,
+    })
+}
+
+/// Parse local sources only (OpenCode, Claude, Codex, Gemini - NO Cursor)
+/// This can run in parallel with network operations (Cursor sync, pricing fetch)
+#[napi]
+pub fn parse_local_sources(options: LocalParseOptions) -> napi::Result<ParsedMessages> {
+    Ok(crate::parse_local_sources(options)?)
+}
+
+/// Finalize model report: apply pricing to local messages, add Cursor, aggregate
+#[napi]
+pub fn finalize_report(
+    options: FinalizeReportOptions,
+    pricing: Option<ClassInstance<PricingHandle>>,
+) -> napi::Result<ModelReport> {
+    Ok(match pricing {
+        Some(handle) => crate::finalize_report_with_pricing_data(options, &handle.data)?,
+        None => crate::finalize_report(options)?,
+    })
+}
+
+/// Finalize monthly report with pricing
+#[napi]
+pub fn finalize_monthly_report(
+    options: FinalizeMonthlyOptions,
+    pricing: Option<ClassInstance<PricingHandle>>,
+) -> napi::Result<MonthlyReport> {
+    Ok(match pricing {
+        Some(handle) => crate::finalize_monthly_report_with_pricing_data(options, &handle.data)?,
+        None => crate::finalize_monthly_report(options)?,
+    })
+}
+
+/// Finalize graph with pricing
+#[napi]
+pub fn finalize_graph(
+    options: FinalizeGraphOptions,
+    pricing: Option<ClassInstance<PricingHandle>>,
+) -> napi::Result<GraphResult> {
+    Ok(match pricing {
+        Some(handle) => crate::finalize_graph_with_pricing_data(options, &handle.data)?,
+        None => crate::finalize_graph(options)?,
+    })
+}
+
+type WatchCallback = ThreadsafeFunction<WatchEvent, Unknown<'static>, WatchEvent, Status, false>;
+
+/// Watches session files and reports usage deltas as they are written
+#[napi]
+pub struct UsageWatcher {
+    handle: Option<WatchHandle>,
+}
+
+#[napi]
+impl UsageWatcher {
+    /// Parse all session files, then call `callback` with a `WatchEvent` whenever
+    /// files change. Call `close()` to stop watching.
+    #[napi(constructor)]
+    pub fn new(
+        options: WatchOptions,
+        callback: WatchCallback,
+        pricing: Option<ClassInstance<PricingHandle>>,
+    ) -> napi::Result<Self> {
+        let on_update = move |event| {
+            callback.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+        };
+        let handle = match pricing {
+            Some(pricing) => {
+                crate::watch_with_pricing_data(options, pricing.data.clone(), on_update)?
+            }
+            None => crate::watch(options, on_update)?,
+        };
+
+        Ok(Self {
+            handle: Some(handle),
+        })
+    }
+
+    /// Daily contributions of everything seen so far
+    #[napi]
+    pub fn contributions(&self) -> Vec<DailyContribution> {
+        self.handle
+            .as_ref()
+            .map(|handle| handle.contributions())
+            .unwrap_or_default()
+    }
+
+    /// Stop watching session files
+    #[napi]
+    pub fn close(&mut self) {
+        if let Some(mut handle) = self.handle.take() {
+            handle.close();
+        }
+    }
+}
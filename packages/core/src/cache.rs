@@ -0,0 +1,356 @@
+//! Persistent parse cache
+//!
+//! Stores the messages parsed from each session file, keyed by path, size and
+//! modification time, so unchanged files are not re-read on the next run.
+//! Append-only JSONL sources (Claude, Codex) resume from the byte offset where
+//! the previous parse stopped instead of starting over.
+
+use crate::sessions::{
+    timestamp_to_date, Diagnostic, ParsedFile, ResumeState, SessionSource, UnifiedMessage,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Bump when the cache layout or parser output changes incompatibly
+const CACHE_FORMAT: u32 = 5;
+
+const CACHE_FILE_NAME: &str = "parse-cache.json";
+
+/// Cached parse result for a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub source: String,
+    /// `SessionSource::parser_version` of the parser that produced the entry
+    #[serde(default)]
+    pub parser_version: u64,
+    pub size: u64,
+    pub mtime_ms: i64,
+    /// Present for sources that can resume parsing from an offset
+    pub resume: Option<ResumeState>,
+    pub messages: Vec<UnifiedMessage>,
+    /// Problems found while parsing, replayed on cache hits
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: String,
+    entries: HashMap<String, CacheEntry>,
+}
+
+/// On-disk cache of parsed session files
+#[derive(Debug)]
+pub struct ParseCache {
+    path: PathBuf,
+    entries: HashMap<String, CacheEntry>,
+    /// Entries re-parsed during this run, merged into `entries` on save
+    updates: Mutex<HashMap<String, CacheEntry>>,
+}
+
+/// Version stamp written to the cache file. Includes the crate version so a
+/// new release never reuses results produced by an older parser.
+fn cache_version() -> String {
+    format!("{}-{}", CACHE_FORMAT, env!("CARGO_PKG_VERSION"))
+}
+
+/// Cache directory: `$XDG_CACHE_HOME/tokscale` or `{home}/.cache/tokscale`
+pub fn cache_dir(home_dir: &str) -> PathBuf {
+    let base = std::env::var("XDG_CACHE_HOME").unwrap_or_else(|_| format!("{}/.cache", home_dir));
+    PathBuf::from(base).join("tokscale")
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, i64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    let mtime_ms = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    Some((meta.len(), mtime_ms))
+}
+
+impl ParseCache {
+    /// Load the cache for a home directory. A missing, unreadable or
+    /// outdated cache file yields an empty cache.
+    pub fn load(home_dir: &str) -> Self {
+        Self::load_from(cache_dir(home_dir).join(CACHE_FILE_NAME))
+    }
+
+    /// Load the cache from an explicit file path
+    pub fn load_from(path: PathBuf) -> Self {
+        let mut entries = std::fs::read(&path)
+            .ok()
+            .and_then(|mut data| simd_json::from_slice::<CacheFile>(&mut data).ok())
+            .filter(|file| file.version == cache_version())
+            .map(|file| file.entries)
+            .unwrap_or_default();
+
+        for entry in entries.values_mut() {
+            restore_derived_fields(&mut entry.messages);
+        }
+
+        Self {
+            path,
+            entries,
+            updates: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Parse a file through the cache
+    ///
+    /// Unchanged files return their cached messages and diagnostics. Files
+    /// from resumable sources that were only appended to are parsed from the
+    /// previous offset; anything else is parsed from scratch.
+    pub fn parse_file(&self, source: &dyn SessionSource, path: &Path) -> ParsedFile {
+        let (size, mtime_ms) = match file_stamp(path) {
+            Some(stamp) => stamp,
+            None => return source.parse_file(path),
+        };
+        let key = path.to_string_lossy().to_string();
+
+        let cached = self.entries.get(&key).filter(|entry| {
+            entry.source == source.name() && entry.parser_version == source.parser_version()
+        });
+
+        if let Some(entry) = cached {
+            if entry.size == size && entry.mtime_ms == mtime_ms {
+                return ParsedFile {
+                    messages: entry.messages.clone(),
+                    diagnostics: entry.diagnostics.clone(),
+                };
+            }
+
+            // Appended to since the last run: parse only the new lines
+            let resume = entry
+                .resume
+                .as_ref()
+                .filter(|resume| size > entry.size && resume.matches(path));
+            if let Some(resume) = resume {
+                if let Some((appended, resume)) = source.parse_file_from(path, resume) {
+                    let mut parsed = ParsedFile {
+                        messages: entry.messages.clone(),
+                        diagnostics: entry.diagnostics.clone(),
+                    };
+                    parsed.extend(appended);
+                    self.record(key, source, size, mtime_ms, Some(resume), &parsed);
+                    return parsed;
+                }
+            }
+        }
+
+        let (parsed, resume) = match source.parse_file_from(path, &ResumeState::default()) {
+            Some((parsed, resume)) => (parsed, Some(resume)),
+            None => (source.parse_file(path), None),
+        };
+        self.record(key, source, size, mtime_ms, resume, &parsed);
+        parsed
+    }
+
+    fn record(
+        &self,
+        key: String,
+        source: &dyn SessionSource,
+        size: u64,
+        mtime_ms: i64,
+        resume: Option<ResumeState>,
+        parsed: &ParsedFile,
+    ) {
+        let entry = CacheEntry {
+            source: source.name().to_string(),
+            parser_version: source.parser_version(),
+            size,
+            mtime_ms,
+            resume,
+            messages: parsed.messages.clone(),
+            diagnostics: parsed.diagnostics.clone(),
+        };
+        if let Ok(mut updates) = self.updates.lock() {
+            updates.insert(key, entry);
+        }
+    }
+
+    /// Write the cache back to disk if anything changed.
+    /// Entries for files that no longer exist are dropped.
+    pub fn save(self) -> std::io::Result<()> {
+        let updates = self.updates.into_inner().unwrap_or_default();
+        let mut entries = self.entries;
+        let before = entries.len();
+        entries.retain(|path, _| Path::new(path).exists());
+
+        if updates.is_empty() && entries.len() == before {
+            return Ok(());
+        }
+        entries.extend(updates);
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = CacheFile {
+            version: cache_version(),
+            entries,
+        };
+        let data = serde_json::to_vec(&file).map_err(std::io::Error::other)?;
+
+        // Write to a temporary file first so a crash never leaves a torn cache
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, &self.path)
+    }
+}
+
+/// Restore fields that are derived rather than stored
+fn restore_derived_fields(messages: &mut [UnifiedMessage]) {
+    for msg in messages {
+        if msg.date.is_empty() {
+            msg.date = timestamp_to_date(msg.timestamp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::SourceRegistry;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn claude_line(id: &str, timestamp: &str) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{}","requestId":"req_{}","message":{{"id":"{}","model":"claude-sonnet-4","usage":{{"input_tokens":10,"output_tokens":20}}}}}}"#,
+            timestamp, id, id
+        )
+    }
+
+    #[test]
+    fn test_cache_reuses_and_resumes() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join("cache").join(CACHE_FILE_NAME);
+        let session = dir.path().join("session.jsonl");
+        let claude = SourceRegistry::default().find("claude").unwrap();
+
+        let mut file = std::fs::File::create(&session).unwrap();
+        writeln!(file, "{}", claude_line("msg_1", "2025-06-15T12:00:00Z")).unwrap();
+        file.flush().unwrap();
+
+        let cache = ParseCache::load_from(cache_path.clone());
+        let first = cache.parse_file(claude.as_ref(), &session);
+        assert_eq!(first.messages.len(), 1);
+        cache.save().unwrap();
+        assert!(cache_path.exists());
+
+        // Unchanged file comes straight from the cache, with its date restored
+        let cache = ParseCache::load_from(cache_path.clone());
+        assert_eq!(cache.entries.len(), 1);
+        let cached = cache.parse_file(claude.as_ref(), &session);
+        assert_eq!(cached.messages.len(), 1);
+        assert_eq!(cached.messages[0].date, "2025-06-15");
+
+        // Appended lines are parsed from the stored offset
+        writeln!(file, r#"{{"usage": not json"#).unwrap();
+        writeln!(file, "{}", claude_line("msg_2", "2025-06-16T12:00:00Z")).unwrap();
+        file.flush().unwrap();
+        let resumed = cache.parse_file(claude.as_ref(), &session);
+        assert_eq!(resumed.messages.len(), 2);
+        assert_eq!(
+            resumed.messages[1].dedup_key.as_deref(),
+            Some("msg_2:req_msg_2")
+        );
+        assert_eq!(
+            resumed.diagnostics,
+            vec![Diagnostic::malformed_lines(1, Some(2))]
+        );
+        cache.save().unwrap();
+
+        // Diagnostics are replayed from the cache
+        let cache = ParseCache::load_from(cache_path);
+        assert_eq!(
+            cache.parse_file(claude.as_ref(), &session).diagnostics,
+            vec![Diagnostic::malformed_lines(1, Some(2))]
+        );
+        let entry = cache.entries.values().next().unwrap();
+        assert_eq!(entry.messages.len(), 2);
+        assert_eq!(
+            entry.resume.as_ref().unwrap().offset,
+            std::fs::metadata(&session).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_cache_reparses_rewritten_files() {
+        let dir = TempDir::new().unwrap();
+        let session = dir.path().join("session.jsonl");
+        let claude = SourceRegistry::default().find("claude").unwrap();
+        std::fs::write(
+            &session,
+            claude_line("msg_1", "2025-06-15T12:00:00Z") + "\n",
+        )
+        .unwrap();
+
+        let cache_path = dir.path().join(CACHE_FILE_NAME);
+        let cache = ParseCache::load_from(cache_path.clone());
+        let first = cache.parse_file(claude.as_ref(), &session);
+        assert_eq!(first.messages.len(), 1);
+        cache.save().unwrap();
+
+        // Larger than before, but the parsed bytes changed, so it is not resumed
+        std::fs::write(
+            &session,
+            format!(
+                "{}\n{}\n",
+                claude_line("msg_3", "2025-06-17T12:00:00Z"),
+                claude_line("msg_2", "2025-06-16T12:00:00Z")
+            ),
+        )
+        .unwrap();
+        let cache = ParseCache::load_from(cache_path);
+        let parsed = cache.parse_file(claude.as_ref(), &session);
+        let keys: Vec<_> = parsed
+            .messages
+            .iter()
+            .map(|msg| msg.dedup_key.as_deref().unwrap())
+            .collect();
+        assert_eq!(keys, vec!["msg_3:req_msg_3", "msg_2:req_msg_2"]);
+    }
+
+    #[test]
+    fn test_cache_ignores_other_versions() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join(CACHE_FILE_NAME);
+        std::fs::write(
+            &cache_path,
+            r#"{"version":"0-old","entries":{"/x":{"source":"claude","size":1,"mtime_ms":1,"resume":null,"messages":[]}}}"#,
+        )
+        .unwrap();
+
+        let cache = ParseCache::load_from(cache_path);
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn test_cache_drops_deleted_files() {
+        let dir = TempDir::new().unwrap();
+        let cache_path = dir.path().join(CACHE_FILE_NAME);
+        let session = dir.path().join("gone.jsonl");
+        std::fs::write(
+            &session,
+            claude_line("msg_1", "2025-06-15T12:00:00Z") + "\n",
+        )
+        .unwrap();
+
+        let cache = ParseCache::load_from(cache_path.clone());
+        cache.parse_file(
+            SourceRegistry::default().find("claude").unwrap().as_ref(),
+            &session,
+        );
+        cache.save().unwrap();
+
+        std::fs::remove_file(&session).unwrap();
+        ParseCache::load_from(cache_path.clone()).save().unwrap();
+        assert!(ParseCache::load_from(cache_path).entries.is_empty());
+    }
+}
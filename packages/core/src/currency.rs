@@ -0,0 +1,327 @@
+//! Display currency for costs
+//!
+//! Costs are calculated in USD. A currency option (ISO 4217 code such as
+//! "KRW") converts every message's cost with a local exchange-rate table, so
+//! reports, monthly totals and graphs all use the same currency. The table is
+//! a static JSON file of units per US dollar, optionally dated per month:
+//!
+//! ```json
+//! {
+//!   "rates": { "KRW": 1380.0, "JPY": 151.2, "EUR": 0.92 },
+//!   "monthly": {
+//!     "2024-05": { "KRW": 1365.5, "JPY": 155.9 }
+//!   }
+//! }
+//! ```
+//!
+//! A message uses the rate of its month (from its `date`, so in the report's
+//! timezone), then the undated rate, then the rate of the nearest dated month.
+
+use crate::sessions::UnifiedMessage;
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Currency every cost is calculated in
+pub const BASE_CURRENCY: &str = "USD";
+
+/// Error loading an exchange-rate table or selecting a currency
+#[derive(Debug, thiserror::Error)]
+pub enum CurrencyError {
+    #[error("Invalid currency code '{0}': expected an ISO 4217 code like \"EUR\"")]
+    InvalidCode(String),
+
+    #[error(
+        "No exchange rate table found for {0}; add one at ~/.config/tokscale/exchange-rates.json"
+    )]
+    NoRateTable(String),
+
+    #[error("Failed to read exchange rate file {path}: {message}")]
+    Io { path: String, message: String },
+
+    #[error("Invalid exchange rate JSON: {0}")]
+    Json(String),
+
+    #[error("The exchange rate table has no rate for {0}")]
+    MissingRate(String),
+
+    #[error("Invalid exchange rate for {currency}: {rate} (expected a positive number)")]
+    InvalidRate { currency: String, rate: f64 },
+}
+
+/// Exchange rates in units of each currency per US dollar
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExchangeRates {
+    /// Rates used for months without a dated rate
+    #[serde(default)]
+    pub rates: HashMap<String, f64>,
+    /// Rates by month (YYYY-MM)
+    #[serde(default)]
+    pub monthly: BTreeMap<String, HashMap<String, f64>>,
+}
+
+impl ExchangeRates {
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, CurrencyError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| CurrencyError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Self::from_json_str(&content)
+    }
+
+    pub fn from_json_str(content: &str) -> Result<Self, CurrencyError> {
+        let rates: Self =
+            serde_json::from_str(content).map_err(|e| CurrencyError::Json(e.to_string()))?;
+        let all = rates
+            .rates
+            .iter()
+            .chain(rates.monthly.values().flat_map(|month| month.iter()));
+        for (currency, rate) in all {
+            if !rate.is_finite() || *rate <= 0.0 {
+                return Err(CurrencyError::InvalidRate {
+                    currency: currency.clone(),
+                    rate: *rate,
+                });
+            }
+        }
+        Ok(rates)
+    }
+
+    /// `~/.config/tokscale/exchange-rates.json`, if it exists
+    pub fn default_path(home_dir: impl AsRef<Path>) -> Option<PathBuf> {
+        let path = home_dir
+            .as_ref()
+            .join(".config")
+            .join("tokscale")
+            .join("exchange-rates.json");
+        path.is_file().then_some(path)
+    }
+
+    /// Rate for a currency in a month (YYYY-MM)
+    pub fn rate(&self, currency: &str, month: &str) -> Option<f64> {
+        if let Some(rate) = self.monthly.get(month).and_then(|m| m.get(currency)) {
+            return Some(*rate);
+        }
+        if let Some(rate) = self.rates.get(currency) {
+            return Some(*rate);
+        }
+
+        // Nearest dated month, preferring earlier ones
+        let dated = |rates: &HashMap<String, f64>| rates.get(currency).copied();
+        let before = self
+            .monthly
+            .range(..month.to_string())
+            .rev()
+            .find_map(|(m, rates)| Some((m, dated(rates)?)));
+        let after = self
+            .monthly
+            .range(month.to_string()..)
+            .find_map(|(m, rates)| Some((m, dated(rates)?)));
+        match (before, after) {
+            (Some((b, before)), Some((a, after))) => {
+                Some(if month_distance(b, month) <= month_distance(month, a) {
+                    before
+                } else {
+                    after
+                })
+            }
+            (Some((_, rate)), None) | (None, Some((_, rate))) => Some(rate),
+            (None, None) => None,
+        }
+    }
+
+    fn has_currency(&self, currency: &str) -> bool {
+        self.rates.contains_key(currency) || self.monthly.values().any(|m| m.contains_key(currency))
+    }
+}
+
+/// Months from `from` to `to` (both YYYY-MM); 0 if either is malformed
+fn month_distance(from: &str, to: &str) -> i64 {
+    let index = |month: &str| -> Option<i64> {
+        let (year, month) = month.split_once('-')?;
+        Some(year.parse::<i64>().ok()? * 12 + month.parse::<i64>().ok()?)
+    };
+    match (index(from), index(to)) {
+        (Some(from), Some(to)) => to - from,
+        _ => 0,
+    }
+}
+
+/// Currency that costs are reported in
+#[derive(Debug, Clone, Default)]
+pub struct Currency {
+    code: Option<String>,
+    rates: ExchangeRates,
+}
+
+impl Currency {
+    /// Select a currency by ISO 4217 code. USD needs no table; other currencies
+    /// need a table with a rate for them.
+    pub fn new(code: &str, rates: Option<ExchangeRates>) -> Result<Self, CurrencyError> {
+        let code = code.trim().to_ascii_uppercase();
+        if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return Err(CurrencyError::InvalidCode(code));
+        }
+        if code == BASE_CURRENCY {
+            return Ok(Self::default());
+        }
+
+        let rates = rates.ok_or_else(|| CurrencyError::NoRateTable(code.clone()))?;
+        if !rates.has_currency(&code) {
+            return Err(CurrencyError::MissingRate(code));
+        }
+        Ok(Self {
+            code: Some(code),
+            rates,
+        })
+    }
+
+    /// Resolve the `currency` and `exchange_rates` options. The table defaults
+    /// to `~/.config/tokscale/exchange-rates.json`.
+    pub fn from_options(
+        currency: &Option<String>,
+        exchange_rates: &Option<String>,
+        home_dir: &str,
+    ) -> Result<Self, CurrencyError> {
+        let Some(code) = currency else {
+            return Ok(Self::default());
+        };
+        let path = exchange_rates
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| ExchangeRates::default_path(home_dir));
+        let rates = path.map(ExchangeRates::from_json_file).transpose()?;
+        Self::new(code, rates)
+    }
+
+    /// ISO 4217 code of the currency
+    pub fn code(&self) -> &str {
+        self.code.as_deref().unwrap_or(BASE_CURRENCY)
+    }
+
+    /// Convert a USD amount spent on `date` (YYYY-MM-DD)
+    pub fn convert(&self, usd: f64, date: &str) -> f64 {
+        let Some(code) = &self.code else {
+            return usd;
+        };
+        let month = date.get(..7).unwrap_or(date);
+        // `new` checked that the table has the currency, so a rate is always found
+        usd * self.rates.rate(code, month).unwrap_or(1.0)
+    }
+}
+
+/// Convert message costs from USD into the currency. A no-op for USD.
+pub fn convert_costs(messages: &mut [UnifiedMessage], currency: &Currency) {
+    if currency.code.is_none() {
+        return;
+    }
+    messages.par_iter_mut().for_each(|msg| {
+        msg.cost = currency.convert(msg.cost, &msg.date);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATES: &str = r#"{
+        "rates": {"KRW": 1300.0, "EUR": 0.9},
+        "monthly": {
+            "2024-05": {"KRW": 1365.0, "JPY": 155.0},
+            "2024-09": {"JPY": 142.0}
+        }
+    }"#;
+
+    fn currency(code: &str) -> Currency {
+        Currency::new(code, Some(ExchangeRates::from_json_str(RATES).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn test_rate_lookup_order() {
+        let krw = currency("krw");
+        assert_eq!(krw.code(), "KRW");
+        // Dated month first, then the undated rate
+        assert_eq!(krw.convert(2.0, "2024-05-20"), 2730.0);
+        assert_eq!(krw.convert(2.0, "2024-06-01"), 2600.0);
+
+        // Only dated rates: the nearest month, earlier on ties
+        let jpy = currency("JPY");
+        assert_eq!(jpy.convert(1.0, "2024-01-15"), 155.0);
+        assert_eq!(jpy.convert(1.0, "2024-07-01"), 155.0);
+        assert_eq!(jpy.convert(1.0, "2024-08-01"), 142.0);
+        assert_eq!(jpy.convert(1.0, "2025-03-01"), 142.0);
+    }
+
+    #[test]
+    fn test_usd_needs_no_table() {
+        let usd = Currency::new("usd", None).unwrap();
+        assert_eq!(usd.code(), "USD");
+        assert_eq!(usd.convert(1.5, "2024-05-01"), 1.5);
+        assert_eq!(Currency::default().code(), "USD");
+    }
+
+    #[test]
+    fn test_currency_errors() {
+        assert!(matches!(
+            Currency::new("euro", None),
+            Err(CurrencyError::InvalidCode(_))
+        ));
+        assert!(matches!(
+            Currency::new("EUR", None),
+            Err(CurrencyError::NoRateTable(_))
+        ));
+        let rates = ExchangeRates::from_json_str(RATES).unwrap();
+        assert!(matches!(
+            Currency::new("GBP", Some(rates)),
+            Err(CurrencyError::MissingRate(_))
+        ));
+        assert!(matches!(
+            ExchangeRates::from_json_str(r#"{"rates": {"EUR": -1}}"#),
+            Err(CurrencyError::InvalidRate { .. })
+        ));
+        assert!(matches!(
+            ExchangeRates::from_json_str(r#"{"rate": {}}"#),
+            Err(CurrencyError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_options_uses_default_table() {
+        let home = tempfile::TempDir::new().unwrap();
+        let dir = home.path().join(".config").join("tokscale");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("exchange-rates.json"), RATES).unwrap();
+        let home = home.path().to_str().unwrap();
+
+        let eur = Currency::from_options(&Some("EUR".into()), &None, home).unwrap();
+        assert_eq!(eur.convert(10.0, "2024-01-01"), 9.0);
+        assert_eq!(
+            Currency::from_options(&None, &None, home).unwrap().code(),
+            "USD"
+        );
+        assert!(matches!(
+            Currency::from_options(&Some("EUR".into()), &Some("/nonexistent.json".into()), home),
+            Err(CurrencyError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_convert_costs() {
+        let mut messages = vec![UnifiedMessage::new(
+            "claude",
+            "claude-sonnet-4",
+            "anthropic",
+            "ses_1",
+            1_716_000_000_000, // 2024-05-18
+            crate::TokenBreakdown::default(),
+            1.0,
+        )];
+        convert_costs(&mut messages, &Currency::default());
+        assert_eq!(messages[0].cost, 1.0);
+        convert_costs(&mut messages, &currency("KRW"));
+        assert_eq!(messages[0].cost, 1365.0);
+    }
+}
@@ -0,0 +1,54 @@
+//! Error type for the public API
+
+use crate::archive::ArchiveError;
+use crate::currency::CurrencyError;
+use crate::pricing::PricingError;
+use crate::timezone::TimezoneError;
+
+/// Errors returned by the report, graph and watch functions
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("HOME directory not specified and HOME environment variable not set")]
+    HomeDirNotFound,
+
+    #[error(transparent)]
+    InvalidTimezone(#[from] TimezoneError),
+
+    #[error(
+        "Unknown session sort key '{0}' (expected cost, duration, messages, tokens or recent)"
+    )]
+    InvalidSortKey(String),
+
+    #[error(transparent)]
+    Pricing(#[from] PricingError),
+
+    #[error(transparent)]
+    Currency(#[from] CurrencyError),
+
+    #[error(transparent)]
+    Archive(#[from] ArchiveError),
+
+    #[error("Failed to watch session files: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+/// Result type of the public API
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        assert_eq!(
+            Error::InvalidSortKey("price".into()).to_string(),
+            "Unknown session sort key 'price' (expected cost, duration, messages, tokens or recent)"
+        );
+        let timezone = crate::timezone::DateZone::parse("Mars/Olympus").unwrap_err();
+        assert_eq!(
+            Error::from(timezone).to_string(),
+            "Invalid timezone 'Mars/Olympus': expected an IANA name like \"Asia/Seoul\" or an offset like \"+09:00\""
+        );
+    }
+}
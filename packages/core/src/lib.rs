@@ -2,41 +2,68 @@
 //!
 //! This module provides parallel file scanning, SIMD JSON parsing, and efficient
 //! aggregation of token usage data from multiple AI coding assistant sessions.
+//!
+//! The functions below form a plain Rust API returning [`Result`]. The Node.js
+//! bindings in `bindings` wrap them and are only built with the default `napi`
+//! feature; Rust users can depend on the crate with `default-features = false`.
 
 #![deny(clippy::all)]
 
+#[cfg(feature = "napi")]
 use napi_derive::napi;
 
 mod aggregator;
+mod archive;
+#[cfg(feature = "napi")]
+mod bindings;
+mod cache;
+mod currency;
+mod error;
 mod parser;
 mod pricing;
 mod scanner;
 mod sessions;
+mod timezone;
+mod watcher;
 
 pub use aggregator::*;
+pub use archive::ArchiveError;
+pub use currency::CurrencyError;
+pub use error::{Error, Result};
 pub use parser::*;
-pub use pricing::PricingData;
+pub use pricing::{
+    MatchKind, PricingData, PricingError, PricingMatch, PricingOverrides, PricingRule,
+    PricingSnapshots,
+};
 pub use scanner::*;
+pub use timezone::TimezoneError;
+pub use watcher::WatchHandle;
 
-/// Version of the native module
-#[napi]
+/// Version of the crate
 pub fn version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
-/// Simple health check to verify the native module is working
-#[napi]
-pub fn health_check() -> String {
-    "tokscale-core is healthy!".to_string()
-}
-
 /// Configuration options for graph generation
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct GraphOptions {
     /// Home directory path (defaults to user's home)
     pub home_dir: Option<String>,
-    /// Sources to include: "opencode", "claude", "codex", "gemini", "cursor", "amp", "droid"
+    /// Additional home directories, e.g. logs synced from containers or other
+    /// machines. Scanned with each tool's default layout; environment overrides
+    /// such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+    pub extra_home_dirs: Option<Vec<String>>,
+    /// Directories to scan per source name, replacing that source's default
+    /// locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+    pub source_paths: Option<HashMap<String, Vec<String>>>,
+    /// Session archives (`.tar`, `.tar.gz`/`.tgz` or `.zip` snapshots of tool
+    /// directories), read without extracting. Their messages are tagged with
+    /// the archive's file name.
+    pub archives: Option<Vec<String>>,
+    /// Sources to include: "opencode", "claude", "codex", "gemini", "cursor", "amp", "droid",
+    /// "copilot", "qwen", "kilo", or a source defined in `~/.config/tokscale/sources.toml`
+    /// (defaults to every registered source)
     pub sources: Option<Vec<String>>,
     /// Start date filter (YYYY-MM-DD)
     pub since: Option<String>,
@@ -44,13 +71,18 @@ pub struct GraphOptions {
     pub until: Option<String>,
     /// Filter to specific year
     pub year: Option<String>,
+    /// Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+    /// Defaults to UTC; date filters apply to dates in this zone.
+    pub timezone: Option<String>,
     /// Number of parallel threads (defaults to CPU count)
     pub threads: Option<u32>,
+    /// Use the persistent parse cache (defaults to true)
+    pub cache: Option<bool>,
 }
 
 /// Token breakdown by type
-#[napi(object)]
-#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct TokenBreakdown {
     pub input: i64,
     pub output: i64,
@@ -63,8 +95,8 @@ pub struct TokenBreakdown {
 // Two-Phase Processing Types (for parallel execution optimization)
 // =============================================================================
 
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ParsedMessage {
     pub source: String,
     pub model_id: String,
@@ -78,11 +110,34 @@ pub struct ParsedMessage {
     pub cache_write: i64,
     pub reasoning: i64,
     pub agent: Option<String>,
+    pub project: Option<String>,
+    /// Home directory or source path the message was found under
+    pub root: Option<String>,
+    /// Cost recorded by the tool itself (e.g. Amp credits), used for models
+    /// without pricing data when the source's billing policy allows it
+    pub recorded_cost: Option<f64>,
+}
+
+/// A problem found while parsing a session file (see `sessions::Diagnostic`)
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ScanDiagnostic {
+    pub source: String,
+    pub path: String,
+    /// One of "io", "json", "malformed_lines", "unknown_schema", "config"
+    pub kind: String,
+    pub message: String,
+    /// 1-based line number of the first bad line (line-based formats only)
+    pub line: Option<u32>,
+    /// Number of lines skipped (line-based formats only)
+    pub skipped_lines: u32,
+    /// Version marker found in a file with an unrecognised structure
+    pub schema_version: Option<String>,
 }
 
 /// Result of parsing local sources (excludes Cursor - it's network-synced)
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ParsedMessages {
     pub messages: Vec<ParsedMessage>,
     pub opencode_count: i32,
@@ -91,36 +146,73 @@ pub struct ParsedMessages {
     pub gemini_count: i32,
     pub amp_count: i32,
     pub droid_count: i32,
+    /// Message counts keyed by source name (covers every registered source)
+    pub source_counts: Option<HashMap<String, i32>>,
+    /// Number of duplicate messages dropped (e.g. Claude responses logged more than once)
+    pub duplicate_count: Option<i32>,
+    /// Files that were skipped or only partially parsed
+    pub diagnostics: Option<Vec<ScanDiagnostic>>,
     pub processing_time_ms: u32,
 }
 
 /// Options for parsing local sources only (no Cursor)
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct LocalParseOptions {
     pub home_dir: Option<String>,
+    /// Additional home directories, e.g. logs synced from containers or other
+    /// machines. Scanned with each tool's default layout; environment overrides
+    /// such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+    pub extra_home_dirs: Option<Vec<String>>,
+    /// Directories to scan per source name, replacing that source's default
+    /// locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+    pub source_paths: Option<HashMap<String, Vec<String>>>,
+    /// Session archives (`.tar`, `.tar.gz`/`.tgz` or `.zip` snapshots of tool
+    /// directories), read without extracting. Their messages are tagged with
+    /// the archive's file name.
+    pub archives: Option<Vec<String>>,
     pub sources: Option<Vec<String>>,
     pub since: Option<String>,
     pub until: Option<String>,
     pub year: Option<String>,
+    /// Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+    /// Defaults to UTC; date filters apply to dates in this zone.
+    pub timezone: Option<String>,
+    /// Use the persistent parse cache (defaults to true)
+    pub cache: Option<bool>,
 }
 
 /// Options for finalizing report with pricing
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FinalizeReportOptions {
     pub home_dir: Option<String>,
+    /// Additional home directories, e.g. logs synced from containers or other
+    /// machines. Scanned with each tool's default layout; environment overrides
+    /// such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+    pub extra_home_dirs: Option<Vec<String>>,
+    /// Directories to scan per source name, replacing that source's default
+    /// locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+    pub source_paths: Option<HashMap<String, Vec<String>>>,
     pub local_messages: ParsedMessages,
     pub pricing: Vec<PricingEntry>,
     pub include_cursor: bool,
     pub since: Option<String>,
     pub until: Option<String>,
     pub year: Option<String>,
+    /// Timezone for network-synced (Cursor) messages; local messages keep the
+    /// dates assigned by `parse_local_sources`. Defaults to UTC.
+    pub timezone: Option<String>,
+    /// Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+    /// Defaults to USD; other currencies need an exchange-rate table.
+    pub currency: Option<String>,
+    /// Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json)
+    pub exchange_rates: Option<String>,
 }
 
 /// Daily contribution totals
-#[napi(object)]
-#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct DailyTotals {
     pub tokens: i64,
     pub cost: f64,
@@ -128,8 +220,8 @@ pub struct DailyTotals {
 }
 
 /// Source contribution for a specific day
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SourceContribution {
     pub source: String,
     pub model_id: String,
@@ -140,8 +232,8 @@ pub struct SourceContribution {
 }
 
 /// Daily contribution data
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DailyContribution {
     pub date: String,
     pub totals: DailyTotals,
@@ -151,8 +243,8 @@ pub struct DailyContribution {
 }
 
 /// Year summary
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct YearSummary {
     pub year: String,
     pub total_tokens: i64,
@@ -162,8 +254,8 @@ pub struct YearSummary {
 }
 
 /// Data summary statistics
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DataSummary {
     pub total_tokens: i64,
     pub total_cost: f64,
@@ -176,44 +268,83 @@ pub struct DataSummary {
 }
 
 /// Metadata about the graph generation
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GraphMeta {
     pub generated_at: String,
     pub version: String,
     pub date_range_start: String,
     pub date_range_end: String,
     pub processing_time_ms: u32,
+    /// ISO 4217 code of the currency every cost is in
+    pub currency: String,
+    /// Number of duplicate messages dropped (e.g. Claude responses logged more than once)
+    pub duplicate_count: i32,
 }
 
 /// Complete graph result
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GraphResult {
     pub meta: GraphMeta,
     pub summary: DataSummary,
     pub years: Vec<YearSummary>,
     pub contributions: Vec<DailyContribution>,
+    /// Models without pricing, by token volume (empty when built without pricing data)
+    pub unpriced_models: Vec<UnpricedModel>,
+    /// Files that were skipped or only partially parsed
+    pub diagnostics: Vec<ScanDiagnostic>,
 }
 
 // =============================================================================
 // Main NAPI Export: generateGraph
 // =============================================================================
 
+use currency::Currency;
 use rayon::prelude::*;
-use sessions::UnifiedMessage;
+use sessions::{Diagnostic, ParsedFile, SessionSource, SourceRegistry, UnifiedMessage};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
+use timezone::DateZone;
 
 /// Get home directory from options or environment, returning error if not available
-fn get_home_dir(home_dir_option: &Option<String>) -> napi::Result<String> {
+fn get_home_dir(home_dir_option: &Option<String>) -> Result<String> {
     home_dir_option
         .clone()
         .or_else(|| std::env::var("HOME").ok())
-        .ok_or_else(|| {
-            napi::Error::from_reason(
-                "HOME directory not specified and HOME environment variable not set",
-            )
-        })
+        .ok_or(Error::HomeDirNotFound)
+}
+
+/// Build the scan roots from the home directory, extra home directory and
+/// source path options, with the user-defined sources of the home directory
+fn get_scan_roots(
+    home_dir: &Option<String>,
+    extra_home_dirs: &Option<Vec<String>>,
+    source_paths: &Option<HashMap<String, Vec<String>>>,
+) -> Result<ScanRoots> {
+    let home_dir = get_home_dir(home_dir)?;
+    let sources = SourceRegistry::load(&home_dir);
+    Ok(ScanRoots::new(home_dir)
+        .with_extra_home_dirs(extra_home_dirs.clone().unwrap_or_default())
+        .with_source_paths(source_paths.clone().unwrap_or_default())
+        .with_sources(sources))
+}
+
+/// Parse the timezone option, returning an error for unknown names or malformed offsets
+fn get_date_zone(timezone: &Option<String>) -> Result<DateZone> {
+    Ok(DateZone::from_option(timezone)?)
+}
+
+/// Resolve the currency options, loading the exchange-rate table if a currency
+/// other than USD is selected
+fn get_currency(
+    currency: &Option<String>,
+    exchange_rates: &Option<String>,
+    home_dir: &str,
+) -> Result<Currency> {
+    Ok(Currency::from_options(currency, exchange_rates, home_dir)?)
 }
 
 /// Generate graph data from all session sources
@@ -223,24 +354,22 @@ fn get_home_dir(home_dir_option: &Option<String>) -> napi::Result<String> {
 /// 2. Parallel session parsing
 /// 3. Date filtering
 /// 4. Parallel aggregation
-#[napi]
-pub fn generate_graph(options: GraphOptions) -> napi::Result<GraphResult> {
+pub fn generate_graph(options: GraphOptions) -> Result<GraphResult> {
     let start = Instant::now();
 
-    let home_dir = get_home_dir(&options.home_dir)?;
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?
+    .with_archives(options.archives.clone().unwrap_or_default());
+    let zone = get_date_zone(&options.timezone)?;
 
     // Get sources to scan
-    let sources = options.sources.clone().unwrap_or_else(|| {
-        vec![
-            "opencode".to_string(),
-            "claude".to_string(),
-            "codex".to_string(),
-            "gemini".to_string(),
-            "cursor".to_string(),
-            "amp".to_string(),
-            "droid".to_string(),
-        ]
-    });
+    let sources = options
+        .sources
+        .clone()
+        .unwrap_or_else(|| roots.sources.names());
 
     // Configure thread pool if specified
     if let Some(threads) = options.threads {
@@ -250,67 +379,16 @@ pub fn generate_graph(options: GraphOptions) -> napi::Result<GraphResult> {
             .ok();
     }
 
-    // 1. Parallel file scanning
-    let scan_result = scanner::scan_all_sources(&home_dir, &sources);
-
-    // 2. Parallel session parsing
+    // 1-2. Parallel file scanning and session parsing
     let mut all_messages: Vec<UnifiedMessage> = Vec::new();
-
-    // Parse OpenCode files in parallel
-    let opencode_messages: Vec<UnifiedMessage> = scan_result
-        .opencode_files
-        .par_iter()
-        .filter_map(|path| sessions::opencode::parse_opencode_file(path))
-        .collect();
-    all_messages.extend(opencode_messages);
-
-    // Parse Claude files in parallel
-    let claude_messages: Vec<UnifiedMessage> = scan_result
-        .claude_files
-        .par_iter()
-        .flat_map(|path| sessions::claudecode::parse_claude_file(path))
-        .collect();
-    all_messages.extend(claude_messages);
-
-    // Parse Codex files in parallel
-    let codex_messages: Vec<UnifiedMessage> = scan_result
-        .codex_files
-        .par_iter()
-        .flat_map(|path| sessions::codex::parse_codex_file(path))
-        .collect();
-    all_messages.extend(codex_messages);
-
-    // Parse Gemini files in parallel
-    let gemini_messages: Vec<UnifiedMessage> = scan_result
-        .gemini_files
-        .par_iter()
-        .flat_map(|path| sessions::gemini::parse_gemini_file(path))
-        .collect();
-    all_messages.extend(gemini_messages);
-
-    // Parse Cursor files in parallel
-    let cursor_messages: Vec<UnifiedMessage> = scan_result
-        .cursor_files
-        .par_iter()
-        .flat_map(|path| sessions::cursor::parse_cursor_file(path))
-        .collect();
-    all_messages.extend(cursor_messages);
-
-    // Parse Amp files in parallel
-    let amp_messages: Vec<UnifiedMessage> = scan_result
-        .amp_files
-        .par_iter()
-        .flat_map(|path| sessions::amp::parse_amp_file(path))
-        .collect();
-    all_messages.extend(amp_messages);
-
-    // Parse Droid files in parallel
-    let droid_messages: Vec<UnifiedMessage> = scan_result
-        .droid_files
-        .par_iter()
-        .flat_map(|path| sessions::droid::parse_droid_file(path))
-        .collect();
-    all_messages.extend(droid_messages);
+    let mut duplicate_count = 0;
+    let (parsed_sources, mut diagnostics) =
+        scan_and_parse(&roots, &sources, options.cache.unwrap_or(true), &zone)?;
+    for parsed in parsed_sources {
+        all_messages.extend(parsed.messages);
+        duplicate_count += parsed.duplicates as i32;
+        diagnostics.extend(parsed.diagnostics);
+    }
 
     // 3. Apply date filters
     let filtered_messages = filter_messages(all_messages, &options);
@@ -320,11 +398,148 @@ pub fn generate_graph(options: GraphOptions) -> napi::Result<GraphResult> {
 
     // 5. Generate result
     let processing_time_ms = start.elapsed().as_millis() as u32;
-    let result = aggregator::generate_graph_result(contributions, processing_time_ms);
+    let mut result = aggregator::generate_graph_result(contributions, processing_time_ms);
+    result.diagnostics = diagnostics;
+    result.meta.duplicate_count = duplicate_count;
 
     Ok(result)
 }
 
+/// Messages parsed from one source's files
+struct SourceMessages {
+    source: String,
+    messages: Vec<UnifiedMessage>,
+    /// Number of duplicate messages dropped (see `sessions::deduplicate_messages`)
+    duplicates: usize,
+    diagnostics: Vec<ScanDiagnostic>,
+}
+
+/// Scan the given sources and parse their files, going through the persistent
+/// parse cache unless `use_cache` is false. Archive entries are parsed along
+/// with them, bypassing the cache. Message dates are derived in `zone`.
+///
+/// Also returns diagnostics that belong to no source, such as a sources file
+/// that could not be loaded.
+fn scan_and_parse(
+    roots: &ScanRoots,
+    sources: &[String],
+    use_cache: bool,
+    zone: &DateZone,
+) -> Result<(Vec<SourceMessages>, Vec<ScanDiagnostic>)> {
+    let selected = roots.sources.select(sources);
+    let scan_result = scanner::scan_sources(roots, sources);
+    let archived = archive::parse_archives(&roots.archives, &selected)?;
+    let parse_cache = use_cache.then(|| cache::ParseCache::load(&roots.home_dir));
+
+    let mut parsed = parse_scanned_files(&selected, &scan_result, archived, parse_cache.as_ref());
+    for source in &mut parsed {
+        timezone::localize_dates(&mut source.messages, zone);
+    }
+
+    if let Some(parse_cache) = parse_cache {
+        // The cache is an optimization only; failing to persist it is not an error
+        parse_cache.save().ok();
+    }
+
+    // User-defined sources are local, so a broken sources file is reported
+    // with the local sources only and listed once when synced sources follow
+    let mut diagnostics = Vec::new();
+    if let Some((path, diagnostic)) = roots.sources.config_diagnostic() {
+        if selected.iter().any(|source| source.is_local()) {
+            diagnostics.push(to_scan_diagnostic("custom", path, diagnostic));
+        }
+    }
+
+    Ok((parsed, diagnostics))
+}
+
+/// Parse every scanned file in parallel with its source's parser, grouped by source,
+/// and add the files already parsed from archives. Duplicate messages are removed
+/// across all files of a source.
+fn parse_scanned_files(
+    selected: &[Arc<dyn SessionSource>],
+    scan_result: &ScanResult,
+    archived: Vec<archive::ArchiveFile>,
+    parse_cache: Option<&cache::ParseCache>,
+) -> Vec<SourceMessages> {
+    let mut archived_by_source: HashMap<String, Vec<archive::ArchiveFile>> = HashMap::new();
+    for file in archived {
+        archived_by_source
+            .entry(file.source.clone())
+            .or_default()
+            .push(file);
+    }
+
+    scan_result
+        .sources
+        .iter()
+        .map(|(name, files)| {
+            let source = selected.iter().find(|s| s.name() == name);
+            let parsed_files: Vec<(&PathBuf, ParsedFile)> = match source {
+                Some(source) => files
+                    .par_iter()
+                    .map(|path| {
+                        let parsed = match parse_cache {
+                            Some(c) => c.parse_file(source.as_ref(), path),
+                            None => source.parse_file(path),
+                        };
+                        (path, parsed)
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+
+            let mut messages: Vec<UnifiedMessage> = Vec::new();
+            let mut diagnostics: Vec<ScanDiagnostic> = Vec::new();
+            for (path, parsed) in parsed_files {
+                let root = scan_result.tag(path);
+                messages.extend(parsed.messages.into_iter().map(|mut msg| {
+                    msg.root = root.map(str::to_string);
+                    msg
+                }));
+                diagnostics.extend(
+                    parsed
+                        .diagnostics
+                        .iter()
+                        .map(|d| to_scan_diagnostic(name, path, d)),
+                );
+            }
+            for file in archived_by_source.remove(name).unwrap_or_default() {
+                messages.extend(file.parsed.messages.into_iter().map(|mut msg| {
+                    msg.root = Some(file.tag.clone());
+                    msg
+                }));
+                diagnostics.extend(
+                    file.parsed
+                        .diagnostics
+                        .iter()
+                        .map(|d| to_scan_diagnostic(name, &file.path, d)),
+                );
+            }
+
+            let duplicates = sessions::deduplicate_messages(&mut messages);
+            SourceMessages {
+                source: name.clone(),
+                messages,
+                duplicates,
+                diagnostics,
+            }
+        })
+        .collect()
+}
+
+fn to_scan_diagnostic(source: &str, path: &Path, diagnostic: &Diagnostic) -> ScanDiagnostic {
+    ScanDiagnostic {
+        source: source.to_string(),
+        path: path.to_string_lossy().to_string(),
+        kind: diagnostic.kind.as_str().to_string(),
+        message: diagnostic.message.clone(),
+        line: diagnostic.line.map(|line| line as u32),
+        skipped_lines: diagnostic.skipped_lines as u32,
+        schema_version: diagnostic.schema_version.clone(),
+    }
+}
+
 /// Filter messages by date range options
 fn filter_messages(messages: Vec<UnifiedMessage>, options: &GraphOptions) -> Vec<UnifiedMessage> {
     let mut filtered = messages;
@@ -349,7 +564,8 @@ fn filter_messages(messages: Vec<UnifiedMessage>, options: &GraphOptions) -> Vec
 }
 
 /// Scan session files and return file counts per source
-#[napi(object)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ScanStats {
     pub opencode_files: i32,
     pub claude_files: i32,
@@ -359,36 +575,33 @@ pub struct ScanStats {
     pub amp_files: i32,
     pub droid_files: i32,
     pub total_files: i32,
+    /// File counts keyed by source name (covers every registered source)
+    pub source_files: HashMap<String, i32>,
 }
 
 /// Scan for session files (for debugging/testing)
-#[napi]
-pub fn scan_sessions(home_dir: Option<String>, sources: Option<Vec<String>>) -> napi::Result<ScanStats> {
-    let home = get_home_dir(&home_dir)?;
-
-    let srcs = sources.unwrap_or_else(|| {
-        vec![
-            "opencode".to_string(),
-            "claude".to_string(),
-            "codex".to_string(),
-            "gemini".to_string(),
-            "cursor".to_string(),
-            "amp".to_string(),
-            "droid".to_string(),
-        ]
-    });
+pub fn scan_sessions(home_dir: Option<String>, sources: Option<Vec<String>>) -> Result<ScanStats> {
+    let roots = get_scan_roots(&home_dir, &None, &None)?;
+
+    let srcs = sources.unwrap_or_else(|| roots.sources.names());
 
-    let result = scanner::scan_all_sources(&home, &srcs);
+    let result = scanner::scan_sources(&roots, &srcs);
+    let count = |name: &str| result.files(name).len() as i32;
 
     Ok(ScanStats {
-        opencode_files: result.opencode_files.len() as i32,
-        claude_files: result.claude_files.len() as i32,
-        codex_files: result.codex_files.len() as i32,
-        gemini_files: result.gemini_files.len() as i32,
-        cursor_files: result.cursor_files.len() as i32,
-        amp_files: result.amp_files.len() as i32,
-        droid_files: result.droid_files.len() as i32,
+        opencode_files: count("opencode"),
+        claude_files: count("claude"),
+        codex_files: count("codex"),
+        gemini_files: count("gemini"),
+        cursor_files: count("cursor"),
+        amp_files: count("amp"),
+        droid_files: count("droid"),
         total_files: result.total_files() as i32,
+        source_files: result
+            .sources
+            .iter()
+            .map(|(name, files)| (name.to_string(), files.len() as i32))
+            .collect(),
     })
 }
 
@@ -397,30 +610,54 @@ pub fn scan_sessions(home_dir: Option<String>, sources: Option<Vec<String>>) ->
 // =============================================================================
 
 /// Pricing data for a single model (passed from TypeScript)
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ModelPricing {
     pub input_cost_per_token: f64,
     pub output_cost_per_token: f64,
     pub cache_read_input_token_cost: Option<f64>,
     pub cache_creation_input_token_cost: Option<f64>,
+    /// Rate for reasoning tokens (defaults to the output rate)
+    pub output_cost_per_reasoning_token: Option<f64>,
+    /// Rates for requests whose prompt (input plus cache tokens) exceeds 200k tokens
+    pub input_cost_per_token_above_200k_tokens: Option<f64>,
+    pub output_cost_per_token_above_200k_tokens: Option<f64>,
+    pub cache_read_input_token_cost_above_200k_tokens: Option<f64>,
+    pub cache_creation_input_token_cost_above_200k_tokens: Option<f64>,
 }
 
 /// Entry in the pricing map
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PricingEntry {
     pub model_id: String,
     pub pricing: ModelPricing,
+    /// Date these rates took effect (YYYY-MM-DD, UTC). Messages are priced with
+    /// the latest dated entry on or before their timestamp; undated entries
+    /// cover models without dated entries and messages before the first one.
+    pub effective_from: Option<String>,
 }
 
 /// Options for reports with pricing
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct ReportOptions {
     /// Home directory path (defaults to user's home)
     pub home_dir: Option<String>,
-    /// Sources to include: "opencode", "claude", "codex", "gemini", "cursor", "amp", "droid"
+    /// Additional home directories, e.g. logs synced from containers or other
+    /// machines. Scanned with each tool's default layout; environment overrides
+    /// such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+    pub extra_home_dirs: Option<Vec<String>>,
+    /// Directories to scan per source name, replacing that source's default
+    /// locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+    pub source_paths: Option<HashMap<String, Vec<String>>>,
+    /// Session archives (`.tar`, `.tar.gz`/`.tgz` or `.zip` snapshots of tool
+    /// directories), read without extracting. Their messages are tagged with
+    /// the archive's file name.
+    pub archives: Option<Vec<String>>,
+    /// Sources to include: "opencode", "claude", "codex", "gemini", "cursor", "amp", "droid",
+    /// "copilot", "qwen", "kilo", or a source defined in `~/.config/tokscale/sources.toml`
+    /// (defaults to every registered source)
     pub sources: Option<Vec<String>>,
     /// Pricing data for cost calculation
     pub pricing: Vec<PricingEntry>,
@@ -430,11 +667,21 @@ pub struct ReportOptions {
     pub until: Option<String>,
     /// Filter to specific year
     pub year: Option<String>,
+    /// Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+    /// Defaults to UTC; date filters apply to dates in this zone.
+    pub timezone: Option<String>,
+    /// Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+    /// Defaults to USD; other currencies need an exchange-rate table.
+    pub currency: Option<String>,
+    /// Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json)
+    pub exchange_rates: Option<String>,
+    /// Use the persistent parse cache (defaults to true)
+    pub cache: Option<bool>,
 }
 
 /// Model usage summary for reports
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ModelUsage {
     pub source: String,
     pub model: String,
@@ -446,11 +693,53 @@ pub struct ModelUsage {
     pub reasoning: i64,
     pub message_count: i32,
     pub cost: f64,
+    /// Pricing rule that priced the model: "base", or the local override
+    /// (e.g. "override my-model", "glob ollama/*", "anthropic discount 20%");
+    /// `None` if no pricing matched
+    pub pricing_rule: Option<String>,
+    /// Pricing key the model resolved to (the pattern for pattern overrides)
+    pub pricing_key: Option<String>,
+    /// How the model matched its pricing: "exact", "prefixed", "normalized",
+    /// "fuzzy", "pattern" or "none"
+    pub match_kind: String,
+}
+
+/// Model without pricing whose cost counts as 0 in a report
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UnpricedModel {
+    pub model: String,
+    pub sources: Vec<String>,
+    pub tokens: TokenBreakdown,
+    /// Sum of every token category
+    pub total_tokens: i64,
+    pub message_count: i32,
+}
+
+/// How a model id resolves against the pricing data
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PricingExplanation {
+    pub model_id: String,
+    /// Pricing key the model resolved to (the pattern for pattern overrides)
+    pub pricing_key: Option<String>,
+    /// "exact", "prefixed", "normalized", "fuzzy", "pattern" or "none"
+    pub match_kind: String,
+    /// "base", or the local override that set the rates
+    pub pricing_rule: Option<String>,
+    /// Base rates of the resolved entry (all 0 when unpriced)
+    pub input_cost_per_token: f64,
+    pub output_cost_per_token: f64,
+    pub cache_read_input_token_cost: f64,
+    pub cache_creation_input_token_cost: f64,
+    pub output_cost_per_reasoning_token: f64,
+    /// Prompt sizes above which long-context rates apply
+    pub tier_thresholds: Vec<i64>,
 }
 
 /// Monthly usage summary
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MonthlyUsage {
     pub month: String,
     pub models: Vec<String>,
@@ -463,266 +752,370 @@ pub struct MonthlyUsage {
 }
 
 /// Model report result
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ModelReport {
     pub entries: Vec<ModelUsage>,
+    /// Models without pricing, by token volume
+    pub unpriced_models: Vec<UnpricedModel>,
     pub total_input: i64,
     pub total_output: i64,
     pub total_cache_read: i64,
     pub total_cache_write: i64,
     pub total_messages: i32,
     pub total_cost: f64,
+    /// ISO 4217 code of the currency every cost is in
+    pub currency: String,
+    pub processing_time_ms: u32,
+}
+
+/// Project usage summary for reports
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProjectUsage {
+    /// Project or working directory ("unknown" if the source does not record one)
+    pub project: String,
+    pub sources: Vec<String>,
+    pub models: Vec<String>,
+    pub input: i64,
+    pub output: i64,
+    pub cache_read: i64,
+    pub cache_write: i64,
+    pub reasoning: i64,
+    pub message_count: i32,
+    pub cost: f64,
+}
+
+/// Project report result
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProjectReport {
+    pub entries: Vec<ProjectUsage>,
+    /// Models without pricing, by token volume
+    pub unpriced_models: Vec<UnpricedModel>,
+    pub total_input: i64,
+    pub total_output: i64,
+    pub total_cache_read: i64,
+    pub total_cache_write: i64,
+    pub total_messages: i32,
+    pub total_cost: f64,
+    /// ISO 4217 code of the currency every cost is in
+    pub currency: String,
+    pub processing_time_ms: u32,
+}
+
+/// Options for the per-session report
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct SessionReportOptions {
+    /// Home directory path (defaults to user's home)
+    pub home_dir: Option<String>,
+    /// Additional home directories, e.g. logs synced from containers or other
+    /// machines. Scanned with each tool's default layout; environment overrides
+    /// such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+    pub extra_home_dirs: Option<Vec<String>>,
+    /// Directories to scan per source name, replacing that source's default
+    /// locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+    pub source_paths: Option<HashMap<String, Vec<String>>>,
+    /// Session archives (`.tar`, `.tar.gz`/`.tgz` or `.zip` snapshots of tool
+    /// directories), read without extracting. Their messages are tagged with
+    /// the archive's file name.
+    pub archives: Option<Vec<String>>,
+    /// Sources to include (defaults to every registered source)
+    pub sources: Option<Vec<String>>,
+    /// Pricing data for cost calculation
+    pub pricing: Vec<PricingEntry>,
+    /// Start date filter (YYYY-MM-DD)
+    pub since: Option<String>,
+    /// End date filter (YYYY-MM-DD)
+    pub until: Option<String>,
+    /// Filter to specific year
+    pub year: Option<String>,
+    /// Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+    /// Defaults to UTC; date filters apply to dates in this zone.
+    pub timezone: Option<String>,
+    /// Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+    /// Defaults to USD; other currencies need an exchange-rate table.
+    pub currency: Option<String>,
+    /// Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json)
+    pub exchange_rates: Option<String>,
+    /// Use the persistent parse cache (defaults to true)
+    pub cache: Option<bool>,
+    /// Sort key, descending: "cost" (default), "duration", "messages", "tokens" or "recent"
+    pub sort_by: Option<String>,
+    /// Only return the first N sessions after sorting
+    pub limit: Option<u32>,
+    /// Drop sessions that cost less than this amount (in the report currency)
+    pub min_cost: Option<f64>,
+}
+
+/// Session usage summary for reports
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SessionUsage {
+    pub source: String,
+    pub session_id: String,
+    pub project: Option<String>,
+    /// Timestamp of the first message (Unix milliseconds)
+    pub first_timestamp: i64,
+    /// Timestamp of the last message (Unix milliseconds)
+    pub last_timestamp: i64,
+    /// Wall-clock time between the first and last message
+    pub duration_ms: i64,
+    pub models: Vec<String>,
+    pub input: i64,
+    pub output: i64,
+    pub cache_read: i64,
+    pub cache_write: i64,
+    pub reasoning: i64,
+    pub message_count: i32,
+    pub cost: f64,
+}
+
+/// Session report result
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SessionReport {
+    pub entries: Vec<SessionUsage>,
+    /// Models without pricing, by token volume (before the limit and minimum cost)
+    pub unpriced_models: Vec<UnpricedModel>,
+    /// Number of sessions before the limit and minimum cost were applied
+    pub total_sessions: i32,
+    pub total_messages: i32,
+    pub total_cost: f64,
+    /// ISO 4217 code of the currency every cost is in
+    pub currency: String,
     pub processing_time_ms: u32,
 }
 
 /// Monthly report result
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MonthlyReport {
     pub entries: Vec<MonthlyUsage>,
+    /// Models without pricing, by token volume
+    pub unpriced_models: Vec<UnpricedModel>,
     pub total_cost: f64,
+    /// ISO 4217 code of the currency every cost is in
+    pub currency: String,
     pub processing_time_ms: u32,
 }
 
 /// Convert pricing entries to internal PricingData
-fn build_pricing_data(entries: &[PricingEntry]) -> PricingData {
+fn build_pricing_data(entries: &[PricingEntry]) -> Result<PricingData> {
     let mut pricing_data = PricingData::new();
     for entry in entries {
-        pricing_data.add_model(
-            entry.model_id.clone(),
-            pricing::ModelPricing {
-                input_cost_per_token: entry.pricing.input_cost_per_token,
-                output_cost_per_token: entry.pricing.output_cost_per_token,
-                cache_read_input_token_cost: entry
-                    .pricing
-                    .cache_read_input_token_cost
-                    .unwrap_or(0.0),
-                cache_creation_input_token_cost: entry
-                    .pricing
-                    .cache_creation_input_token_cost
-                    .unwrap_or(0.0),
-            },
-        );
+        let p = &entry.pricing;
+        let mut model = pricing::ModelPricing {
+            input_cost_per_token: p.input_cost_per_token,
+            output_cost_per_token: p.output_cost_per_token,
+            cache_read_input_token_cost: p.cache_read_input_token_cost.unwrap_or(0.0),
+            cache_creation_input_token_cost: p.cache_creation_input_token_cost.unwrap_or(0.0),
+            output_cost_per_reasoning_token: p.output_cost_per_reasoning_token,
+            ..Default::default()
+        };
+
+        let long_context = pricing::PricingTier {
+            threshold: 200_000,
+            input_cost_per_token: p.input_cost_per_token_above_200k_tokens,
+            output_cost_per_token: p.output_cost_per_token_above_200k_tokens,
+            cache_read_input_token_cost: p.cache_read_input_token_cost_above_200k_tokens,
+            cache_creation_input_token_cost: p.cache_creation_input_token_cost_above_200k_tokens,
+        };
+        if long_context.input_cost_per_token.is_some()
+            || long_context.output_cost_per_token.is_some()
+            || long_context.cache_read_input_token_cost.is_some()
+            || long_context.cache_creation_input_token_cost.is_some()
+        {
+            model.add_tier(long_context);
+        }
+
+        match &entry.effective_from {
+            Some(date) => {
+                let effective_from = pricing::history::parse_effective_from(date)?;
+                pricing_data.add_model_version(entry.model_id.clone(), effective_from, model);
+            }
+            None => pricing_data.add_model(entry.model_id.clone(), model),
+        }
     }
     // Pre-compute sorted keys for fast lookups
     pricing_data.finalize();
-    pricing_data
+    Ok(pricing_data)
+}
+
+/// Explain how a model id resolves to pricing: the matched key, the kind of
+/// match, the override rule and the resulting rates
+pub fn explain_pricing(model_id: &str, pricing_data: &PricingData) -> PricingExplanation {
+    let found = pricing_data.resolve(model_id);
+    let rates = found.map(|f| f.pricing.rates_for(0));
+    PricingExplanation {
+        model_id: model_id.to_string(),
+        pricing_key: found.map(|f| f.key.to_string()),
+        match_kind: found
+            .map_or(pricing::MatchKind::None, |f| f.kind)
+            .as_str()
+            .to_string(),
+        pricing_rule: found.map(|f| f.pricing.rule_label()),
+        input_cost_per_token: rates.map_or(0.0, |r| r.input),
+        output_cost_per_token: rates.map_or(0.0, |r| r.output),
+        cache_read_input_token_cost: rates.map_or(0.0, |r| r.cache_read),
+        cache_creation_input_token_cost: rates.map_or(0.0, |r| r.cache_write),
+        output_cost_per_reasoning_token: rates.map_or(0.0, |r| r.reasoning),
+        tier_thresholds: found
+            .map(|f| f.pricing.tiers.iter().map(|t| t.threshold).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// Zeroed model report row, with the model's pricing resolution filled in
+fn empty_model_usage(msg: &UnifiedMessage, pricing_data: &PricingData) -> ModelUsage {
+    let found = pricing_data.resolve(&msg.model_id);
+    ModelUsage {
+        source: msg.source.clone(),
+        model: msg.model_id.clone(),
+        provider: msg.provider_id.clone(),
+        input: 0,
+        output: 0,
+        cache_read: 0,
+        cache_write: 0,
+        reasoning: 0,
+        message_count: 0,
+        cost: 0.0,
+        pricing_rule: found.map(|f| f.pricing.rule_label()),
+        pricing_key: found.map(|f| f.key.to_string()),
+        match_kind: found
+            .map_or(pricing::MatchKind::None, |f| f.kind)
+            .as_str()
+            .to_string(),
+    }
 }
 
 /// Parse all messages with pricing calculation
 fn parse_all_messages_with_pricing(
-    home_dir: &str,
+    roots: &ScanRoots,
     sources: &[String],
     pricing_data: &PricingData,
-) -> Vec<UnifiedMessage> {
-    let scan_result = scanner::scan_all_sources(home_dir, sources);
+    use_cache: bool,
+    zone: &DateZone,
+) -> Result<Vec<UnifiedMessage>> {
+    Ok(
+        parse_all_messages_with_diagnostics(roots, sources, pricing_data, use_cache, zone)?
+            .messages,
+    )
+}
+
+/// Priced messages of a scan, with what was dropped or skipped along the way
+struct PricedMessages {
+    messages: Vec<UnifiedMessage>,
+    diagnostics: Vec<ScanDiagnostic>,
+    /// Number of duplicate messages dropped (see `sessions::deduplicate_messages`)
+    duplicates: usize,
+}
+
+/// Parse all messages with pricing calculation, also returning parse diagnostics
+/// and the number of duplicates dropped
+fn parse_all_messages_with_diagnostics(
+    roots: &ScanRoots,
+    sources: &[String],
+    pricing_data: &PricingData,
+    use_cache: bool,
+    zone: &DateZone,
+) -> Result<PricedMessages> {
     let mut all_messages: Vec<UnifiedMessage> = Vec::new();
+    let mut duplicates = 0;
+    let (parsed_sources, mut diagnostics) = scan_and_parse(roots, sources, use_cache, zone)?;
+    for parsed in parsed_sources {
+        all_messages.extend(parsed.messages);
+        duplicates += parsed.duplicates;
+        diagnostics.extend(parsed.diagnostics);
+    }
 
-    // Parse OpenCode files in parallel
-    let opencode_messages: Vec<UnifiedMessage> = scan_result
-        .opencode_files
-        .par_iter()
-        .filter_map(|path| {
-            let mut msg = sessions::opencode::parse_opencode_file(path)?;
-            // Recalculate cost using pricing data
-            msg.cost = pricing_data.calculate_cost(
-                &msg.model_id,
-                msg.tokens.input,
-                msg.tokens.output,
-                msg.tokens.cache_read,
-                msg.tokens.cache_write,
-                msg.tokens.reasoning,
-            );
-            Some(msg)
-        })
-        .collect();
-    all_messages.extend(opencode_messages);
-
-    // Parse Claude files in parallel
-    let claude_messages: Vec<UnifiedMessage> = scan_result
-        .claude_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::claudecode::parse_claude_file(path)
-                .into_iter()
-                .map(|mut msg| {
-                    msg.cost = pricing_data.calculate_cost(
-                        &msg.model_id,
-                        msg.tokens.input,
-                        msg.tokens.output,
-                        msg.tokens.cache_read,
-                        msg.tokens.cache_write,
-                        msg.tokens.reasoning,
-                    );
-                    msg
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    all_messages.extend(claude_messages);
-
-    // Parse Codex files in parallel
-    let codex_messages: Vec<UnifiedMessage> = scan_result
-        .codex_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::codex::parse_codex_file(path)
-                .into_iter()
-                .map(|mut msg| {
-                    msg.cost = pricing_data.calculate_cost(
-                        &msg.model_id,
-                        msg.tokens.input,
-                        msg.tokens.output,
-                        msg.tokens.cache_read,
-                        msg.tokens.cache_write,
-                        msg.tokens.reasoning,
-                    );
-                    msg
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    all_messages.extend(codex_messages);
-
-    // Parse Gemini files in parallel
-    let gemini_messages: Vec<UnifiedMessage> = scan_result
-        .gemini_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::gemini::parse_gemini_file(path)
-                .into_iter()
-                .map(|mut msg| {
-                    // Gemini: thoughts count as output for billing
-                    msg.cost = pricing_data.calculate_cost(
-                        &msg.model_id,
-                        msg.tokens.input,
-                        msg.tokens.output + msg.tokens.reasoning,
-                        0, // Gemini cached tokens are free
-                        0,
-                        0,
-                    );
-                    msg
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    all_messages.extend(gemini_messages);
-
-    // Parse Cursor files in parallel
-    // Calculate cost using our pricing data for consistency with other providers
-    // Fall back to CSV cost only if no pricing is found
-    let cursor_messages: Vec<UnifiedMessage> = scan_result
-        .cursor_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::cursor::parse_cursor_file(path)
-                .into_iter()
-                .map(|mut msg| {
-                    let csv_cost = msg.cost; // Store original CSV cost
-                    let calculated_cost = pricing_data.calculate_cost(
-                        &msg.model_id,
-                        msg.tokens.input,
-                        msg.tokens.output,
-                        msg.tokens.cache_read,
-                        msg.tokens.cache_write,
-                        msg.tokens.reasoning,
-                    );
-                    // Use calculated cost if available, otherwise keep CSV cost
-                    msg.cost = if calculated_cost > 0.0 {
-                        calculated_cost
-                    } else {
-                        csv_cost
-                    };
-                    msg
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    all_messages.extend(cursor_messages);
-
-    // Parse Amp files in parallel
-    // Calculate cost using our pricing data for consistency with other providers
-    // Fall back to credits (original cost) only if no pricing is found
-    let amp_messages: Vec<UnifiedMessage> = scan_result
-        .amp_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::amp::parse_amp_file(path)
-                .into_iter()
-                .map(|mut msg| {
-                    let credits = msg.cost; // Store original credits value
-                    let calculated_cost = pricing_data.calculate_cost(
-                        &msg.model_id,
-                        msg.tokens.input,
-                        msg.tokens.output,
-                        msg.tokens.cache_read,
-                        msg.tokens.cache_write,
-                        msg.tokens.reasoning,
-                    );
-                    // Use calculated cost if available, otherwise keep credits
-                    msg.cost = if calculated_cost > 0.0 {
-                        calculated_cost
-                    } else {
-                        credits
-                    };
-                    msg
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    all_messages.extend(amp_messages);
-
-    // Parse Droid files in parallel
-    let droid_messages: Vec<UnifiedMessage> = scan_result
-        .droid_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::droid::parse_droid_file(path)
-                .into_iter()
-                .map(|mut msg| {
-                    msg.cost = pricing_data.calculate_cost(
-                        &msg.model_id,
-                        msg.tokens.input,
-                        msg.tokens.output,
-                        msg.tokens.cache_read,
-                        msg.tokens.cache_write,
-                        msg.tokens.reasoning,
-                    );
-                    msg
-                })
-                .collect::<Vec<_>>()
-        })
+    // Recalculate cost using pricing data and each source's billing rules
+    all_messages.par_iter_mut().for_each(|msg| {
+        msg.cost = sessions::calculate_message_cost(msg, pricing_data);
+    });
+
+    Ok(PricedMessages {
+        messages: all_messages,
+        diagnostics,
+        duplicates,
+    })
+}
+
+/// Parse network-synced sources (Cursor) that are not part of `parse_local_sources`
+fn parse_synced_sources_with_pricing(
+    roots: &ScanRoots,
+    pricing_data: &PricingData,
+    zone: &DateZone,
+) -> Result<Vec<UnifiedMessage>> {
+    Ok(parse_synced_sources_with_diagnostics(roots, pricing_data, zone)?.messages)
+}
+
+/// Parse network-synced sources, also returning parse diagnostics
+fn parse_synced_sources_with_diagnostics(
+    roots: &ScanRoots,
+    pricing_data: &PricingData,
+    zone: &DateZone,
+) -> Result<PricedMessages> {
+    let synced: Vec<String> = roots
+        .sources
+        .all()
+        .iter()
+        .filter(|s| !s.is_local())
+        .map(|s| s.name().to_string())
         .collect();
-    all_messages.extend(droid_messages);
 
-    all_messages
+    if synced.is_empty() {
+        return Ok(PricedMessages {
+            messages: Vec::new(),
+            diagnostics: Vec::new(),
+            duplicates: 0,
+        });
+    }
+
+    parse_all_messages_with_diagnostics(roots, &synced, pricing_data, true, zone)
 }
 
 /// Get model usage report with pricing calculation
-#[napi]
-pub fn get_model_report(options: ReportOptions) -> napi::Result<ModelReport> {
-    let start = Instant::now();
+pub fn get_model_report(options: ReportOptions) -> Result<ModelReport> {
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    get_model_report_with_pricing_data(options, &pricing_data)
+}
 
-    let home_dir = get_home_dir(&options.home_dir)?;
+/// Same as [`get_model_report`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn get_model_report_with_pricing_data(
+    options: ReportOptions,
+    pricing_data: &PricingData,
+) -> Result<ModelReport> {
+    let start = Instant::now();
 
-    let sources = options.sources.clone().unwrap_or_else(|| {
-        vec![
-            "opencode".to_string(),
-            "claude".to_string(),
-            "codex".to_string(),
-            "gemini".to_string(),
-            "cursor".to_string(),
-            "amp".to_string(),
-            "droid".to_string(),
-        ]
-    });
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?
+    .with_archives(options.archives.clone().unwrap_or_default());
+    let zone = get_date_zone(&options.timezone)?;
+    let currency = get_currency(&options.currency, &options.exchange_rates, &roots.home_dir)?;
+
+    let sources = options
+        .sources
+        .clone()
+        .unwrap_or_else(|| roots.sources.names());
 
-    let pricing_data = build_pricing_data(&options.pricing);
-    let all_messages = parse_all_messages_with_pricing(&home_dir, &sources, &pricing_data);
+    let all_messages = parse_all_messages_with_pricing(
+        &roots,
+        &sources,
+        pricing_data,
+        options.cache.unwrap_or(true),
+        &zone,
+    )?;
 
     // Apply date filters
-    let filtered = filter_messages_for_report(all_messages, &options);
+    let mut filtered = filter_messages_for_report(all_messages, &options);
+    let unpriced_models = aggregator::unpriced_models(&filtered, pricing_data);
+    currency::convert_costs(&mut filtered, &currency);
 
     // Aggregate by model
     let mut model_map: std::collections::HashMap<String, ModelUsage> =
@@ -730,18 +1123,9 @@ pub fn get_model_report(options: ReportOptions) -> napi::Result<ModelReport> {
 
     for msg in filtered {
         let key = format!("{}:{}:{}", msg.source, msg.provider_id, msg.model_id);
-        let entry = model_map.entry(key).or_insert_with(|| ModelUsage {
-            source: msg.source.clone(),
-            model: msg.model_id.clone(),
-            provider: msg.provider_id.clone(),
-            input: 0,
-            output: 0,
-            cache_read: 0,
-            cache_write: 0,
-            reasoning: 0,
-            message_count: 0,
-            cost: 0.0,
-        });
+        let entry = model_map
+            .entry(key)
+            .or_insert_with(|| empty_model_usage(&msg, pricing_data));
 
         entry.input += msg.tokens.input;
         entry.output += msg.tokens.output;
@@ -776,12 +1160,79 @@ pub fn get_model_report(options: ReportOptions) -> napi::Result<ModelReport> {
 
     Ok(ModelReport {
         entries,
+        unpriced_models,
+        total_input,
+        total_output,
+        total_cache_read,
+        total_cache_write,
+        total_messages,
+        total_cost,
+        currency: currency.code().to_string(),
+        processing_time_ms: start.elapsed().as_millis() as u32,
+    })
+}
+
+/// Get per-project usage report with pricing calculation
+pub fn get_project_report(options: ReportOptions) -> Result<ProjectReport> {
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    get_project_report_with_pricing_data(options, &pricing_data)
+}
+
+/// Same as [`get_project_report`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn get_project_report_with_pricing_data(
+    options: ReportOptions,
+    pricing_data: &PricingData,
+) -> Result<ProjectReport> {
+    let start = Instant::now();
+
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?
+    .with_archives(options.archives.clone().unwrap_or_default());
+    let zone = get_date_zone(&options.timezone)?;
+    let currency = get_currency(&options.currency, &options.exchange_rates, &roots.home_dir)?;
+
+    let sources = options
+        .sources
+        .clone()
+        .unwrap_or_else(|| roots.sources.names());
+
+    let all_messages = parse_all_messages_with_pricing(
+        &roots,
+        &sources,
+        pricing_data,
+        options.cache.unwrap_or(true),
+        &zone,
+    )?;
+
+    // Apply date filters
+    let mut filtered = filter_messages_for_report(all_messages, &options);
+    let unpriced_models = aggregator::unpriced_models(&filtered, pricing_data);
+    currency::convert_costs(&mut filtered, &currency);
+
+    // Aggregate by project
+    let entries = aggregator::aggregate_by_project(filtered);
+
+    let total_input: i64 = entries.iter().map(|e| e.input).sum();
+    let total_output: i64 = entries.iter().map(|e| e.output).sum();
+    let total_cache_read: i64 = entries.iter().map(|e| e.cache_read).sum();
+    let total_cache_write: i64 = entries.iter().map(|e| e.cache_write).sum();
+    let total_messages: i32 = entries.iter().map(|e| e.message_count).sum();
+    let total_cost: f64 = entries.iter().map(|e| e.cost).sum();
+
+    Ok(ProjectReport {
+        entries,
+        unpriced_models,
         total_input,
         total_output,
         total_cache_read,
         total_cache_write,
         total_messages,
         total_cost,
+        currency: currency.code().to_string(),
         processing_time_ms: start.elapsed().as_millis() as u32,
     })
 }
@@ -798,30 +1249,116 @@ struct MonthAggregator {
     cost: f64,
 }
 
-/// Get monthly usage report with pricing calculation
-#[napi]
-pub fn get_monthly_report(options: ReportOptions) -> napi::Result<MonthlyReport> {
+/// Get per-session usage report with pricing calculation
+pub fn get_session_report(options: SessionReportOptions) -> Result<SessionReport> {
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    get_session_report_with_pricing_data(options, &pricing_data)
+}
+
+/// Same as [`get_session_report`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn get_session_report_with_pricing_data(
+    options: SessionReportOptions,
+    pricing_data: &PricingData,
+) -> Result<SessionReport> {
     let start = Instant::now();
 
-    let home_dir = get_home_dir(&options.home_dir)?;
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?
+    .with_archives(options.archives.clone().unwrap_or_default());
+    let zone = get_date_zone(&options.timezone)?;
+    let currency = get_currency(&options.currency, &options.exchange_rates, &roots.home_dir)?;
+
+    let sort = match options.sort_by.as_deref() {
+        Some(key) => aggregator::SessionSort::parse(key)
+            .ok_or_else(|| Error::InvalidSortKey(key.to_string()))?,
+        None => aggregator::SessionSort::Cost,
+    };
+
+    let sources = options
+        .sources
+        .clone()
+        .unwrap_or_else(|| roots.sources.names());
+
+    let all_messages = parse_all_messages_with_pricing(
+        &roots,
+        &sources,
+        pricing_data,
+        options.cache.unwrap_or(true),
+        &zone,
+    )?;
+
+    let mut filtered =
+        filter_messages_by_date(all_messages, &options.year, &options.since, &options.until);
+    let unpriced_models = aggregator::unpriced_models(&filtered, pricing_data);
+    currency::convert_costs(&mut filtered, &currency);
+
+    let mut entries = aggregator::aggregate_by_session(filtered);
+    let total_sessions = entries.len() as i32;
+    let total_messages: i32 = entries.iter().map(|e| e.message_count).sum();
+    let total_cost: f64 = entries.iter().map(|e| e.cost).sum();
 
-    let sources = options.sources.clone().unwrap_or_else(|| {
-        vec![
-            "opencode".to_string(),
-            "claude".to_string(),
-            "codex".to_string(),
-            "gemini".to_string(),
-            "cursor".to_string(),
-            "amp".to_string(),
-            "droid".to_string(),
-        ]
-    });
+    aggregator::select_sessions(
+        &mut entries,
+        sort,
+        options.limit.map(|l| l as usize),
+        options.min_cost,
+    );
+
+    Ok(SessionReport {
+        entries,
+        unpriced_models,
+        total_sessions,
+        total_messages,
+        total_cost,
+        currency: currency.code().to_string(),
+        processing_time_ms: start.elapsed().as_millis() as u32,
+    })
+}
+
+/// Get monthly usage report with pricing calculation
+pub fn get_monthly_report(options: ReportOptions) -> Result<MonthlyReport> {
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    get_monthly_report_with_pricing_data(options, &pricing_data)
+}
+
+/// Same as [`get_monthly_report`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn get_monthly_report_with_pricing_data(
+    options: ReportOptions,
+    pricing_data: &PricingData,
+) -> Result<MonthlyReport> {
+    let start = Instant::now();
+
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?
+    .with_archives(options.archives.clone().unwrap_or_default());
+    let zone = get_date_zone(&options.timezone)?;
+    let currency = get_currency(&options.currency, &options.exchange_rates, &roots.home_dir)?;
+
+    let sources = options
+        .sources
+        .clone()
+        .unwrap_or_else(|| roots.sources.names());
 
-    let pricing_data = build_pricing_data(&options.pricing);
-    let all_messages = parse_all_messages_with_pricing(&home_dir, &sources, &pricing_data);
+    let all_messages = parse_all_messages_with_pricing(
+        &roots,
+        &sources,
+        pricing_data,
+        options.cache.unwrap_or(true),
+        &zone,
+    )?;
 
     // Apply date filters
-    let filtered = filter_messages_for_report(all_messages, &options);
+    let mut filtered = filter_messages_for_report(all_messages, &options);
+    let unpriced_models = aggregator::unpriced_models(&filtered, pricing_data);
+    currency::convert_costs(&mut filtered, &currency);
 
     // Aggregate by month
     let mut month_map: std::collections::HashMap<String, MonthAggregator> =
@@ -867,42 +1404,64 @@ pub fn get_monthly_report(options: ReportOptions) -> napi::Result<MonthlyReport>
 
     Ok(MonthlyReport {
         entries,
+        unpriced_models,
         total_cost,
+        currency: currency.code().to_string(),
         processing_time_ms: start.elapsed().as_millis() as u32,
     })
 }
 
 /// Generate graph data with pricing calculation
-#[napi]
-pub fn generate_graph_with_pricing(options: ReportOptions) -> napi::Result<GraphResult> {
-    let start = Instant::now();
+pub fn generate_graph_with_pricing(options: ReportOptions) -> Result<GraphResult> {
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    generate_graph_with_pricing_data(options, &pricing_data)
+}
 
-    let home_dir = get_home_dir(&options.home_dir)?;
+/// Same as [`generate_graph_with_pricing`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn generate_graph_with_pricing_data(
+    options: ReportOptions,
+    pricing_data: &PricingData,
+) -> Result<GraphResult> {
+    let start = Instant::now();
 
-    let sources = options.sources.clone().unwrap_or_else(|| {
-        vec![
-            "opencode".to_string(),
-            "claude".to_string(),
-            "codex".to_string(),
-            "gemini".to_string(),
-            "cursor".to_string(),
-            "amp".to_string(),
-            "droid".to_string(),
-        ]
-    });
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?
+    .with_archives(options.archives.clone().unwrap_or_default());
+    let zone = get_date_zone(&options.timezone)?;
+    let currency = get_currency(&options.currency, &options.exchange_rates, &roots.home_dir)?;
+
+    let sources = options
+        .sources
+        .clone()
+        .unwrap_or_else(|| roots.sources.names());
 
-    let pricing_data = build_pricing_data(&options.pricing);
-    let all_messages = parse_all_messages_with_pricing(&home_dir, &sources, &pricing_data);
+    let parsed = parse_all_messages_with_diagnostics(
+        &roots,
+        &sources,
+        pricing_data,
+        options.cache.unwrap_or(true),
+        &zone,
+    )?;
 
     // Apply date filters
-    let filtered = filter_messages_for_report(all_messages, &options);
+    let mut filtered = filter_messages_for_report(parsed.messages, &options);
+    let unpriced_models = aggregator::unpriced_models(&filtered, pricing_data);
+    currency::convert_costs(&mut filtered, &currency);
 
     // Aggregate by date
     let contributions = aggregator::aggregate_by_date(filtered);
 
     // Generate result
     let processing_time_ms = start.elapsed().as_millis() as u32;
-    let result = aggregator::generate_graph_result(contributions, processing_time_ms);
+    let mut result = aggregator::generate_graph_result(contributions, processing_time_ms);
+    result.unpriced_models = unpriced_models;
+    result.diagnostics = parsed.diagnostics;
+    result.meta.currency = currency.code().to_string();
+    result.meta.duplicate_count = parsed.duplicates as i32;
 
     Ok(result)
 }
@@ -911,22 +1470,32 @@ pub fn generate_graph_with_pricing(options: ReportOptions) -> napi::Result<Graph
 fn filter_messages_for_report(
     messages: Vec<UnifiedMessage>,
     options: &ReportOptions,
+) -> Vec<UnifiedMessage> {
+    filter_messages_by_date(messages, &options.year, &options.since, &options.until)
+}
+
+/// Filter messages by year and inclusive since/until dates
+fn filter_messages_by_date(
+    messages: Vec<UnifiedMessage>,
+    year: &Option<String>,
+    since: &Option<String>,
+    until: &Option<String>,
 ) -> Vec<UnifiedMessage> {
     let mut filtered = messages;
 
     // Filter by year
-    if let Some(year) = &options.year {
+    if let Some(year) = year {
         let year_prefix = format!("{}-", year);
         filtered.retain(|m| m.date.starts_with(&year_prefix));
     }
 
     // Filter by since date
-    if let Some(since) = &options.since {
+    if let Some(since) = since {
         filtered.retain(|m| m.date.as_str() >= since.as_str());
     }
 
     // Filter by until date
-    if let Some(until) = &options.until {
+    if let Some(until) = until {
         filtered.retain(|m| m.date.as_str() <= until.as_str());
     }
 
@@ -939,124 +1508,64 @@ fn filter_messages_for_report(
 
 /// Parse local sources only (OpenCode, Claude, Codex, Gemini - NO Cursor)
 /// This can run in parallel with network operations (Cursor sync, pricing fetch)
-#[napi]
-pub fn parse_local_sources(options: LocalParseOptions) -> napi::Result<ParsedMessages> {
+pub fn parse_local_sources(options: LocalParseOptions) -> Result<ParsedMessages> {
     let start = Instant::now();
 
-    let home_dir = get_home_dir(&options.home_dir)?;
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?
+    .with_archives(options.archives.clone().unwrap_or_default());
+    let zone = get_date_zone(&options.timezone)?;
 
     // Default to local sources only (no cursor)
-    let sources = options.sources.clone().unwrap_or_else(|| {
-        vec![
-            "opencode".to_string(),
-            "claude".to_string(),
-            "codex".to_string(),
-            "gemini".to_string(),
-            "amp".to_string(),
-            "droid".to_string(),
-        ]
-    });
+    let sources = options
+        .sources
+        .clone()
+        .unwrap_or_else(|| roots.sources.local_names());
 
-    // Filter out cursor if somehow included
-    let local_sources: Vec<String> = sources.into_iter().filter(|s| s != "cursor").collect();
+    // Filter out network-synced sources if somehow included
+    let local_sources: Vec<String> = sources
+        .into_iter()
+        .filter(|s| {
+            roots
+                .sources
+                .find(s)
+                .is_some_and(|source| source.is_local())
+        })
+        .collect();
 
-    let scan_result = scanner::scan_all_sources(&home_dir, &local_sources);
+    let (parsed_sources, mut diagnostics) =
+        scan_and_parse(&roots, &local_sources, options.cache.unwrap_or(true), &zone)?;
 
     let mut messages: Vec<ParsedMessage> = Vec::new();
-
-    // Parse OpenCode files in parallel
-    let opencode_msgs: Vec<ParsedMessage> = scan_result
-        .opencode_files
-        .par_iter()
-        .filter_map(|path| {
-            let msg = sessions::opencode::parse_opencode_file(path)?;
-            Some(unified_to_parsed(&msg))
-        })
-        .collect();
-    let opencode_count = opencode_msgs.len() as i32;
-    messages.extend(opencode_msgs);
-
-    // Parse Claude files in parallel
-    let claude_msgs: Vec<ParsedMessage> = scan_result
-        .claude_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::claudecode::parse_claude_file(path)
-                .into_iter()
-                .map(|msg| unified_to_parsed(&msg))
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    let claude_count = claude_msgs.len() as i32;
-    messages.extend(claude_msgs);
-
-    // Parse Codex files in parallel
-    let codex_msgs: Vec<ParsedMessage> = scan_result
-        .codex_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::codex::parse_codex_file(path)
-                .into_iter()
-                .map(|msg| unified_to_parsed(&msg))
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    let codex_count = codex_msgs.len() as i32;
-    messages.extend(codex_msgs);
-
-    // Parse Gemini files in parallel
-    let gemini_msgs: Vec<ParsedMessage> = scan_result
-        .gemini_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::gemini::parse_gemini_file(path)
-                .into_iter()
-                .map(|msg| unified_to_parsed(&msg))
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    let gemini_count = gemini_msgs.len() as i32;
-    messages.extend(gemini_msgs);
-
-    // Parse Amp files in parallel
-    let amp_msgs: Vec<ParsedMessage> = scan_result
-        .amp_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::amp::parse_amp_file(path)
-                .into_iter()
-                .map(|msg| unified_to_parsed(&msg))
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    let amp_count = amp_msgs.len() as i32;
-    messages.extend(amp_msgs);
-
-    // Parse Droid files in parallel
-    let droid_msgs: Vec<ParsedMessage> = scan_result
-        .droid_files
-        .par_iter()
-        .flat_map(|path| {
-            sessions::droid::parse_droid_file(path)
-                .into_iter()
-                .map(|msg| unified_to_parsed(&msg))
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    let droid_count = droid_msgs.len() as i32;
-    messages.extend(droid_msgs);
+    let mut source_counts: HashMap<String, i32> = HashMap::new();
+    let mut duplicate_count = 0;
+
+    for parsed in parsed_sources {
+        source_counts.insert(parsed.source.to_string(), parsed.messages.len() as i32);
+        duplicate_count += parsed.duplicates as i32;
+        messages.extend(parsed.messages.iter().map(unified_to_parsed));
+        diagnostics.extend(parsed.diagnostics);
+    }
 
     // Apply date filters
     let filtered = filter_parsed_messages(messages, &options);
 
+    let count = |name: &str| source_counts.get(name).copied().unwrap_or(0);
+
     Ok(ParsedMessages {
         messages: filtered,
-        opencode_count,
-        claude_count,
-        codex_count,
-        gemini_count,
-        amp_count,
-        droid_count,
+        opencode_count: count("opencode"),
+        claude_count: count("claude"),
+        codex_count: count("codex"),
+        gemini_count: count("gemini"),
+        amp_count: count("amp"),
+        droid_count: count("droid"),
+        source_counts: Some(source_counts),
+        duplicate_count: Some(duplicate_count),
+        diagnostics: Some(diagnostics),
         processing_time_ms: start.elapsed().as_millis() as u32,
     })
 }
@@ -1075,6 +1584,9 @@ fn unified_to_parsed(msg: &UnifiedMessage) -> ParsedMessage {
         cache_write: msg.tokens.cache_write,
         reasoning: msg.tokens.reasoning,
         agent: msg.agent.clone(),
+        project: msg.project.clone(),
+        root: msg.root.clone(),
+        recorded_cost: (msg.cost > 0.0).then_some(msg.cost),
     }
 }
 
@@ -1101,7 +1613,7 @@ fn filter_parsed_messages(
     filtered
 }
 
-fn parsed_to_unified(msg: &ParsedMessage, cost: f64) -> UnifiedMessage {
+fn parsed_to_unified(msg: &ParsedMessage) -> UnifiedMessage {
     UnifiedMessage {
         source: msg.source.clone(),
         model_id: msg.model_id.clone(),
@@ -1116,19 +1628,35 @@ fn parsed_to_unified(msg: &ParsedMessage, cost: f64) -> UnifiedMessage {
             cache_write: msg.cache_write,
             reasoning: msg.reasoning,
         },
-        cost,
+        cost: msg.recorded_cost.unwrap_or(0.0),
         agent: msg.agent.clone(),
+        project: msg.project.clone(),
+        dedup_key: None,
+        root: msg.root.clone(),
     }
 }
 
 /// Finalize model report: apply pricing to local messages, add Cursor, aggregate
-#[napi]
-pub fn finalize_report(options: FinalizeReportOptions) -> napi::Result<ModelReport> {
-    let start = Instant::now();
+pub fn finalize_report(options: FinalizeReportOptions) -> Result<ModelReport> {
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    finalize_report_with_pricing_data(options, &pricing_data)
+}
 
-    let home_dir = get_home_dir(&options.home_dir)?;
+/// Same as [`finalize_report`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn finalize_report_with_pricing_data(
+    options: FinalizeReportOptions,
+    pricing_data: &PricingData,
+) -> Result<ModelReport> {
+    let start = Instant::now();
 
-    let pricing_data = build_pricing_data(&options.pricing);
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?;
+    let zone = get_date_zone(&options.timezone)?;
+    let currency = get_currency(&options.currency, &options.exchange_rates, &roots.home_dir)?;
 
     // Convert local messages and apply pricing
     let mut all_messages: Vec<UnifiedMessage> = options
@@ -1136,50 +1664,19 @@ pub fn finalize_report(options: FinalizeReportOptions) -> napi::Result<ModelRepo
         .messages
         .iter()
         .map(|msg| {
-            let cost = pricing_data.calculate_cost(
-                &msg.model_id,
-                msg.input,
-                msg.output,
-                msg.cache_read,
-                msg.cache_write,
-                msg.reasoning,
-            );
-            parsed_to_unified(msg, cost)
+            let mut unified = parsed_to_unified(msg);
+            unified.cost = sessions::calculate_message_cost(&unified, pricing_data);
+            unified
         })
         .collect();
 
     // Add Cursor messages if enabled
     if options.include_cursor {
-        let cursor_cache_dir = format!("{}/.config/tokscale/cursor-cache", home_dir);
-        let cursor_files = scanner::scan_directory(&cursor_cache_dir, "*.csv");
-
-        let cursor_messages: Vec<UnifiedMessage> = cursor_files
-            .par_iter()
-            .flat_map(|path| {
-                sessions::cursor::parse_cursor_file(path)
-                    .into_iter()
-                    .map(|mut msg| {
-                        let csv_cost = msg.cost;
-                        let calculated_cost = pricing_data.calculate_cost(
-                            &msg.model_id,
-                            msg.tokens.input,
-                            msg.tokens.output,
-                            msg.tokens.cache_read,
-                            msg.tokens.cache_write,
-                            msg.tokens.reasoning,
-                        );
-                        msg.cost = if calculated_cost > 0.0 {
-                            calculated_cost
-                        } else {
-                            csv_cost
-                        };
-                        msg
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect();
-
-        all_messages.extend(cursor_messages);
+        all_messages.extend(parse_synced_sources_with_pricing(
+            &roots,
+            pricing_data,
+            &zone,
+        )?);
     }
 
     // Apply date filters to cursor messages (local already filtered)
@@ -1196,24 +1693,18 @@ pub fn finalize_report(options: FinalizeReportOptions) -> napi::Result<ModelRepo
         }
     }
 
+    let unpriced_models = aggregator::unpriced_models(&all_messages, pricing_data);
+    currency::convert_costs(&mut all_messages, &currency);
+
     // Aggregate by model
     let mut model_map: std::collections::HashMap<String, ModelUsage> =
         std::collections::HashMap::new();
 
     for msg in all_messages {
         let key = format!("{}:{}:{}", msg.source, msg.provider_id, msg.model_id);
-        let entry = model_map.entry(key).or_insert_with(|| ModelUsage {
-            source: msg.source.clone(),
-            model: msg.model_id.clone(),
-            provider: msg.provider_id.clone(),
-            input: 0,
-            output: 0,
-            cache_read: 0,
-            cache_write: 0,
-            reasoning: 0,
-            message_count: 0,
-            cost: 0.0,
-        });
+        let entry = model_map
+            .entry(key)
+            .or_insert_with(|| empty_model_usage(&msg, pricing_data));
 
         entry.input += msg.tokens.input;
         entry.output += msg.tokens.output;
@@ -1244,37 +1735,67 @@ pub fn finalize_report(options: FinalizeReportOptions) -> napi::Result<ModelRepo
 
     Ok(ModelReport {
         entries,
+        unpriced_models,
         total_input,
         total_output,
         total_cache_read,
         total_cache_write,
         total_messages,
         total_cost,
+        currency: currency.code().to_string(),
         processing_time_ms: start.elapsed().as_millis() as u32,
     })
 }
 
 /// Options for finalizing monthly report
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FinalizeMonthlyOptions {
     pub home_dir: Option<String>,
+    /// Additional home directories, e.g. logs synced from containers or other
+    /// machines. Scanned with each tool's default layout; environment overrides
+    /// such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+    pub extra_home_dirs: Option<Vec<String>>,
+    /// Directories to scan per source name, replacing that source's default
+    /// locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+    pub source_paths: Option<HashMap<String, Vec<String>>>,
     pub local_messages: ParsedMessages,
     pub pricing: Vec<PricingEntry>,
     pub include_cursor: bool,
     pub since: Option<String>,
     pub until: Option<String>,
     pub year: Option<String>,
+    /// Timezone for network-synced (Cursor) messages; local messages keep the
+    /// dates assigned by `parse_local_sources`. Defaults to UTC.
+    pub timezone: Option<String>,
+    /// Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+    /// Defaults to USD; other currencies need an exchange-rate table.
+    pub currency: Option<String>,
+    /// Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json)
+    pub exchange_rates: Option<String>,
 }
 
 /// Finalize monthly report with pricing
-#[napi]
-pub fn finalize_monthly_report(options: FinalizeMonthlyOptions) -> napi::Result<MonthlyReport> {
-    let start = Instant::now();
+pub fn finalize_monthly_report(options: FinalizeMonthlyOptions) -> Result<MonthlyReport> {
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    finalize_monthly_report_with_pricing_data(options, &pricing_data)
+}
 
-    let home_dir = get_home_dir(&options.home_dir)?;
+/// Same as [`finalize_monthly_report`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn finalize_monthly_report_with_pricing_data(
+    options: FinalizeMonthlyOptions,
+    pricing_data: &PricingData,
+) -> Result<MonthlyReport> {
+    let start = Instant::now();
 
-    let pricing_data = build_pricing_data(&options.pricing);
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?;
+    let zone = get_date_zone(&options.timezone)?;
+    let currency = get_currency(&options.currency, &options.exchange_rates, &roots.home_dir)?;
 
     // Convert local messages and apply pricing
     let mut all_messages: Vec<UnifiedMessage> = options
@@ -1282,50 +1803,19 @@ pub fn finalize_monthly_report(options: FinalizeMonthlyOptions) -> napi::Result<
         .messages
         .iter()
         .map(|msg| {
-            let cost = pricing_data.calculate_cost(
-                &msg.model_id,
-                msg.input,
-                msg.output,
-                msg.cache_read,
-                msg.cache_write,
-                msg.reasoning,
-            );
-            parsed_to_unified(msg, cost)
+            let mut unified = parsed_to_unified(msg);
+            unified.cost = sessions::calculate_message_cost(&unified, pricing_data);
+            unified
         })
         .collect();
 
     // Add Cursor messages if enabled
     if options.include_cursor {
-        let cursor_cache_dir = format!("{}/.config/tokscale/cursor-cache", home_dir);
-        let cursor_files = scanner::scan_directory(&cursor_cache_dir, "*.csv");
-
-        let cursor_messages: Vec<UnifiedMessage> = cursor_files
-            .par_iter()
-            .flat_map(|path| {
-                sessions::cursor::parse_cursor_file(path)
-                    .into_iter()
-                    .map(|mut msg| {
-                        let csv_cost = msg.cost;
-                        let calculated_cost = pricing_data.calculate_cost(
-                            &msg.model_id,
-                            msg.tokens.input,
-                            msg.tokens.output,
-                            msg.tokens.cache_read,
-                            msg.tokens.cache_write,
-                            msg.tokens.reasoning,
-                        );
-                        msg.cost = if calculated_cost > 0.0 {
-                            calculated_cost
-                        } else {
-                            csv_cost
-                        };
-                        msg
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect();
-
-        all_messages.extend(cursor_messages);
+        all_messages.extend(parse_synced_sources_with_pricing(
+            &roots,
+            pricing_data,
+            &zone,
+        )?);
     }
 
     // Apply date filters
@@ -1340,6 +1830,9 @@ pub fn finalize_monthly_report(options: FinalizeMonthlyOptions) -> napi::Result<
         all_messages.retain(|m| m.date.as_str() <= until.as_str());
     }
 
+    let unpriced_models = aggregator::unpriced_models(&all_messages, pricing_data);
+    currency::convert_costs(&mut all_messages, &currency);
+
     // Aggregate by month
     let mut month_map: std::collections::HashMap<String, MonthAggregator> =
         std::collections::HashMap::new();
@@ -1380,32 +1873,62 @@ pub fn finalize_monthly_report(options: FinalizeMonthlyOptions) -> napi::Result<
 
     Ok(MonthlyReport {
         entries,
+        unpriced_models,
         total_cost,
+        currency: currency.code().to_string(),
         processing_time_ms: start.elapsed().as_millis() as u32,
     })
 }
 
 /// Options for finalizing graph
-#[napi(object)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FinalizeGraphOptions {
     pub home_dir: Option<String>,
+    /// Additional home directories, e.g. logs synced from containers or other
+    /// machines. Scanned with each tool's default layout; environment overrides
+    /// such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+    pub extra_home_dirs: Option<Vec<String>>,
+    /// Directories to scan per source name, replacing that source's default
+    /// locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+    pub source_paths: Option<HashMap<String, Vec<String>>>,
     pub local_messages: ParsedMessages,
     pub pricing: Vec<PricingEntry>,
     pub include_cursor: bool,
     pub since: Option<String>,
     pub until: Option<String>,
     pub year: Option<String>,
+    /// Timezone for network-synced (Cursor) messages; local messages keep the
+    /// dates assigned by `parse_local_sources`. Defaults to UTC.
+    pub timezone: Option<String>,
+    /// Currency for costs: an ISO 4217 code such as "KRW", "JPY" or "EUR".
+    /// Defaults to USD; other currencies need an exchange-rate table.
+    pub currency: Option<String>,
+    /// Exchange-rate table for `currency` (defaults to ~/.config/tokscale/exchange-rates.json)
+    pub exchange_rates: Option<String>,
 }
 
 /// Finalize graph with pricing
-#[napi]
-pub fn finalize_graph(options: FinalizeGraphOptions) -> napi::Result<GraphResult> {
-    let start = Instant::now();
+pub fn finalize_graph(options: FinalizeGraphOptions) -> Result<GraphResult> {
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    finalize_graph_with_pricing_data(options, &pricing_data)
+}
 
-    let home_dir = get_home_dir(&options.home_dir)?;
+/// Same as [`finalize_graph`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn finalize_graph_with_pricing_data(
+    options: FinalizeGraphOptions,
+    pricing_data: &PricingData,
+) -> Result<GraphResult> {
+    let start = Instant::now();
 
-    let pricing_data = build_pricing_data(&options.pricing);
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?;
+    let zone = get_date_zone(&options.timezone)?;
+    let currency = get_currency(&options.currency, &options.exchange_rates, &roots.home_dir)?;
 
     // Convert local messages and apply pricing
     let mut all_messages: Vec<UnifiedMessage> = options
@@ -1413,50 +1936,25 @@ pub fn finalize_graph(options: FinalizeGraphOptions) -> napi::Result<GraphResult
         .messages
         .iter()
         .map(|msg| {
-            let cost = pricing_data.calculate_cost(
-                &msg.model_id,
-                msg.input,
-                msg.output,
-                msg.cache_read,
-                msg.cache_write,
-                msg.reasoning,
-            );
-            parsed_to_unified(msg, cost)
+            let mut unified = parsed_to_unified(msg);
+            unified.cost = sessions::calculate_message_cost(&unified, pricing_data);
+            unified
         })
         .collect();
 
+    let mut diagnostics = options
+        .local_messages
+        .diagnostics
+        .clone()
+        .unwrap_or_default();
+    let mut duplicate_count = options.local_messages.duplicate_count.unwrap_or(0);
+
     // Add Cursor messages if enabled
     if options.include_cursor {
-        let cursor_cache_dir = format!("{}/.config/tokscale/cursor-cache", home_dir);
-        let cursor_files = scanner::scan_directory(&cursor_cache_dir, "*.csv");
-
-        let cursor_messages: Vec<UnifiedMessage> = cursor_files
-            .par_iter()
-            .flat_map(|path| {
-                sessions::cursor::parse_cursor_file(path)
-                    .into_iter()
-                    .map(|mut msg| {
-                        let csv_cost = msg.cost;
-                        let calculated_cost = pricing_data.calculate_cost(
-                            &msg.model_id,
-                            msg.tokens.input,
-                            msg.tokens.output,
-                            msg.tokens.cache_read,
-                            msg.tokens.cache_write,
-                            msg.tokens.reasoning,
-                        );
-                        msg.cost = if calculated_cost > 0.0 {
-                            calculated_cost
-                        } else {
-                            csv_cost
-                        };
-                        msg
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect();
-
-        all_messages.extend(cursor_messages);
+        let synced = parse_synced_sources_with_diagnostics(&roots, pricing_data, &zone)?;
+        all_messages.extend(synced.messages);
+        diagnostics.extend(synced.diagnostics);
+        duplicate_count += synced.duplicates as i32;
     }
 
     // Apply date filters
@@ -1471,12 +1969,180 @@ pub fn finalize_graph(options: FinalizeGraphOptions) -> napi::Result<GraphResult
         all_messages.retain(|m| m.date.as_str() <= until.as_str());
     }
 
+    let unpriced_models = aggregator::unpriced_models(&all_messages, pricing_data);
+    currency::convert_costs(&mut all_messages, &currency);
+
     // Aggregate by date
     let contributions = aggregator::aggregate_by_date(all_messages);
 
     // Generate result
     let processing_time_ms = start.elapsed().as_millis() as u32;
-    let result = aggregator::generate_graph_result(contributions, processing_time_ms);
+    let mut result = aggregator::generate_graph_result(contributions, processing_time_ms);
+    result.unpriced_models = unpriced_models;
+    result.diagnostics = diagnostics;
+    result.meta.currency = currency.code().to_string();
+    result.meta.duplicate_count = duplicate_count;
 
     Ok(result)
 }
+
+// =============================================================================
+// Watch Mode
+// =============================================================================
+
+/// Options for watching session files
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct WatchOptions {
+    /// Home directory path (defaults to user's home)
+    pub home_dir: Option<String>,
+    /// Additional home directories, e.g. logs synced from containers or other
+    /// machines. Scanned with each tool's default layout; environment overrides
+    /// such as `CLAUDE_CONFIG_DIR` or `CODEX_HOME` only apply to `home_dir`.
+    pub extra_home_dirs: Option<Vec<String>>,
+    /// Directories to scan per source name, replacing that source's default
+    /// locations (e.g. `{"claude": ["/mnt/devbox/.claude/projects"]}`)
+    pub source_paths: Option<HashMap<String, Vec<String>>>,
+    /// Sources to watch (defaults to every local source)
+    pub sources: Option<Vec<String>>,
+    /// Pricing data for cost calculation
+    pub pricing: Vec<PricingEntry>,
+    /// Timezone for day bucketing: IANA name ("Asia/Seoul") or UTC offset ("+09:00").
+    /// Defaults to UTC.
+    pub timezone: Option<String>,
+    /// Quiet period before a burst of file changes is processed (defaults to 200ms)
+    pub debounce_ms: Option<u32>,
+}
+
+/// Change in daily contributions caused by a batch of file changes
+#[cfg_attr(feature = "napi", napi(object))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WatchEvent {
+    /// Per-day deltas; usage from rewritten or deleted files shows up as negative values
+    pub contributions: Vec<DailyContribution>,
+    /// Session files that were re-read
+    pub changed_files: Vec<String>,
+}
+
+/// Watch the session files of the selected sources and call `on_update`
+/// whenever their usage changes. Watching stops when the handle is closed or
+/// dropped.
+pub fn watch<F>(options: WatchOptions, on_update: F) -> Result<WatchHandle>
+where
+    F: FnMut(WatchEvent) + Send + 'static,
+{
+    let pricing_data = build_pricing_data(&options.pricing)?;
+    watch_with_pricing_data(options, pricing_data, on_update)
+}
+
+/// Same as [`watch`], but priced with already loaded pricing data instead of
+/// `options.pricing`
+pub fn watch_with_pricing_data<F>(
+    options: WatchOptions,
+    pricing_data: PricingData,
+    mut on_update: F,
+) -> Result<WatchHandle>
+where
+    F: FnMut(WatchEvent) + Send + 'static,
+{
+    let roots = get_scan_roots(
+        &options.home_dir,
+        &options.extra_home_dirs,
+        &options.source_paths,
+    )?;
+    let zone = get_date_zone(&options.timezone)?;
+    let sources = options
+        .sources
+        .clone()
+        .unwrap_or_else(|| roots.sources.local_names());
+    let debounce = std::time::Duration::from_millis(options.debounce_ms.unwrap_or(200) as u64);
+
+    let session_watcher = watcher::SessionWatcher::new(roots, &sources, pricing_data, zone);
+    let handle = watcher::watch(session_watcher, debounce, move |update| {
+        on_update(WatchEvent {
+            contributions: update.contributions,
+            changed_files: update
+                .changed_files
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+        })
+    })?;
+
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes one Amp thread with a model missing from the pricing data
+    fn write_amp_thread(dir: &std::path::Path) {
+        let thread = r#"{
+            "id": "T-1",
+            "usageLedger": {"events": [{
+                "timestamp": "2025-06-01T10:00:00Z",
+                "model": "amp-internal-model",
+                "credits": 0.42,
+                "tokens": {"input": 1000, "output": 200}
+            }]}
+        }"#;
+        std::fs::write(dir.join("T-1.json"), thread).unwrap();
+    }
+
+    #[test]
+    fn test_two_phase_report_keeps_recorded_cost() {
+        let dir = tempfile::tempdir().unwrap();
+        write_amp_thread(dir.path());
+        let home_dir = Some(dir.path().to_string_lossy().to_string());
+        let source_paths = Some(HashMap::from([(
+            "amp".to_string(),
+            vec![dir.path().to_string_lossy().to_string()],
+        )]));
+        let sources = Some(vec!["amp".to_string()]);
+
+        let direct = get_model_report(ReportOptions {
+            home_dir: home_dir.clone(),
+            extra_home_dirs: None,
+            source_paths: source_paths.clone(),
+            archives: None,
+            sources: sources.clone(),
+            pricing: Vec::new(),
+            since: None,
+            until: None,
+            year: None,
+            timezone: None,
+            currency: None,
+            exchange_rates: None,
+            cache: Some(false),
+        })
+        .unwrap();
+
+        let local_messages = parse_local_sources(LocalParseOptions {
+            home_dir: home_dir.clone(),
+            source_paths: source_paths.clone(),
+            sources,
+            cache: Some(false),
+            ..Default::default()
+        })
+        .unwrap();
+        let two_phase = finalize_report(FinalizeReportOptions {
+            home_dir,
+            extra_home_dirs: None,
+            source_paths,
+            local_messages,
+            pricing: Vec::new(),
+            include_cursor: false,
+            since: None,
+            until: None,
+            year: None,
+            timezone: None,
+            currency: None,
+            exchange_rates: None,
+        })
+        .unwrap();
+
+        assert!((direct.total_cost - 0.42).abs() < 1e-9);
+        assert!((two_phase.total_cost - direct.total_cost).abs() < 1e-9);
+    }
+}
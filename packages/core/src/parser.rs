@@ -1,11 +1,60 @@
 //! SIMD-accelerated JSON parser
 //!
-//! Uses simd-json for fast JSON parsing with SIMD instructions.
+//! Uses simd-json for fast JSON parsing with SIMD instructions. JSONL files
+//! are streamed through a per-thread line buffer so large session histories
+//! are read without allocating per line.
 
+use memchr::memmem;
+use std::cell::RefCell;
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
 use std::path::Path;
 
+/// Read buffer size for JSONL files; session lines are often several KB
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Line buffers larger than this are not kept for reuse after a file is read
+const MAX_RETAINED_LINE_BUFFER: usize = 1024 * 1024;
+
+/// JSONL files larger than this are split into chunks of about this size
+/// and parsed in parallel
+pub const PARALLEL_CHUNK_SIZE: u64 = 32 * 1024 * 1024;
+
+/// Number of bytes before a resume offset covered by `fingerprint_before`
+const FINGERPRINT_SIZE: u64 = 4 * 1024;
+
+thread_local! {
+    /// Line buffer reused by every JSONL file read on this thread
+    static LINE_BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Cheap pre-check that skips JSONL lines which cannot hold anything a
+/// parser needs, before paying for deserialization
+#[derive(Debug, Clone, Copy)]
+pub struct LineFilter {
+    needles: &'static [&'static str],
+}
+
+impl LineFilter {
+    /// Deserialize every line
+    pub const ALL: Self = Self { needles: &[] };
+
+    /// Only deserialize lines containing at least one of `needles`
+    pub const fn containing_any(needles: &'static [&'static str]) -> Self {
+        Self { needles }
+    }
+
+    pub fn matches(&self, line: &[u8]) -> bool {
+        self.needles.is_empty()
+            || self
+                .needles
+                .iter()
+                .any(|needle| memmem::find(line, needle.as_bytes()).is_some())
+    }
+}
+
 /// Parse a JSON file using SIMD-accelerated parsing
 pub fn parse_json_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, ParseError> {
     let mut data = fs::read(path).map_err(|e| ParseError::IoError(e.to_string()))?;
@@ -14,29 +63,233 @@ pub fn parse_json_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T,
 }
 
 /// Parse a JSONL file (one JSON object per line)
-pub fn parse_jsonl_file<T, F>(path: &Path, mut process: F) -> Result<(), ParseError>
+///
+/// Malformed lines are skipped; returns how many were skipped.
+pub fn parse_jsonl_file<T, F>(path: &Path, mut process: F) -> Result<usize, ParseError>
 where
     T: serde::de::DeserializeOwned,
     F: FnMut(T),
 {
-    let file = fs::File::open(path).map_err(|e| ParseError::IoError(e.to_string()))?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line.map_err(|e| ParseError::IoError(e.to_string()))?;
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
+    let progress = read_jsonl_lines_from(path, 0, LineFilter::ALL, |line| {
+        match simd_json::from_slice::<T>(line) {
+            Ok(value) => {
+                process(value);
+                true
+            }
+            Err(_) => false, // Skip malformed lines (match TypeScript behavior)
+        }
+    })?;
+
+    Ok(progress.skipped_lines as usize)
+}
+
+/// Outcome of `read_jsonl_lines_from`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonlProgress {
+    /// Offset just past the last consumed line
+    pub offset: u64,
+    /// Number of lines consumed, including blank and malformed ones
+    pub lines: u64,
+    /// Lines that `process` rejected
+    pub skipped_lines: u64,
+    /// 1-based number of the first rejected line, counted from the start offset
+    pub first_skipped_line: Option<u64>,
+}
+
+impl JsonlProgress {
+    /// Extend with the progress of the range that follows this one
+    pub fn append(&mut self, next: JsonlProgress) {
+        if self.first_skipped_line.is_none() {
+            self.first_skipped_line = next.first_skipped_line.map(|line| self.lines + line);
+        }
+        self.offset = next.offset;
+        self.lines += next.lines;
+        self.skipped_lines += next.skipped_lines;
+    }
+}
+
+/// Read the lines of a JSONL file starting at byte `offset`
+///
+/// `process` receives each non-empty, trimmed line that passes `filter` and
+/// returns whether it was valid JSON. Lines rejected by the filter are
+/// consumed without being checked. A final line without a trailing newline
+/// is only consumed if it parses, since the writer may still be appending
+/// to it.
+pub fn read_jsonl_lines_from<F>(
+    path: &Path,
+    offset: u64,
+    filter: LineFilter,
+    process: F,
+) -> Result<JsonlProgress, ParseError>
+where
+    F: FnMut(&mut [u8]) -> bool,
+{
+    read_jsonl_range(path, offset..u64::MAX, filter, process)
+}
+
+/// Read the lines of a JSONL file within a byte range, as produced by
+/// `split_jsonl_chunks`. Behaves like `read_jsonl_lines_from` otherwise.
+pub fn read_jsonl_range<F>(
+    path: &Path,
+    range: Range<u64>,
+    filter: LineFilter,
+    process: F,
+) -> Result<JsonlProgress, ParseError>
+where
+    F: FnMut(&mut [u8]) -> bool,
+{
+    let mut file = fs::File::open(path).map_err(|e| ParseError::IoError(e.to_string()))?;
+    if range.start > 0 {
+        file.seek(SeekFrom::Start(range.start))
+            .map_err(|e| ParseError::IoError(e.to_string()))?;
+    }
+    let limit = range.end.saturating_sub(range.start);
+    let reader = BufReader::with_capacity(READ_BUFFER_SIZE, file.take(limit));
+    with_line_buffer(|buf| read_lines(reader, buf, range.start, filter, process))
+}
+
+/// Read the lines of JSONL data already in memory (e.g. an archive entry).
+/// Behaves like `read_jsonl_lines_from` at offset 0.
+pub fn read_jsonl_bytes<F>(
+    data: &[u8],
+    filter: LineFilter,
+    process: F,
+) -> Result<JsonlProgress, ParseError>
+where
+    F: FnMut(&mut [u8]) -> bool,
+{
+    with_line_buffer(|buf| read_lines(data, buf, 0, filter, process))
+}
+
+fn with_line_buffer<T>(read: impl FnOnce(&mut Vec<u8>) -> T) -> T {
+    // Take the buffer rather than borrowing it so a nested read on the same
+    // thread gets its own
+    let mut buf = LINE_BUFFER.with(|cell| std::mem::take(&mut *cell.borrow_mut()));
+    let result = read(&mut buf);
+
+    buf.clear();
+    if buf.capacity() <= MAX_RETAINED_LINE_BUFFER {
+        LINE_BUFFER.with(|cell| *cell.borrow_mut() = buf);
+    }
+    result
+}
+
+fn read_lines<R, F>(
+    mut reader: R,
+    buf: &mut Vec<u8>,
+    offset: u64,
+    filter: LineFilter,
+    mut process: F,
+) -> Result<JsonlProgress, ParseError>
+where
+    R: BufRead,
+    F: FnMut(&mut [u8]) -> bool,
+{
+    let mut progress = JsonlProgress {
+        offset,
+        ..Default::default()
+    };
+
+    loop {
+        buf.clear();
+        let read = reader
+            .read_until(b'\n', buf)
+            .map_err(|e| ParseError::IoError(e.to_string()))?;
+        if read == 0 {
+            break;
         }
 
-        let mut bytes = trimmed.as_bytes().to_vec();
-        match simd_json::from_slice::<T>(&mut bytes) {
-            Ok(value) => process(value),
-            Err(_) => continue, // Skip malformed lines (match TypeScript behavior)
+        let complete = buf.last() == Some(&b'\n');
+        let line = trim_ascii(buf);
+        let valid = if line.is_empty() {
+            true
+        } else if filter.matches(line) {
+            process(line)
+        } else {
+            // Nothing of interest, but a partial line may still grow into one
+            if !complete {
+                break;
+            }
+            true
+        };
+
+        if !complete && !valid {
+            break;
+        }
+        progress.offset += read as u64;
+        progress.lines += 1;
+        if !valid {
+            progress.skipped_lines += 1;
+            progress.first_skipped_line.get_or_insert(progress.lines);
         }
     }
 
-    Ok(())
+    Ok(progress)
+}
+
+/// Split a JSONL file from `offset` to its end into byte ranges of roughly
+/// `chunk_size` bytes, each starting at the beginning of a line
+///
+/// The last range is open-ended so it also covers anything appended after
+/// the split. Files smaller than `chunk_size` yield a single range.
+pub fn split_jsonl_chunks(
+    path: &Path,
+    offset: u64,
+    chunk_size: u64,
+) -> Result<Vec<Range<u64>>, ParseError> {
+    let io_error = |e: std::io::Error| ParseError::IoError(e.to_string());
+    let mut file = fs::File::open(path).map_err(io_error)?;
+    let len = file.metadata().map_err(io_error)?.len();
+
+    let mut starts = vec![offset];
+    let mut target = offset.saturating_add(chunk_size.max(1));
+    while target < len {
+        // Look for the first newline at or after the byte before `target`, so
+        // a line starting exactly at `target` begins the next chunk
+        file.seek(SeekFrom::Start(target - 1)).map_err(io_error)?;
+        let mut reader = BufReader::with_capacity(READ_BUFFER_SIZE, &mut file);
+        let skipped = reader.skip_until(b'\n').map_err(io_error)? as u64;
+        let boundary = target - 1 + skipped;
+        if boundary >= len {
+            break;
+        }
+        starts.push(boundary);
+        target = boundary + chunk_size.max(1);
+    }
+
+    let mut ends: Vec<u64> = starts[1..].to_vec();
+    ends.push(u64::MAX);
+    Ok(starts.into_iter().zip(ends).map(|(s, e)| s..e).collect())
+}
+
+/// Hash of the bytes just before `offset` (up to `FINGERPRINT_SIZE` of them),
+/// used to tell a file that was only appended to from one that was rewritten.
+/// `None` if the file is shorter than `offset` or cannot be read.
+pub fn fingerprint_before(path: &Path, offset: u64) -> Option<u64> {
+    let start = offset.saturating_sub(FINGERPRINT_SIZE);
+    let mut file = fs::File::open(path).ok()?;
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut bytes = Vec::with_capacity((offset - start) as usize);
+    file.take(offset - start).read_to_end(&mut bytes).ok()?;
+    if bytes.len() as u64 != offset - start {
+        return None;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+fn trim_ascii(buf: &mut [u8]) -> &mut [u8] {
+    let start = buf
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(buf.len());
+    let end = buf
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |i| i + 1);
+    &mut buf[start..end]
 }
 
 /// Parse error types
@@ -175,7 +428,7 @@ mod tests {
 
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, r#"{{"name": "a", "value": 1}}"#).unwrap();
-        writeln!(file, "").unwrap(); // Empty line
+        writeln!(file).unwrap(); // Empty line
         writeln!(file, "   ").unwrap(); // Whitespace only
         writeln!(file, r#"{{"name": "b", "value": 2}}"#).unwrap();
 
@@ -202,12 +455,13 @@ mod tests {
         writeln!(file, r#"{{"name": "also good", "value": 2}}"#).unwrap();
 
         let mut results: Vec<TestStruct> = Vec::new();
-        parse_jsonl_file(&file_path, |item: TestStruct| {
+        let skipped = parse_jsonl_file(&file_path, |item: TestStruct| {
             results.push(item);
         })
         .unwrap();
 
         // Only valid lines should be parsed
+        assert_eq!(skipped, 2);
         assert_eq!(results.len(), 2);
         assert_eq!(results[0].name, "good");
         assert_eq!(results[1].name, "also good");
@@ -254,6 +508,174 @@ mod tests {
         assert_eq!(count, 1000);
     }
 
+    #[test]
+    fn test_read_jsonl_lines_from_offset() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("append.jsonl");
+
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(file, r#"{{"name": "first", "value": 1}}"#).unwrap();
+        file.flush().unwrap();
+
+        let mut names = Vec::new();
+        let progress =
+            read_jsonl_lines_from(
+                &file_path,
+                0,
+                LineFilter::ALL,
+                |line| match simd_json::from_slice::<TestStruct>(line) {
+                    Ok(item) => {
+                        names.push(item.name);
+                        true
+                    }
+                    Err(_) => false,
+                },
+            )
+            .unwrap();
+        assert_eq!(names, vec!["first"]);
+        assert_eq!(
+            progress.offset,
+            std::fs::metadata(&file_path).unwrap().len()
+        );
+        assert_eq!(progress.lines, 1);
+
+        // Append a malformed line, a complete line and a partially written one
+        writeln!(file, "not valid json").unwrap();
+        writeln!(file, r#"{{"name": "second", "value": 2}}"#).unwrap();
+        write!(file, r#"{{"name": "thi"#).unwrap();
+        file.flush().unwrap();
+
+        names.clear();
+        let next = read_jsonl_lines_from(&file_path, progress.offset, LineFilter::ALL, |line| {
+            match simd_json::from_slice::<TestStruct>(line) {
+                Ok(item) => {
+                    names.push(item.name);
+                    true
+                }
+                Err(_) => false,
+            }
+        })
+        .unwrap();
+        assert_eq!(names, vec!["second"]);
+        assert_eq!(next.lines, 2);
+        assert_eq!(next.skipped_lines, 1);
+        assert_eq!(next.first_skipped_line, Some(1));
+        // The partial line is left for the next read and not counted as malformed
+        assert!(next.offset < std::fs::metadata(&file_path).unwrap().len());
+    }
+
+    #[test]
+    fn test_read_jsonl_lines_from_skips_filtered_lines() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("filtered.jsonl");
+
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(file, r#"{{"name": "keep", "value": 1}}"#).unwrap();
+        writeln!(file, "not json, but filtered out").unwrap();
+        writeln!(file, r#"{{"label": "other", "value": 2}}"#).unwrap();
+        write!(file, r#"{{"label": "partial"#).unwrap();
+        file.flush().unwrap();
+
+        let filter = LineFilter::containing_any(&["\"name\""]);
+        let mut seen = 0;
+        let progress = read_jsonl_lines_from(&file_path, 0, filter, |line| {
+            seen += 1;
+            simd_json::from_slice::<TestStruct>(line).is_ok()
+        })
+        .unwrap();
+
+        assert_eq!(seen, 1);
+        assert_eq!(progress.lines, 3);
+        assert_eq!(progress.skipped_lines, 0);
+        // A partial line is not consumed even when the filter rejects it
+        assert!(progress.offset < std::fs::metadata(&file_path).unwrap().len());
+    }
+
+    #[test]
+    fn test_line_filter_matches() {
+        assert!(LineFilter::ALL.matches(b"anything"));
+
+        let filter = LineFilter::containing_any(&["\"usage\"", "token_count"]);
+        assert!(filter.matches(br#"{"message":{"usage":{}}}"#));
+        assert!(filter.matches(br#"{"payload":{"type":"token_count"}}"#));
+        assert!(!filter.matches(br#"{"type":"user","message":"what is my usage?"}"#));
+    }
+
+    #[test]
+    fn test_split_jsonl_chunks_reads_every_line_once() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("chunks.jsonl");
+
+        let mut file = File::create(&file_path).unwrap();
+        for i in 0..50 {
+            if i == 37 {
+                writeln!(file, "not valid json").unwrap();
+            } else {
+                writeln!(file, r#"{{"name": "item-{}", "value": {}}}"#, i, i).unwrap();
+            }
+        }
+        file.flush().unwrap();
+
+        let chunks = split_jsonl_chunks(&file_path, 0, 100).unwrap();
+        assert!(chunks.len() > 5);
+        assert_eq!(chunks.last().unwrap().end, u64::MAX);
+
+        let data = std::fs::read(&file_path).unwrap();
+        for chunk in &chunks[1..] {
+            assert_eq!(data[chunk.start as usize - 1], b'\n');
+        }
+
+        let mut names = Vec::new();
+        let mut progress = JsonlProgress::default();
+        for chunk in chunks {
+            let next = read_jsonl_range(&file_path, chunk, LineFilter::ALL, |line| {
+                match simd_json::from_slice::<TestStruct>(line) {
+                    Ok(item) => {
+                        names.push(item.name);
+                        true
+                    }
+                    Err(_) => false,
+                }
+            })
+            .unwrap();
+            progress.append(next);
+        }
+
+        assert_eq!(names.len(), 49);
+        assert_eq!(names[0], "item-0");
+        assert_eq!(names[48], "item-49");
+        assert_eq!(progress.lines, 50);
+        assert_eq!(progress.skipped_lines, 1);
+        assert_eq!(progress.first_skipped_line, Some(38));
+        assert_eq!(progress.offset, data.len() as u64);
+    }
+
+    #[test]
+    fn test_split_jsonl_chunks_small_file() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("small.jsonl");
+        std::fs::write(&file_path, "{}\n{}\n").unwrap();
+
+        let chunks = split_jsonl_chunks(&file_path, 3, PARALLEL_CHUNK_SIZE).unwrap();
+        assert_eq!(chunks, vec![3..u64::MAX]);
+    }
+
+    #[test]
+    fn test_fingerprint_before() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("session.jsonl");
+        std::fs::write(&file_path, "{\"a\":1}\n").unwrap();
+        let fingerprint = fingerprint_before(&file_path, 8);
+        assert!(fingerprint.is_some());
+
+        // Appending keeps it, rewriting the consumed bytes does not
+        std::fs::write(&file_path, "{\"a\":1}\n{\"b\":2}\n").unwrap();
+        assert_eq!(fingerprint_before(&file_path, 8), fingerprint);
+        std::fs::write(&file_path, "{\"a\":9}\n{\"b\":2}\n").unwrap();
+        assert_ne!(fingerprint_before(&file_path, 8), fingerprint);
+        assert_eq!(fingerprint_before(&file_path, 100), None);
+    }
+
     #[test]
     fn test_parse_error_display() {
         let io_error = ParseError::IoError("file not found".to_string());
@@ -0,0 +1,284 @@
+//! Time-versioned pricing
+//!
+//! Models can carry dated versions of their rates, so old usage is priced at
+//! the rates in effect when it happened. A message uses the latest version whose
+//! `effective_from` is on or before its timestamp; messages before the first
+//! dated version, and models without dated versions, use the undated rates.
+//!
+//! Price histories are kept locally as snapshot files. Each snapshot lists
+//! LiteLLM-style entries with the date their rates took effect:
+//!
+//! ```json
+//! {
+//!   "snapshots": [
+//!     {
+//!       "effective_from": "2024-05-13",
+//!       "models": {
+//!         "gpt-4o": { "input_cost_per_token": 5e-06, "output_cost_per_token": 1.5e-05 }
+//!       }
+//!     },
+//!     {
+//!       "effective_from": "2024-08-06",
+//!       "models": {
+//!         "gpt-4o": { "input_cost_per_token": 2.5e-06, "output_cost_per_token": 1e-05 }
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+
+use super::{litellm, ModelPricing, PricingData, PricingError, PricingRule};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Contents of a pricing snapshot file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PricingSnapshots {
+    pub snapshots: Vec<PricingSnapshot>,
+}
+
+/// Rates that took effect on `effective_from`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PricingSnapshot {
+    /// Date the rates took effect (YYYY-MM-DD, UTC)
+    pub effective_from: String,
+    /// Entries in LiteLLM's `model_prices_and_context_window.json` schema
+    pub models: Map<String, Value>,
+}
+
+impl PricingSnapshots {
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, PricingError> {
+        Self::from_json_str(&super::read_pricing_file(path.as_ref())?)
+    }
+
+    pub fn from_json_str(content: &str) -> Result<Self, PricingError> {
+        serde_json::from_str(content).map_err(|e| PricingError::Json(e.to_string()))
+    }
+
+    /// `~/.config/tokscale/pricing-history.json`, if it exists
+    pub fn default_path(home_dir: impl AsRef<Path>) -> Option<PathBuf> {
+        let path = home_dir
+            .as_ref()
+            .join(".config")
+            .join("tokscale")
+            .join("pricing-history.json");
+        path.is_file().then_some(path)
+    }
+}
+
+/// Parse an `effective_from` date into Unix milliseconds at UTC midnight
+pub fn parse_effective_from(date: &str) -> Result<i64, PricingError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp_millis())
+        .ok_or_else(|| PricingError::InvalidDate(date.to_string()))
+}
+
+impl PricingData {
+    /// Add a dated version of a model's rates, in effect from `effective_from`
+    /// (Unix milliseconds) until the next version
+    pub fn add_model_version(
+        &mut self,
+        model_id: String,
+        effective_from: i64,
+        pricing: ModelPricing,
+    ) {
+        let versions = self.history.entry(model_id).or_default();
+        let pricing = ModelPricing {
+            effective_from: Some(effective_from),
+            ..pricing
+        };
+        let pos = versions.partition_point(|v| v.effective_from <= Some(effective_from));
+        if pos > 0 && versions[pos - 1].effective_from == Some(effective_from) {
+            versions[pos - 1] = pricing;
+        } else {
+            versions.insert(pos, pricing);
+        }
+    }
+
+    /// Add every snapshot of a price history. Nothing is added if a snapshot
+    /// has an invalid date.
+    pub fn add_snapshots(&mut self, snapshots: &PricingSnapshots) -> Result<(), PricingError> {
+        let dates = snapshots
+            .snapshots
+            .iter()
+            .map(|s| parse_effective_from(&s.effective_from))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (snapshot, effective_from) in snapshots.snapshots.iter().zip(dates) {
+            for (model_id, fields) in &snapshot.models {
+                if let Some(fields) = fields.as_object() {
+                    let pricing = litellm::model_pricing(fields);
+                    self.add_model_version(model_id.clone(), effective_from, pricing);
+                }
+            }
+        }
+        self.finalize();
+        Ok(())
+    }
+
+    /// Load and add a snapshot file (see `PricingSnapshots`)
+    pub fn add_snapshots_file(&mut self, path: impl AsRef<Path>) -> Result<(), PricingError> {
+        let snapshots = PricingSnapshots::from_json_file(path)?;
+        self.add_snapshots(&snapshots)
+    }
+
+    /// Make models that only have dated versions resolvable: their earliest
+    /// version stands in for the undated rates
+    pub(super) fn fill_dated_only_models(&mut self) {
+        for (model_id, versions) in &self.history {
+            let Some(earliest) = versions.first() else {
+                continue;
+            };
+            let dated_only = self
+                .models
+                .get(model_id)
+                .is_none_or(|p| p.effective_from.is_some());
+            if dated_only {
+                self.models.insert(model_id.clone(), earliest.clone());
+            }
+        }
+    }
+
+    /// Pricing for a model at a point in time (Unix milliseconds). Override
+    /// rules are not versioned and apply at any time.
+    pub fn get_pricing_at(&self, model_id: &str, timestamp: i64) -> Option<&ModelPricing> {
+        let found = self.resolve(model_id)?;
+        if matches!(
+            found.pricing.rule,
+            Some(PricingRule::Exact(_) | PricingRule::Glob(_) | PricingRule::Regex(_))
+        ) {
+            return Some(found.pricing);
+        }
+        let version = self.history.get(found.key).and_then(|versions| {
+            versions
+                .iter()
+                .rev()
+                .find(|v| v.effective_from.is_some_and(|from| from <= timestamp))
+        });
+        Some(version.unwrap_or(found.pricing))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const HISTORY: &str = r#"{
+        "snapshots": [
+            {
+                "effective_from": "2024-08-06",
+                "models": {
+                    "gpt-4o": {"input_cost_per_token": 2.5e-06, "output_cost_per_token": 1e-05}
+                }
+            },
+            {
+                "effective_from": "2024-05-13",
+                "models": {
+                    "gpt-4o": {"input_cost_per_token": 5e-06, "output_cost_per_token": 1.5e-05},
+                    "gemini-1.5-flash": {"input_cost_per_token": 3.5e-07}
+                }
+            }
+        ]
+    }"#;
+
+    fn ms(date: &str) -> i64 {
+        parse_effective_from(date).unwrap()
+    }
+
+    fn input_rate(pricing: &PricingData, model: &str, date: &str) -> f64 {
+        pricing
+            .get_pricing_at(model, ms(date))
+            .unwrap()
+            .input_cost_per_token
+    }
+
+    #[test]
+    fn test_rates_in_effect_at_timestamp() {
+        let mut pricing = PricingData::new();
+        pricing.add_model(
+            "gpt-4o".to_string(),
+            ModelPricing {
+                input_cost_per_token: 2e-06,
+                ..Default::default()
+            },
+        );
+        pricing
+            .add_snapshots(&PricingSnapshots::from_json_str(HISTORY).unwrap())
+            .unwrap();
+
+        // Before the first dated version: the undated rates
+        assert_eq!(input_rate(&pricing, "gpt-4o", "2024-01-01"), 2e-06);
+        assert_eq!(input_rate(&pricing, "gpt-4o", "2024-05-13"), 5e-06);
+        assert_eq!(input_rate(&pricing, "gpt-4o", "2024-08-05"), 5e-06);
+        assert_eq!(input_rate(&pricing, "gpt-4o", "2025-01-01"), 2.5e-06);
+        // Fuzzy matches use the history of the key they resolve to
+        assert_eq!(input_rate(&pricing, "openai/gpt-4o", "2024-06-01"), 5e-06);
+
+        // Only dated versions: the earliest one applies before it took effect
+        assert_eq!(
+            input_rate(&pricing, "gemini-1.5-flash", "2023-01-01"),
+            3.5e-07
+        );
+        assert_eq!(
+            pricing
+                .get_pricing("gemini-1.5-flash")
+                .unwrap()
+                .effective_from,
+            Some(ms("2024-05-13"))
+        );
+    }
+
+    #[test]
+    fn test_snapshot_file_and_invalid_date() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "{}", HISTORY).unwrap();
+        let mut pricing = PricingData::new();
+        pricing.add_snapshots_file(file.path()).unwrap();
+        assert_eq!(input_rate(&pricing, "gpt-4o", "2024-09-01"), 2.5e-06);
+
+        let invalid = r#"{"snapshots": [{"effective_from": "May 2024", "models": {}}]}"#;
+        assert!(matches!(
+            PricingData::new().add_snapshots(&PricingSnapshots::from_json_str(invalid).unwrap()),
+            Err(PricingError::InvalidDate(_))
+        ));
+    }
+
+    #[test]
+    fn test_same_date_replaces_version() {
+        let mut pricing = PricingData::new();
+        for rate in [1.0, 2.0] {
+            pricing.add_model_version(
+                "model".to_string(),
+                ms("2024-01-01"),
+                ModelPricing {
+                    input_cost_per_token: rate,
+                    ..Default::default()
+                },
+            );
+        }
+        pricing.finalize();
+        assert_eq!(input_rate(&pricing, "model", "2024-06-01"), 2.0);
+    }
+
+    #[test]
+    fn test_overrides_are_not_versioned() {
+        let mut pricing = PricingData::new();
+        pricing
+            .add_snapshots(&PricingSnapshots::from_json_str(HISTORY).unwrap())
+            .unwrap();
+        let overrides = crate::pricing::PricingOverrides::from_toml_str(
+            "[models.\"gpt-4o\"]\ninput_cost_per_token = 1e-06",
+        )
+        .unwrap();
+        pricing.apply_overrides(&overrides).unwrap();
+
+        assert_eq!(input_rate(&pricing, "gpt-4o", "2024-06-01"), 1e-06);
+    }
+}
@@ -0,0 +1,285 @@
+//! Loader for LiteLLM's `model_prices_and_context_window.json`
+//!
+//! Builds `PricingData` straight from the dataset, so callers no longer need
+//! to convert it into `PricingEntry` lists. Rates are read leniently: entries
+//! that are not objects are skipped and non-numeric rates count as missing.
+
+use super::{ModelPricing, PricingData, PricingError, PricingTier};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Documentation entry at the top of LiteLLM's file
+const SAMPLE_SPEC: &str = "sample_spec";
+
+impl PricingData {
+    /// Load LiteLLM's `model_prices_and_context_window.json`. The
+    /// `{ "timestamp", "data" }` wrapper of the TypeScript CLI's pricing cache
+    /// is accepted as well.
+    pub fn from_litellm_json(path: impl AsRef<Path>) -> Result<Self, PricingError> {
+        let path = path.as_ref();
+        Self::from_litellm_str(&super::read_pricing_file(path)?)
+    }
+
+    /// Parse the contents of a LiteLLM pricing file (see `from_litellm_json`)
+    pub fn from_litellm_str(json: &str) -> Result<Self, PricingError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| PricingError::Json(e.to_string()))?;
+
+        let models = match value.get("data") {
+            Some(data) if value.get("timestamp").is_some() => data,
+            _ => &value,
+        }
+        .as_object()
+        .ok_or(PricingError::NotAnObject)?;
+
+        let mut pricing = PricingData::new();
+        for (model_id, fields) in models {
+            if model_id == SAMPLE_SPEC {
+                continue;
+            }
+            if let Some(fields) = fields.as_object() {
+                pricing.add_model(model_id.clone(), model_pricing(fields));
+            }
+        }
+        pricing.finalize();
+        Ok(pricing)
+    }
+}
+
+/// Convert one LiteLLM entry
+pub(super) fn model_pricing(fields: &Map<String, Value>) -> ModelPricing {
+    let rate = |key: &str| fields.get(key).and_then(Value::as_f64);
+    let text = |key: &str| fields.get(key).and_then(Value::as_str).map(str::to_string);
+
+    let mut model = ModelPricing {
+        input_cost_per_token: rate("input_cost_per_token").unwrap_or(0.0),
+        output_cost_per_token: rate("output_cost_per_token").unwrap_or(0.0),
+        cache_read_input_token_cost: rate("cache_read_input_token_cost").unwrap_or(0.0),
+        cache_creation_input_token_cost: rate("cache_creation_input_token_cost").unwrap_or(0.0),
+        output_cost_per_reasoning_token: rate("output_cost_per_reasoning_token"),
+        tiers: Vec::new(),
+        input_cost_per_token_batches: rate("input_cost_per_token_batches"),
+        output_cost_per_token_batches: rate("output_cost_per_token_batches"),
+        provider: text("litellm_provider"),
+        mode: text("mode"),
+        ..Default::default()
+    };
+
+    // Flat keys: `input_cost_per_token_above_200k_tokens` and friends
+    let mut tiers: Vec<PricingTier> = Vec::new();
+    for (key, value) in fields {
+        let (Some((field, threshold)), Some(value)) = (tier_key(key), value.as_f64()) else {
+            continue;
+        };
+        let index = match tiers.iter().position(|t| t.threshold == threshold) {
+            Some(index) => index,
+            None => {
+                tiers.push(PricingTier {
+                    threshold,
+                    ..Default::default()
+                });
+                tiers.len() - 1
+            }
+        };
+        set_tier_rate(&mut tiers[index], field, value);
+    }
+
+    // `tiered_pricing`: [{ "range": [0, 256000], "input_cost_per_token": ... }, ...]
+    if let Some(ranges) = fields.get("tiered_pricing").and_then(Value::as_array) {
+        for range in ranges.iter().filter_map(Value::as_object) {
+            let start = range
+                .get("range")
+                .and_then(Value::as_array)
+                .and_then(|bounds| bounds.first())
+                .and_then(Value::as_f64)
+                .unwrap_or(0.0) as i64;
+            let rate = |key: &str| range.get(key).and_then(Value::as_f64);
+
+            if start == 0 {
+                // The first range holds the base rates when the flat keys are absent
+                let base = [
+                    ("input_cost_per_token", &mut model.input_cost_per_token),
+                    ("output_cost_per_token", &mut model.output_cost_per_token),
+                    (
+                        "cache_read_input_token_cost",
+                        &mut model.cache_read_input_token_cost,
+                    ),
+                ];
+                for (key, target) in base {
+                    if !fields.contains_key(key) {
+                        if let Some(value) = rate(key) {
+                            *target = value;
+                        }
+                    }
+                }
+                continue;
+            }
+
+            // A range starting at N applies to prompts above N tokens
+            let mut tier = PricingTier {
+                threshold: start,
+                ..Default::default()
+            };
+            for field in TIERED_FIELDS {
+                if let Some(value) = rate(field) {
+                    set_tier_rate(&mut tier, field, value);
+                }
+            }
+            tiers.push(tier);
+        }
+    }
+
+    for tier in tiers {
+        model.add_tier(tier);
+    }
+    model
+}
+
+/// Rates that LiteLLM varies by prompt size
+const TIERED_FIELDS: [&str; 4] = [
+    "input_cost_per_token",
+    "output_cost_per_token",
+    "cache_read_input_token_cost",
+    "cache_creation_input_token_cost",
+];
+
+/// Split `<rate>_above_<N>k_tokens` into the rate name and the threshold in tokens
+fn tier_key(key: &str) -> Option<(&'static str, i64)> {
+    let (field, size) = key.strip_suffix("_tokens")?.split_once("_above_")?;
+    let field = TIERED_FIELDS.into_iter().find(|f| *f == field)?;
+    let (number, scale) = match size.as_bytes().last()? {
+        b'k' => (&size[..size.len() - 1], 1_000),
+        b'm' => (&size[..size.len() - 1], 1_000_000),
+        _ => (size, 1),
+    };
+    Some((field, number.parse::<i64>().ok()? * scale))
+}
+
+fn set_tier_rate(tier: &mut PricingTier, field: &str, value: f64) {
+    let target = match field {
+        "input_cost_per_token" => &mut tier.input_cost_per_token,
+        "output_cost_per_token" => &mut tier.output_cost_per_token,
+        "cache_read_input_token_cost" => &mut tier.cache_read_input_token_cost,
+        "cache_creation_input_token_cost" => &mut tier.cache_creation_input_token_cost,
+        _ => return,
+    };
+    *target = Some(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const DATASET: &str = r#"{
+        "sample_spec": {
+            "input_cost_per_token": 0.0,
+            "litellm_provider": "one of https://docs.litellm.ai/docs/providers",
+            "mode": "one of: chat, embedding, completion, image_generation"
+        },
+        "claude-sonnet-4-5": {
+            "input_cost_per_token": 3e-06,
+            "output_cost_per_token": 1.5e-05,
+            "cache_read_input_token_cost": 3e-07,
+            "cache_creation_input_token_cost": 3.75e-06,
+            "cache_creation_input_token_cost_above_1hr": 6e-06,
+            "input_cost_per_token_above_200k_tokens": 6e-06,
+            "output_cost_per_token_above_200k_tokens": 2.25e-05,
+            "cache_read_input_token_cost_above_200k_tokens": 6e-07,
+            "input_cost_per_token_batches": 1.5e-06,
+            "output_cost_per_token_batches": 7.5e-06,
+            "litellm_provider": "anthropic",
+            "mode": "chat",
+            "max_input_tokens": 1000000,
+            "supports_reasoning": true
+        },
+        "dashscope/qwen-plus": {
+            "litellm_provider": "dashscope",
+            "mode": "chat",
+            "tiered_pricing": [
+                {"range": [0, 256000.0], "input_cost_per_token": 4e-07, "output_cost_per_token": 1.2e-06},
+                {"range": [256000.0, 1000000.0], "input_cost_per_token": 1.2e-06, "output_cost_per_token": 3.6e-06}
+            ]
+        },
+        "text-embedding-3-small": {
+            "input_cost_per_token": "2e-08",
+            "output_cost_per_token": 0.0,
+            "mode": "embedding"
+        },
+        "not-a-model": 42
+    }"#;
+
+    #[test]
+    fn test_from_litellm_str_parses_full_schema() {
+        let pricing = PricingData::from_litellm_str(DATASET).unwrap();
+        assert_eq!(pricing.len(), 3);
+
+        let sonnet = pricing.get_pricing("claude-sonnet-4-5").unwrap();
+        assert_eq!(sonnet.provider.as_deref(), Some("anthropic"));
+        assert_eq!(sonnet.mode.as_deref(), Some("chat"));
+        assert_eq!(sonnet.input_cost_per_token_batches, Some(1.5e-06));
+        assert_eq!(sonnet.output_cost_per_token_batches, Some(7.5e-06));
+        assert_eq!(sonnet.tiers.len(), 1);
+        assert_eq!(sonnet.tiers[0].threshold, 200_000);
+        assert_eq!(sonnet.tiers[0].output_cost_per_token, Some(2.25e-05));
+        assert_eq!(sonnet.tiers[0].cache_creation_input_token_cost, None);
+        assert_eq!(sonnet.rates_for(250_000).cache_read, 6e-07);
+
+        let qwen = pricing.get_pricing("dashscope/qwen-plus").unwrap();
+        assert_eq!(qwen.input_cost_per_token, 4e-07);
+        assert_eq!(qwen.rates_for(100_000).output, 1.2e-06);
+        assert_eq!(qwen.rates_for(300_000).output, 3.6e-06);
+
+        // Non-numeric rates count as missing
+        let embedding = pricing.get_pricing("text-embedding-3-small").unwrap();
+        assert_eq!(embedding.input_cost_per_token, 0.0);
+        assert_eq!(embedding.mode.as_deref(), Some("embedding"));
+    }
+
+    #[test]
+    fn test_from_litellm_json_reads_cache_wrapper() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"{{"timestamp": 1750000000000, "data": {}}}"#,
+            DATASET
+        )
+        .unwrap();
+
+        let pricing = PricingData::from_litellm_json(file.path()).unwrap();
+        assert_eq!(pricing.len(), 3);
+        let cost = pricing.calculate_cost("claude-sonnet-4-5", 1_000, 100, 0, 0, 0);
+        assert!((cost - (1_000.0 * 3e-06 + 100.0 * 1.5e-05)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_from_litellm_errors() {
+        assert!(matches!(
+            PricingData::from_litellm_json("/nonexistent/prices.json"),
+            Err(PricingError::Io { .. })
+        ));
+        assert!(matches!(
+            PricingData::from_litellm_str("{"),
+            Err(PricingError::Json(_))
+        ));
+        assert!(matches!(
+            PricingData::from_litellm_str("[1, 2]"),
+            Err(PricingError::NotAnObject)
+        ));
+    }
+
+    #[test]
+    fn test_tier_key() {
+        assert_eq!(
+            tier_key("input_cost_per_token_above_200k_tokens"),
+            Some(("input_cost_per_token", 200_000))
+        );
+        assert_eq!(
+            tier_key("cache_read_input_token_cost_above_1m_tokens"),
+            Some(("cache_read_input_token_cost", 1_000_000))
+        );
+        assert_eq!(tier_key("cache_creation_input_token_cost_above_1hr"), None);
+        assert_eq!(tier_key("input_cost_per_character_above_128k_tokens"), None);
+    }
+}
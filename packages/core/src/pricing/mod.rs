@@ -0,0 +1,881 @@
+//! Pricing calculation module
+//!
+//! Receives pricing data from TypeScript (or loads LiteLLM's dataset directly,
+//! see `litellm`) and calculates costs for messages. Local overrides (see
+//! `overrides`) are merged on top of the base data, and dated versions (see
+//! `history`) price old usage at the rates of its time.
+//! Optimized for high-throughput lookups with pre-computed indices.
+
+pub mod history;
+pub mod litellm;
+pub mod overrides;
+
+use crate::TokenBreakdown;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub use history::PricingSnapshots;
+pub use overrides::{PricingOverrides, PricingRule};
+
+/// Error loading a pricing or pricing overrides file
+#[derive(Debug, thiserror::Error)]
+pub enum PricingError {
+    #[error("Failed to read pricing file {path}: {message}")]
+    Io { path: String, message: String },
+
+    #[error("Invalid pricing JSON: {0}")]
+    Json(String),
+
+    #[error("Invalid pricing TOML: {0}")]
+    Toml(String),
+
+    #[error("Pricing data must be a JSON object keyed by model name")]
+    NotAnObject,
+
+    #[error("Invalid model pattern {pattern:?}: {message}")]
+    InvalidPattern { pattern: String, message: String },
+
+    #[error("Invalid discount for provider {provider}: {percent}% (expected 0 to 100)")]
+    InvalidDiscount { provider: String, percent: f64 },
+
+    #[error("Invalid effective_from date {0:?} (expected YYYY-MM-DD)")]
+    InvalidDate(String),
+}
+
+fn read_pricing_file(path: &Path) -> Result<String, PricingError> {
+    std::fs::read_to_string(path).map_err(|e| PricingError::Io {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Internal pricing data for a single model
+#[derive(Debug, Clone, Default)]
+pub struct ModelPricing {
+    pub input_cost_per_token: f64,
+    pub output_cost_per_token: f64,
+    pub cache_read_input_token_cost: f64,
+    pub cache_creation_input_token_cost: f64,
+    /// Rate for reasoning tokens (defaults to the output rate)
+    pub output_cost_per_reasoning_token: Option<f64>,
+    /// Long-context tiers, sorted by ascending threshold
+    pub tiers: Vec<PricingTier>,
+    /// Discounted rates for batch API requests
+    pub input_cost_per_token_batches: Option<f64>,
+    pub output_cost_per_token_batches: Option<f64>,
+    /// Provider that serves the model (LiteLLM's `litellm_provider`, e.g. "anthropic")
+    pub provider: Option<String>,
+    /// Kind of endpoint the rates apply to (e.g. "chat", "embedding")
+    pub mode: Option<String>,
+    /// Override rule that set these rates (`None` for the base pricing data)
+    pub rule: Option<PricingRule>,
+    /// Explicitly marked free by an override; the cost is 0 even for sources
+    /// that would fall back to their recorded cost
+    pub free: bool,
+    /// Start of the period these rates apply to (Unix milliseconds); `None`
+    /// for undated rates
+    pub effective_from: Option<i64>,
+}
+
+/// Rates that apply once a request's prompt exceeds `threshold` tokens
+/// (e.g. LiteLLM's `input_cost_per_token_above_200k_tokens`).
+/// Categories left as `None` keep the base rate.
+#[derive(Debug, Clone, Default)]
+pub struct PricingTier {
+    pub threshold: i64,
+    pub input_cost_per_token: Option<f64>,
+    pub output_cost_per_token: Option<f64>,
+    pub cache_read_input_token_cost: Option<f64>,
+    pub cache_creation_input_token_cost: Option<f64>,
+}
+
+/// Per-token rates resolved for a single request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenRates {
+    pub input: f64,
+    pub output: f64,
+    pub cache_read: f64,
+    pub cache_write: f64,
+    pub reasoning: f64,
+}
+
+impl ModelPricing {
+    /// Add a long-context tier, keeping tiers sorted by threshold
+    pub fn add_tier(&mut self, tier: PricingTier) {
+        let pos = self.tiers.partition_point(|t| t.threshold < tier.threshold);
+        self.tiers.insert(pos, tier);
+    }
+
+    /// Resolve the rates for a request whose prompt (input plus cache tokens)
+    /// has `prompt_tokens` tokens. The highest tier whose threshold is
+    /// exceeded wins.
+    pub fn rates_for(&self, prompt_tokens: i64) -> TokenRates {
+        let base = TokenRates {
+            input: self.input_cost_per_token,
+            output: self.output_cost_per_token,
+            cache_read: self.cache_read_input_token_cost,
+            cache_write: self.cache_creation_input_token_cost,
+            reasoning: self
+                .output_cost_per_reasoning_token
+                .unwrap_or(self.output_cost_per_token),
+        };
+
+        match self
+            .tiers
+            .iter()
+            .rev()
+            .find(|t| prompt_tokens > t.threshold)
+        {
+            Some(tier) => TokenRates {
+                input: tier.input_cost_per_token.unwrap_or(base.input),
+                output: tier.output_cost_per_token.unwrap_or(base.output),
+                reasoning: self
+                    .output_cost_per_reasoning_token
+                    .or(tier.output_cost_per_token)
+                    .unwrap_or(base.reasoning),
+                cache_read: tier.cache_read_input_token_cost.unwrap_or(base.cache_read),
+                cache_write: tier
+                    .cache_creation_input_token_cost
+                    .unwrap_or(base.cache_write),
+            },
+            None => base,
+        }
+    }
+
+    /// Which rule priced the model: "base" for the base pricing data, otherwise
+    /// the override rule (e.g. "glob ollama/*", "anthropic discount 20%")
+    pub fn rule_label(&self) -> String {
+        let rule = self
+            .rule
+            .as_ref()
+            .map_or_else(|| "base".to_string(), PricingRule::to_string);
+        if self.free {
+            format!("{} (free)", rule)
+        } else {
+            rule
+        }
+    }
+
+    /// Cost of a single request with already-billable token counts
+    fn cost(
+        &self,
+        input: i64,
+        output: i64,
+        cache_read: i64,
+        cache_write: i64,
+        reasoning: i64,
+    ) -> f64 {
+        let prompt_tokens = input.saturating_add(cache_read).saturating_add(cache_write);
+        let rates = self.rates_for(prompt_tokens);
+
+        input as f64 * rates.input
+            + output as f64 * rates.output
+            + reasoning as f64 * rates.reasoning
+            + cache_read as f64 * rates.cache_read
+            + cache_write as f64 * rates.cache_write
+    }
+}
+
+/// How a source's token counts map onto billable quantities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BillingPolicy {
+    /// Cache read and write tokens are not billed
+    pub free_cache: bool,
+    /// Reasoning tokens are reported apart from output and billed on top of it.
+    /// When false they are already counted in `output` and not billed again.
+    pub reasoning_billed_separately: bool,
+    /// Keep the cost recorded by the source when no pricing matches the model
+    pub fallback_to_recorded_cost: bool,
+}
+
+impl BillingPolicy {
+    pub const STANDARD: Self = Self {
+        free_cache: false,
+        reasoning_billed_separately: true,
+        fallback_to_recorded_cost: false,
+    };
+}
+
+impl Default for BillingPolicy {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// Billing rules by source; sources not listed use `BillingPolicy::STANDARD`
+const BILLING_POLICIES: &[(&str, BillingPolicy)] = &[
+    // Gemini CLI: thoughts are billed as output, and cached tokens are already
+    // counted in `input`, so the cache is not billed again
+    (
+        "gemini",
+        BillingPolicy {
+            free_cache: true,
+            ..BillingPolicy::STANDARD
+        },
+    ),
+    // Qwen Code logs in the Gemini CLI format and is billed the same way
+    (
+        "qwen",
+        BillingPolicy {
+            free_cache: true,
+            ..BillingPolicy::STANDARD
+        },
+    ),
+    // Cursor and Amp record what was actually charged
+    (
+        "cursor",
+        BillingPolicy {
+            fallback_to_recorded_cost: true,
+            ..BillingPolicy::STANDARD
+        },
+    ),
+    (
+        "amp",
+        BillingPolicy {
+            fallback_to_recorded_cost: true,
+            ..BillingPolicy::STANDARD
+        },
+    ),
+];
+
+/// Look up the billing policy for a source name
+pub fn billing_policy(source: &str) -> BillingPolicy {
+    BILLING_POLICIES
+        .iter()
+        .find(|(name, _)| *name == source)
+        .map(|(_, policy)| *policy)
+        .unwrap_or_default()
+}
+
+/// How a model id was matched to a pricing entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The model id is a pricing key
+    Exact,
+    /// The model id with a provider prefix ("anthropic/<model>") is a pricing key
+    Prefixed,
+    /// The Cursor-style name normalized ("claude-4.5-sonnet" to "sonnet-4-5") is a pricing key
+    Normalized,
+    /// A pricing key contains the model id, or the model id contains a pricing key
+    Fuzzy,
+    /// A glob or regex override matched
+    Pattern,
+    /// No pricing matched
+    None,
+}
+
+impl MatchKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MatchKind::Exact => "exact",
+            MatchKind::Prefixed => "prefixed",
+            MatchKind::Normalized => "normalized",
+            MatchKind::Fuzzy => "fuzzy",
+            MatchKind::Pattern => "pattern",
+            MatchKind::None => "none",
+        }
+    }
+}
+
+/// A model id resolved against the pricing data
+#[derive(Debug, Clone, Copy)]
+pub struct PricingMatch<'a> {
+    /// Pricing key the model resolved to (the pattern for pattern overrides)
+    pub key: &'a str,
+    pub kind: MatchKind,
+    pub pricing: &'a ModelPricing,
+}
+
+/// Pre-computed key entry for fast fuzzy matching
+#[derive(Debug, Clone)]
+struct IndexedKey {
+    original: String,
+    lowercase: String,
+}
+
+/// Provider prefixes tried in front of model ids that are not pricing keys
+const PROVIDER_PREFIXES: [&str; 4] = ["anthropic/", "openai/", "google/", "bedrock/"];
+
+/// Whether `needle` occurs in `haystack` delimited by non-alphanumeric characters.
+/// Every occurrence is checked, so "gpt-4" is found in "gpt-4o/gpt-4".
+fn is_word_boundary_match(haystack: &str, needle: &str) -> bool {
+    if needle.is_empty() {
+        return false;
+    }
+    haystack.match_indices(needle).any(|(pos, _)| {
+        let before_ok = pos == 0 || {
+            let ch = haystack[..pos].chars().last().unwrap();
+            !ch.is_alphanumeric()
+        };
+        let after_ok = pos + needle.len() == haystack.len() || {
+            let ch = haystack[pos + needle.len()..].chars().next().unwrap();
+            !ch.is_alphanumeric()
+        };
+        before_ok && after_ok
+    })
+}
+
+/// Pricing dataset containing all model pricing
+/// Optimized with pre-computed indices for fast lookups
+#[derive(Debug, Clone, Default)]
+pub struct PricingData {
+    models: HashMap<String, ModelPricing>,
+    /// Pre-sorted keys with lowercase versions (computed once via finalize())
+    sorted_keys: Vec<IndexedKey>,
+    /// Cache of model_id -> resolved key for repeated lookups
+    resolution_cache: HashMap<String, Option<(String, MatchKind)>>,
+    /// Glob and regex overrides, checked in file order
+    patterns: Vec<overrides::PatternRule>,
+    /// Dated versions by pricing key, sorted by `effective_from`
+    history: HashMap<String, Vec<ModelPricing>>,
+}
+
+impl PricingData {
+    pub fn new() -> Self {
+        Self {
+            models: HashMap::new(),
+            sorted_keys: Vec::new(),
+            resolution_cache: HashMap::new(),
+            patterns: Vec::new(),
+            history: HashMap::new(),
+        }
+    }
+
+    /// Number of models with pricing
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// Add pricing for a model
+    pub fn add_model(&mut self, model_id: String, pricing: ModelPricing) {
+        self.models.insert(model_id, pricing);
+    }
+
+    /// Finalize the pricing data by pre-computing sorted keys
+    /// Call this after all models have been added
+    pub fn finalize(&mut self) {
+        self.fill_dated_only_models();
+        let mut keys: Vec<IndexedKey> = self
+            .models
+            .keys()
+            .map(|k| IndexedKey {
+                original: k.clone(),
+                lowercase: k.to_lowercase(),
+            })
+            .collect();
+        keys.sort_by(|a, b| a.original.cmp(&b.original));
+        self.sorted_keys = keys;
+        self.resolution_cache.clear();
+    }
+
+    /// Get pricing for a model with fuzzy matching (uses pre-computed indices)
+    pub fn get_pricing(&self, model_id: &str) -> Option<&ModelPricing> {
+        self.resolve(model_id).map(|found| found.pricing)
+    }
+
+    /// Resolve a model id to its pricing entry, reporting which key matched and how
+    pub fn resolve(&self, model_id: &str) -> Option<PricingMatch<'_>> {
+        // Local overrides win over the base data
+        if let Some(found) = self.resolve_override(model_id) {
+            return Some(found);
+        }
+
+        // Check resolution cache first
+        let (key, kind) = match self.resolution_cache.get(model_id) {
+            Some(cached) => cached.as_ref().map(|(key, kind)| (key.as_str(), *kind))?,
+            None => self.resolve_key(model_id)?,
+        };
+        let (key, pricing) = self.models.get_key_value(key)?;
+        Some(PricingMatch { key, kind, pricing })
+    }
+
+    /// Rule label of the pricing that applies to a model (see
+    /// `ModelPricing::rule_label`), or `None` if the model is unpriced
+    pub fn pricing_rule(&self, model_id: &str) -> Option<String> {
+        self.get_pricing(model_id).map(ModelPricing::rule_label)
+    }
+
+    /// Pricing from an exact override entry, then from the first matching pattern
+    fn resolve_override(&self, model_id: &str) -> Option<PricingMatch<'_>> {
+        if let Some((key, pricing)) = self.models.get_key_value(model_id) {
+            if matches!(pricing.rule, Some(PricingRule::Exact(_))) {
+                return Some(PricingMatch {
+                    key,
+                    kind: MatchKind::Exact,
+                    pricing,
+                });
+            }
+        }
+        self.patterns
+            .iter()
+            .find(|rule| rule.matches(model_id))
+            .map(|rule| PricingMatch {
+                key: rule.pattern(),
+                kind: MatchKind::Pattern,
+                pricing: &rule.pricing,
+            })
+    }
+
+    /// Get pricing with caching (mutable version for building cache)
+    pub fn get_pricing_cached(&mut self, model_id: &str) -> Option<&ModelPricing> {
+        if !self.resolution_cache.contains_key(model_id)
+            && self.resolve_override(model_id).is_none()
+        {
+            let resolved = self
+                .resolve_key(model_id)
+                .map(|(key, kind)| (key.to_string(), kind));
+            self.resolution_cache.insert(model_id.to_string(), resolved);
+        }
+        self.get_pricing(model_id)
+    }
+
+    /// Resolve model_id to the actual key in the pricing map
+    fn resolve_key(&self, model_id: &str) -> Option<(&str, MatchKind)> {
+        let key_of = |key: &str| self.models.get_key_value(key).map(|(k, _)| k.as_str());
+
+        // Direct lookup
+        if let Some(key) = key_of(model_id) {
+            return Some((key, MatchKind::Exact));
+        }
+
+        // Try with provider prefixes
+        for prefix in PROVIDER_PREFIXES {
+            if let Some(key) = key_of(&format!("{}{}", prefix, model_id)) {
+                return Some((key, MatchKind::Prefixed));
+            }
+        }
+
+        // Normalize model name for Cursor-style names
+        let normalized = Self::normalize_cursor_model_name(model_id);
+        if let Some(ref norm) = normalized {
+            if let Some(key) = key_of(norm) {
+                return Some((key, MatchKind::Normalized));
+            }
+            for prefix in PROVIDER_PREFIXES {
+                if let Some(key) = key_of(&format!("{}{}", prefix, norm)) {
+                    return Some((key, MatchKind::Normalized));
+                }
+            }
+        }
+
+        // Fuzzy matching using pre-computed sorted keys
+        let lower_model = model_id.to_lowercase();
+        let lower_normalized = normalized.as_ref().map(|s| s.to_lowercase());
+        let names = [Some(lower_model.as_str()), lower_normalized.as_deref()];
+
+        // A key containing the model id: the shortest one adds the least
+        let mut best: Option<&IndexedKey> = None;
+        for indexed in &self.sorted_keys {
+            let matched = names
+                .iter()
+                .flatten()
+                .any(|name| is_word_boundary_match(&indexed.lowercase, name));
+            if matched && best.is_none_or(|b| indexed.lowercase.len() < b.lowercase.len()) {
+                best = Some(indexed);
+            }
+        }
+
+        // A key contained in the model id: the longest one is the most specific,
+        // so "mistral-large-2411-custom" resolves to "mistral-large-2411"
+        if best.is_none() {
+            for indexed in &self.sorted_keys {
+                let matched = names
+                    .iter()
+                    .flatten()
+                    .any(|name| is_word_boundary_match(name, &indexed.lowercase));
+                if matched && best.is_none_or(|b| indexed.lowercase.len() > b.lowercase.len()) {
+                    best = Some(indexed);
+                }
+            }
+        }
+
+        best.map(|indexed| (indexed.original.as_str(), MatchKind::Fuzzy))
+    }
+
+    /// Normalize Cursor-style model names to standard format
+    fn normalize_cursor_model_name(model_id: &str) -> Option<String> {
+        let lower = model_id.to_lowercase();
+
+        // Claude models
+        if lower.contains("opus") {
+            if lower.contains("4.5") || lower.contains("4-5") {
+                return Some("opus-4-5".to_string());
+            } else if lower.contains("4") {
+                return Some("opus-4".to_string());
+            }
+        }
+        if lower.contains("sonnet") {
+            if lower.contains("4.5") || lower.contains("4-5") {
+                return Some("sonnet-4-5".to_string());
+            } else if lower.contains("4") {
+                return Some("sonnet-4".to_string());
+            } else if lower.contains("3.7") || lower.contains("3-7") {
+                return Some("sonnet-3-7".to_string());
+            } else if lower.contains("3.5") || lower.contains("3-5") {
+                return Some("sonnet-3-5".to_string());
+            }
+        }
+        if lower.contains("haiku") && (lower.contains("4.5") || lower.contains("4-5")) {
+            return Some("haiku-4-5".to_string());
+        }
+
+        // OpenAI models
+        if lower == "o3" {
+            return Some("o3".to_string());
+        }
+        // Smaller variants are priced apart from their family
+        for variant in ["gpt-4o-mini", "gpt-4.1-mini", "gpt-4.1-nano"] {
+            if lower.contains(variant) {
+                return Some(variant.to_string());
+            }
+        }
+        if lower.starts_with("gpt-4o") || lower == "gpt-4o" {
+            return Some("gpt-4o".to_string());
+        }
+        if lower.starts_with("gpt-4.1") || lower.contains("gpt-4.1") {
+            return Some("gpt-4.1".to_string());
+        }
+
+        // Gemini models
+        if lower.contains("gemini-2.5-pro") {
+            return Some("gemini-2.5-pro".to_string());
+        }
+        if lower.contains("gemini-2.5-flash") {
+            return Some("gemini-2.5-flash".to_string());
+        }
+
+        None
+    }
+
+    /// Calculate cost for token usage
+    ///
+    /// Long-context tiers are selected from this request's prompt size
+    /// (input plus cache read and cache write tokens).
+    pub fn calculate_cost(
+        &self,
+        model_id: &str,
+        input: i64,
+        output: i64,
+        cache_read: i64,
+        cache_write: i64,
+        reasoning: i64,
+    ) -> f64 {
+        match self.get_pricing(model_id) {
+            Some(pricing) => pricing.cost(input, output, cache_read, cache_write, reasoning),
+            None => 0.0,
+        }
+    }
+
+    /// Calculate cost for token usage under a source's billing policy, at the
+    /// rates in effect at `timestamp` (Unix milliseconds)
+    pub fn calculate_cost_with_policy(
+        &self,
+        model_id: &str,
+        tokens: &TokenBreakdown,
+        policy: &BillingPolicy,
+        timestamp: i64,
+    ) -> f64 {
+        let pricing = match self.get_pricing_at(model_id, timestamp) {
+            Some(p) => p,
+            None => return 0.0,
+        };
+
+        let (cache_read, cache_write) = if policy.free_cache {
+            (0, 0)
+        } else {
+            (tokens.cache_read, tokens.cache_write)
+        };
+        let reasoning = if policy.reasoning_billed_separately {
+            tokens.reasoning
+        } else {
+            0
+        };
+
+        pricing.cost(
+            tokens.input,
+            tokens.output,
+            cache_read,
+            cache_write,
+            reasoning,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_cost() {
+        let mut pricing = PricingData::new();
+        pricing.add_model(
+            "claude-3-5-sonnet-20241022".to_string(),
+            ModelPricing {
+                input_cost_per_token: 3.0 / 1_000_000.0,
+                output_cost_per_token: 15.0 / 1_000_000.0,
+                cache_read_input_token_cost: 0.3 / 1_000_000.0,
+                cache_creation_input_token_cost: 3.75 / 1_000_000.0,
+                ..Default::default()
+            },
+        );
+        pricing.finalize();
+
+        let cost = pricing.calculate_cost("claude-3-5-sonnet-20241022", 1000, 500, 2000, 100, 0);
+
+        assert!((cost - 0.011475).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_fuzzy_matching() {
+        let mut pricing = PricingData::new();
+        pricing.add_model(
+            "anthropic/claude-3-5-sonnet-20241022".to_string(),
+            ModelPricing {
+                input_cost_per_token: 3.0 / 1_000_000.0,
+                output_cost_per_token: 15.0 / 1_000_000.0,
+                cache_read_input_token_cost: 0.3 / 1_000_000.0,
+                cache_creation_input_token_cost: 3.75 / 1_000_000.0,
+                ..Default::default()
+            },
+        );
+        pricing.finalize();
+
+        assert!(pricing.get_pricing("claude-3-5-sonnet-20241022").is_some());
+    }
+
+    #[test]
+    fn test_cached_lookup() {
+        let mut pricing = PricingData::new();
+        pricing.add_model(
+            "anthropic/claude-3-5-sonnet-20241022".to_string(),
+            ModelPricing {
+                input_cost_per_token: 3.0 / 1_000_000.0,
+                output_cost_per_token: 15.0 / 1_000_000.0,
+                cache_read_input_token_cost: 0.3 / 1_000_000.0,
+                cache_creation_input_token_cost: 3.75 / 1_000_000.0,
+                ..Default::default()
+            },
+        );
+        pricing.finalize();
+
+        // First lookup resolves and caches
+        assert!(pricing
+            .get_pricing_cached("claude-3-5-sonnet-20241022")
+            .is_some());
+
+        // Second lookup uses cache
+        assert!(pricing
+            .get_pricing_cached("claude-3-5-sonnet-20241022")
+            .is_some());
+    }
+
+    #[test]
+    fn test_resolve_match_kinds() {
+        let mut pricing = PricingData::new();
+        for key in [
+            "anthropic/claude-3-5-sonnet-20241022",
+            "claude-sonnet-4-5",
+            "gpt-4o",
+            "gpt-4o-mini",
+            "dashscope/qwen3-coder-480b-a35b",
+            "qwen3-coder-plus",
+            "mistral-large",
+            "mistral-large-2411",
+        ] {
+            pricing.add_model(key.to_string(), ModelPricing::default());
+        }
+        pricing.finalize();
+
+        let resolve = |model: &str| pricing.resolve(model).map(|found| (found.key, found.kind));
+        assert_eq!(resolve("gpt-4o"), Some(("gpt-4o", MatchKind::Exact)));
+        assert_eq!(
+            resolve("claude-3-5-sonnet-20241022"),
+            Some(("anthropic/claude-3-5-sonnet-20241022", MatchKind::Prefixed))
+        );
+        assert_eq!(
+            resolve("claude-4.5-sonnet-thinking"),
+            Some(("claude-sonnet-4-5", MatchKind::Fuzzy))
+        );
+        assert_eq!(
+            resolve("sonnet-4.5"),
+            Some(("claude-sonnet-4-5", MatchKind::Fuzzy))
+        );
+        // Normalization keeps the mini variant apart from its family
+        assert_eq!(
+            resolve("gpt-4o-mini-2024-07-18"),
+            Some(("gpt-4o-mini", MatchKind::Normalized))
+        );
+        // The closest key wins over the first one in sort order
+        assert_eq!(
+            resolve("qwen3-coder"),
+            Some(("qwen3-coder-plus", MatchKind::Fuzzy))
+        );
+        assert_eq!(
+            resolve("mistral-large-2411-custom"),
+            Some(("mistral-large-2411", MatchKind::Fuzzy))
+        );
+        assert_eq!(resolve("gpt-4"), None);
+        assert_eq!(resolve(""), None);
+    }
+
+    #[test]
+    fn test_normalized_match() {
+        let mut pricing = PricingData::new();
+        pricing.add_model("sonnet-4-5".to_string(), ModelPricing::default());
+        pricing.finalize();
+
+        let found = pricing.resolve("claude-4.5-sonnet-thinking").unwrap();
+        assert_eq!(
+            (found.key, found.kind),
+            ("sonnet-4-5", MatchKind::Normalized)
+        );
+
+        // The cached path reports the same match
+        assert!(pricing
+            .get_pricing_cached("claude-4.5-sonnet-thinking")
+            .is_some());
+        let cached = pricing.resolve("claude-4.5-sonnet-thinking").unwrap();
+        assert_eq!(cached.kind, MatchKind::Normalized);
+    }
+
+    #[test]
+    fn test_word_boundary_checks_every_occurrence() {
+        assert!(is_word_boundary_match("gpt-4o/gpt-4", "gpt-4"));
+        assert!(!is_word_boundary_match("gpt-4o-mini", "gpt-4"));
+        assert!(!is_word_boundary_match("gpt-4o", ""));
+    }
+
+    fn sonnet_with_long_context() -> PricingData {
+        let mut model = ModelPricing {
+            input_cost_per_token: 3.0 / 1_000_000.0,
+            output_cost_per_token: 15.0 / 1_000_000.0,
+            cache_read_input_token_cost: 0.3 / 1_000_000.0,
+            cache_creation_input_token_cost: 3.75 / 1_000_000.0,
+            ..Default::default()
+        };
+        model.add_tier(PricingTier {
+            threshold: 200_000,
+            input_cost_per_token: Some(6.0 / 1_000_000.0),
+            output_cost_per_token: Some(22.5 / 1_000_000.0),
+            cache_read_input_token_cost: Some(0.6 / 1_000_000.0),
+            cache_creation_input_token_cost: None,
+        });
+
+        let mut pricing = PricingData::new();
+        pricing.add_model("claude-sonnet-4-5".to_string(), model);
+        pricing.finalize();
+        pricing
+    }
+
+    #[test]
+    fn test_long_context_tier_below_threshold() {
+        let pricing = sonnet_with_long_context();
+
+        // Exactly 200k prompt tokens stays on the base rate
+        let cost = pricing.calculate_cost("claude-sonnet-4-5", 100_000, 1_000, 100_000, 0, 0);
+        let expected = 100_000.0 * 3.0e-6 + 1_000.0 * 15.0e-6 + 100_000.0 * 0.3e-6;
+        assert!((cost - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_long_context_tier_counts_cache_tokens() {
+        let pricing = sonnet_with_long_context();
+
+        // Input alone is small, but cache tokens push the prompt over 200k
+        let cost = pricing.calculate_cost("claude-sonnet-4-5", 1_000, 1_000, 200_000, 100, 0);
+        let expected = 1_000.0 * 6.0e-6 + 1_000.0 * 22.5e-6 + 200_000.0 * 0.6e-6 + 100.0 * 3.75e-6;
+        assert!((cost - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_highest_exceeded_tier_wins() {
+        let mut model = ModelPricing {
+            input_cost_per_token: 1.0,
+            ..Default::default()
+        };
+        model.add_tier(PricingTier {
+            threshold: 200_000,
+            input_cost_per_token: Some(3.0),
+            ..Default::default()
+        });
+        model.add_tier(PricingTier {
+            threshold: 128_000,
+            input_cost_per_token: Some(2.0),
+            ..Default::default()
+        });
+
+        assert_eq!(model.rates_for(100_000).input, 1.0);
+        assert_eq!(model.rates_for(150_000).input, 2.0);
+        assert_eq!(model.rates_for(250_000).input, 3.0);
+    }
+
+    fn tokens(input: i64, output: i64, cache_read: i64, reasoning: i64) -> TokenBreakdown {
+        TokenBreakdown {
+            input,
+            output,
+            cache_read,
+            cache_write: 0,
+            reasoning,
+        }
+    }
+
+    fn flat_pricing(reasoning_rate: Option<f64>) -> PricingData {
+        let mut pricing = PricingData::new();
+        pricing.add_model(
+            "model".to_string(),
+            ModelPricing {
+                input_cost_per_token: 1.0,
+                output_cost_per_token: 10.0,
+                cache_read_input_token_cost: 0.1,
+                output_cost_per_reasoning_token: reasoning_rate,
+                ..Default::default()
+            },
+        );
+        pricing.finalize();
+        pricing
+    }
+
+    #[test]
+    fn test_billing_policy_table() {
+        assert!(billing_policy("gemini").free_cache);
+        assert!(billing_policy("qwen").free_cache);
+        assert!(billing_policy("cursor").fallback_to_recorded_cost);
+        assert_eq!(billing_policy("claude"), BillingPolicy::STANDARD);
+        assert_eq!(billing_policy("unknown-source"), BillingPolicy::STANDARD);
+    }
+
+    #[test]
+    fn test_policy_free_cache_and_reasoning() {
+        let pricing = flat_pricing(None);
+        let t = tokens(100, 10, 50, 5);
+
+        let standard = pricing.calculate_cost_with_policy("model", &t, &BillingPolicy::STANDARD, 0);
+        assert!((standard - (100.0 + 150.0 + 5.0)).abs() < 1e-9);
+
+        let gemini = pricing.calculate_cost_with_policy("model", &t, &billing_policy("gemini"), 0);
+        assert!((gemini - (100.0 + 150.0)).abs() < 1e-9);
+
+        // Qwen Code: cached tokens are part of the 900 input tokens and billed once
+        let qwen = tokens(900, 120, 300, 40);
+        let cost = pricing.calculate_cost_with_policy("model", &qwen, &billing_policy("qwen"), 0);
+        assert!((cost - (900.0 + 1600.0)).abs() < 1e-9);
+
+        let reasoning_in_output = BillingPolicy {
+            reasoning_billed_separately: false,
+            ..BillingPolicy::STANDARD
+        };
+        let cost = pricing.calculate_cost_with_policy("model", &t, &reasoning_in_output, 0);
+        assert!((cost - (100.0 + 100.0 + 5.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_separate_reasoning_rate() {
+        let pricing = flat_pricing(Some(20.0));
+        let cost = pricing.calculate_cost("model", 0, 1, 0, 0, 2);
+        assert!((cost - (10.0 + 40.0)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,520 @@
+//! Local pricing overrides
+//!
+//! A user file (`~/.config/tokscale/pricing.toml` or `pricing.json`) merged on
+//! top of the base pricing data, for negotiated rates and for self-hosted
+//! models that LiteLLM does not list:
+//!
+//! ```toml
+//! # Exact model ids; rates left out keep the base price
+//! [models."claude-sonnet-4-5"]
+//! input_cost_per_token = 2.4e-6
+//! output_cost_per_token = 1.2e-5
+//!
+//! [models."llama3.1:8b"]
+//! free = true
+//!
+//! # Checked in order for models without an exact entry; rates left out are 0
+//! [[patterns]]
+//! glob = "vllm/*"
+//! input_cost_per_token = 1e-7
+//! output_cost_per_token = 2e-7
+//!
+//! [[patterns]]
+//! regex = "^qwen.*-local$"
+//! free = true
+//!
+//! # Percentage off the base rates of a provider's models
+//! [providers.anthropic]
+//! discount_percent = 15
+//! ```
+//!
+//! The JSON form uses the same keys. Exact entries win over patterns, and both
+//! win over the base data. Provider discounts do not apply to models that have
+//! an exact entry.
+
+use super::{ModelPricing, PricingData, PricingError};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Contents of a pricing overrides file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PricingOverrides {
+    /// Overrides for exact model ids
+    #[serde(default)]
+    pub models: BTreeMap<String, RateOverride>,
+    /// Glob or regex patterns, checked in order
+    #[serde(default)]
+    pub patterns: Vec<PatternOverride>,
+    /// Discounts keyed by provider (LiteLLM's `litellm_provider`)
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderOverride>,
+}
+
+/// Per-token rates set by an override; `free` zeroes every rate
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateOverride {
+    pub input_cost_per_token: Option<f64>,
+    pub output_cost_per_token: Option<f64>,
+    pub cache_read_input_token_cost: Option<f64>,
+    pub cache_creation_input_token_cost: Option<f64>,
+    pub output_cost_per_reasoning_token: Option<f64>,
+    #[serde(default)]
+    pub free: bool,
+}
+
+/// Rates for every model matching `glob` or `regex` (exactly one must be set)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "PatternEntry")]
+pub struct PatternOverride {
+    pub glob: Option<String>,
+    pub regex: Option<String>,
+    pub rates: RateOverride,
+}
+
+/// `[[patterns]]` entry as written; `deny_unknown_fields` does not work
+/// together with `flatten`, so leftover keys are collected and rejected
+#[derive(Deserialize)]
+struct PatternEntry {
+    glob: Option<String>,
+    regex: Option<String>,
+    #[serde(flatten)]
+    rates: RateOverride,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, serde::de::IgnoredAny>,
+}
+
+impl TryFrom<PatternEntry> for PatternOverride {
+    type Error = String;
+
+    fn try_from(entry: PatternEntry) -> Result<Self, Self::Error> {
+        if let Some(key) = entry.unknown.keys().next() {
+            return Err(format!("unknown field `{}` in pattern", key));
+        }
+        Ok(PatternOverride {
+            glob: entry.glob,
+            regex: entry.regex,
+            rates: entry.rates,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderOverride {
+    /// Percentage taken off every base rate, from 0 to 100
+    pub discount_percent: f64,
+}
+
+/// Override rule that priced a model
+#[derive(Debug, Clone, PartialEq)]
+pub enum PricingRule {
+    /// Entry under `[models]`
+    Exact(String),
+    Glob(String),
+    Regex(String),
+    /// Base rates with a provider discount applied
+    ProviderDiscount {
+        provider: String,
+        percent: f64,
+    },
+}
+
+impl fmt::Display for PricingRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PricingRule::Exact(model) => write!(f, "override {}", model),
+            PricingRule::Glob(pattern) => write!(f, "glob {}", pattern),
+            PricingRule::Regex(pattern) => write!(f, "regex {}", pattern),
+            PricingRule::ProviderDiscount { provider, percent } => {
+                write!(f, "{} discount {}%", provider, percent)
+            }
+        }
+    }
+}
+
+/// Compiled `[[patterns]]` entry
+#[derive(Debug, Clone)]
+pub(crate) struct PatternRule {
+    matcher: Matcher,
+    pub(crate) pricing: ModelPricing,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Glob(globset::GlobMatcher),
+    Regex(regex::Regex),
+}
+
+impl PatternRule {
+    /// The glob or regex as written
+    pub(crate) fn pattern(&self) -> &str {
+        match &self.pricing.rule {
+            Some(PricingRule::Glob(pattern) | PricingRule::Regex(pattern)) => pattern,
+            _ => "",
+        }
+    }
+
+    pub(crate) fn matches(&self, model_id: &str) -> bool {
+        match &self.matcher {
+            Matcher::Glob(glob) => glob.is_match(model_id),
+            Matcher::Regex(regex) => regex.is_match(model_id),
+        }
+    }
+}
+
+impl PricingOverrides {
+    /// Read an overrides file: `.json` files are parsed as JSON, anything else as TOML
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, PricingError> {
+        let path = path.as_ref();
+        let content = super::read_pricing_file(path)?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json_str(&content)
+        } else {
+            Self::from_toml_str(&content)
+        }
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self, PricingError> {
+        toml::from_str(content).map_err(|e| PricingError::Toml(e.to_string()))
+    }
+
+    pub fn from_json_str(content: &str) -> Result<Self, PricingError> {
+        serde_json::from_str(content).map_err(|e| PricingError::Json(e.to_string()))
+    }
+
+    /// `~/.config/tokscale/pricing.toml` or `pricing.json`, whichever exists
+    pub fn default_path(home_dir: impl AsRef<Path>) -> Option<PathBuf> {
+        let dir = home_dir.as_ref().join(".config").join("tokscale");
+        ["pricing.toml", "pricing.json"]
+            .into_iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+
+    fn compile_patterns(&self) -> Result<Vec<PatternRule>, PricingError> {
+        self.patterns
+            .iter()
+            .map(|entry| {
+                let invalid = |pattern: &str, message: String| PricingError::InvalidPattern {
+                    pattern: pattern.to_string(),
+                    message,
+                };
+                let (matcher, rule) = match (&entry.glob, &entry.regex) {
+                    (Some(glob), None) => {
+                        let matcher = globset::GlobBuilder::new(glob)
+                            .case_insensitive(true)
+                            .build()
+                            .map_err(|e| invalid(glob, e.to_string()))?
+                            .compile_matcher();
+                        (Matcher::Glob(matcher), PricingRule::Glob(glob.clone()))
+                    }
+                    (None, Some(regex)) => {
+                        let matcher =
+                            regex::Regex::new(regex).map_err(|e| invalid(regex, e.to_string()))?;
+                        (Matcher::Regex(matcher), PricingRule::Regex(regex.clone()))
+                    }
+                    (glob, regex) => {
+                        let pattern = glob.as_deref().or(regex.as_deref()).unwrap_or_default();
+                        return Err(invalid(
+                            pattern,
+                            "set exactly one of `glob` or `regex`".to_string(),
+                        ));
+                    }
+                };
+
+                let mut pricing = ModelPricing::default();
+                entry.rates.apply_to(&mut pricing);
+                pricing.rule = Some(rule);
+                Ok(PatternRule { matcher, pricing })
+            })
+            .collect()
+    }
+}
+
+impl RateOverride {
+    fn apply_to(&self, pricing: &mut ModelPricing) {
+        if self.free {
+            *pricing = ModelPricing {
+                provider: pricing.provider.take(),
+                mode: pricing.mode.take(),
+                free: true,
+                ..Default::default()
+            };
+            return;
+        }
+
+        let rates = [
+            (self.input_cost_per_token, &mut pricing.input_cost_per_token),
+            (
+                self.output_cost_per_token,
+                &mut pricing.output_cost_per_token,
+            ),
+            (
+                self.cache_read_input_token_cost,
+                &mut pricing.cache_read_input_token_cost,
+            ),
+            (
+                self.cache_creation_input_token_cost,
+                &mut pricing.cache_creation_input_token_cost,
+            ),
+        ];
+        for (value, target) in rates {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+        if self.output_cost_per_reasoning_token.is_some() {
+            pricing.output_cost_per_reasoning_token = self.output_cost_per_reasoning_token;
+        }
+        // The base tiers were priced against the base rates
+        pricing.tiers.clear();
+    }
+}
+
+/// Scale every rate of a model by `factor`
+fn scale_rates(pricing: &mut ModelPricing, factor: f64) {
+    pricing.input_cost_per_token *= factor;
+    pricing.output_cost_per_token *= factor;
+    pricing.cache_read_input_token_cost *= factor;
+    pricing.cache_creation_input_token_cost *= factor;
+
+    let optional = [
+        &mut pricing.output_cost_per_reasoning_token,
+        &mut pricing.input_cost_per_token_batches,
+        &mut pricing.output_cost_per_token_batches,
+    ];
+    for rate in optional.into_iter().flatten() {
+        *rate *= factor;
+    }
+    for tier in &mut pricing.tiers {
+        let rates = [
+            &mut tier.input_cost_per_token,
+            &mut tier.output_cost_per_token,
+            &mut tier.cache_read_input_token_cost,
+            &mut tier.cache_creation_input_token_cost,
+        ];
+        for rate in rates.into_iter().flatten() {
+            *rate *= factor;
+        }
+    }
+}
+
+impl PricingData {
+    /// Merge local overrides on top of this data. Nothing is changed if the
+    /// overrides contain an invalid pattern or discount.
+    pub fn apply_overrides(&mut self, overrides: &PricingOverrides) -> Result<(), PricingError> {
+        for (provider, discount) in &overrides.providers {
+            if !(0.0..=100.0).contains(&discount.discount_percent) {
+                return Err(PricingError::InvalidDiscount {
+                    provider: provider.clone(),
+                    percent: discount.discount_percent,
+                });
+            }
+        }
+        let patterns = overrides.compile_patterns()?;
+
+        for (model_id, rates) in &overrides.models {
+            let mut pricing = self.models.get(model_id).cloned().unwrap_or_default();
+            rates.apply_to(&mut pricing);
+            pricing.rule = Some(PricingRule::Exact(model_id.clone()));
+            // The file's rates apply at any time
+            pricing.effective_from = None;
+            self.models.insert(model_id.clone(), pricing);
+        }
+
+        for (provider, discount) in &overrides.providers {
+            let prefix = format!("{}/", provider.to_lowercase());
+            let dated = self
+                .history
+                .iter_mut()
+                .flat_map(|(model_id, versions)| versions.iter_mut().map(move |v| (model_id, v)));
+            for (model_id, pricing) in self.models.iter_mut().chain(dated) {
+                let served_by = match &pricing.provider {
+                    Some(p) => p.eq_ignore_ascii_case(provider),
+                    None => model_id.to_lowercase().starts_with(&prefix),
+                };
+                if served_by && pricing.rule.is_none() {
+                    scale_rates(pricing, 1.0 - discount.discount_percent / 100.0);
+                    pricing.rule = Some(PricingRule::ProviderDiscount {
+                        provider: provider.clone(),
+                        percent: discount.discount_percent,
+                    });
+                }
+            }
+        }
+
+        self.patterns.extend(patterns);
+        self.finalize();
+        Ok(())
+    }
+
+    /// Load and merge an overrides file (see `PricingOverrides::from_file`)
+    pub fn apply_overrides_file(&mut self, path: impl AsRef<Path>) -> Result<(), PricingError> {
+        let overrides = PricingOverrides::from_file(path)?;
+        self.apply_overrides(&overrides)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::PricingTier;
+    use std::io::Write;
+
+    const OVERRIDES: &str = r#"
+        [models."claude-sonnet-4-5"]
+        input_cost_per_token = 2e-6
+
+        [models."llama3.1:8b"]
+        free = true
+
+        [[patterns]]
+        glob = "vllm/*"
+        input_cost_per_token = 1e-7
+        output_cost_per_token = 2e-7
+
+        [[patterns]]
+        regex = "^qwen.*-local$"
+        free = true
+
+        [providers.anthropic]
+        discount_percent = 20
+    "#;
+
+    fn base_pricing() -> PricingData {
+        let mut pricing = PricingData::new();
+        let mut sonnet = ModelPricing {
+            input_cost_per_token: 3e-6,
+            output_cost_per_token: 15e-6,
+            provider: Some("anthropic".to_string()),
+            ..Default::default()
+        };
+        sonnet.add_tier(PricingTier {
+            threshold: 200_000,
+            input_cost_per_token: Some(6e-6),
+            ..Default::default()
+        });
+        pricing.add_model("claude-sonnet-4-5".to_string(), sonnet);
+        pricing.add_model(
+            "anthropic/claude-haiku-4-5".to_string(),
+            ModelPricing {
+                input_cost_per_token: 1e-6,
+                output_cost_per_token: 5e-6,
+                ..Default::default()
+            },
+        );
+        pricing.add_model(
+            "gpt-4o".to_string(),
+            ModelPricing {
+                input_cost_per_token: 2.5e-6,
+                provider: Some("openai".to_string()),
+                ..Default::default()
+            },
+        );
+        pricing.finalize();
+        pricing
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let mut pricing = base_pricing();
+        let overrides = PricingOverrides::from_toml_str(OVERRIDES).unwrap();
+        pricing.apply_overrides(&overrides).unwrap();
+
+        // Exact entry: set rates win, other rates keep the base price undiscounted
+        let sonnet = pricing.get_pricing("claude-sonnet-4-5").unwrap();
+        assert_eq!(sonnet.input_cost_per_token, 2e-6);
+        assert_eq!(sonnet.output_cost_per_token, 15e-6);
+        assert!(sonnet.tiers.is_empty());
+        assert_eq!(sonnet.rule_label(), "override claude-sonnet-4-5");
+
+        // Provider discount, matched by key prefix when no provider is recorded
+        let haiku = pricing.get_pricing("claude-haiku-4-5").unwrap();
+        assert!((haiku.input_cost_per_token - 0.8e-6).abs() < 1e-15);
+        assert_eq!(
+            pricing.pricing_rule("claude-haiku-4-5").as_deref(),
+            Some("anthropic discount 20%")
+        );
+
+        assert_eq!(pricing.pricing_rule("gpt-4o").as_deref(), Some("base"));
+        assert_eq!(
+            pricing.pricing_rule("VLLM/Mistral-7B").as_deref(),
+            Some("glob vllm/*")
+        );
+        assert_eq!(
+            pricing.pricing_rule("qwen3-coder-local").as_deref(),
+            Some("regex ^qwen.*-local$ (free)")
+        );
+        assert_eq!(
+            pricing.pricing_rule("llama3.1:8b").as_deref(),
+            Some("override llama3.1:8b (free)")
+        );
+        assert_eq!(pricing.pricing_rule("unknown-model"), None);
+
+        let cost = pricing.calculate_cost("vllm/mistral-7b", 1_000, 100, 0, 0, 0);
+        assert!((cost - (1_000.0 * 1e-7 + 100.0 * 2e-7)).abs() < 1e-15);
+        assert_eq!(
+            pricing.calculate_cost("llama3.1:8b", 1_000, 100, 0, 0, 0),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_overrides_from_json_file() {
+        let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        write!(
+            file,
+            r#"{{"patterns": [{{"glob": "ollama/*", "free": true}}], "providers": {{"openai": {{"discount_percent": 10}}}}}}"#
+        )
+        .unwrap();
+
+        let mut pricing = base_pricing();
+        pricing.apply_overrides_file(file.path()).unwrap();
+        assert!(pricing.get_pricing("ollama/llama3").unwrap().free);
+        let gpt = pricing.get_pricing("gpt-4o").unwrap();
+        assert!((gpt.input_cost_per_token - 2.25e-6).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        assert!(matches!(
+            PricingOverrides::from_toml_str("[models.x]\ninput_cost = 1"),
+            Err(PricingError::Toml(_))
+        ));
+        assert!(matches!(
+            PricingOverrides::from_toml_str("[[patterns]]\nglob = \"a*\"\ninput_cost = 1"),
+            Err(PricingError::Toml(_))
+        ));
+
+        let mut pricing = base_pricing();
+        for content in [
+            "[[patterns]]\nregex = \"(\"",
+            "[[patterns]]\nglob = \"a*\"\nregex = \"^a\"",
+            "[[patterns]]\nfree = true",
+            "[providers.openai]\ndiscount_percent = 150",
+        ] {
+            let overrides = PricingOverrides::from_toml_str(content).unwrap();
+            assert!(pricing.apply_overrides(&overrides).is_err(), "{}", content);
+        }
+        // Rejected overrides leave the data untouched
+        assert_eq!(pricing.pricing_rule("gpt-4o").as_deref(), Some("base"));
+    }
+
+    #[test]
+    fn test_default_path() {
+        let home = tempfile::TempDir::new().unwrap();
+        assert_eq!(PricingOverrides::default_path(home.path()), None);
+
+        let dir = home.path().join(".config").join("tokscale");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("pricing.json"), "{}").unwrap();
+        assert_eq!(
+            PricingOverrides::default_path(home.path()),
+            Some(dir.join("pricing.json"))
+        );
+    }
+}
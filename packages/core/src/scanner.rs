@@ -2,70 +2,148 @@
 //!
 //! Uses walkdir with rayon for parallel directory traversal.
 
+use crate::sessions::{SessionSource, SourceRegistry};
 use rayon::prelude::*;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-/// Session source type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SessionType {
-    OpenCode,
-    Claude,
-    Codex,
-    Gemini,
-    Cursor,
-    Amp,
-    Droid,
+/// Where to look for session files
+///
+/// The local home directory is scanned with each tool's default layout, or
+/// the directories named by the tool's environment variable (`CLAUDE_CONFIG_DIR`,
+/// `CODEX_HOME`, `XDG_DATA_HOME`). Extra home directories, such as logs synced
+/// from containers or remote machines, use the default layout only. Explicit
+/// `source_paths` replace both for their source. Archives are read in
+/// addition to all of them (see `archive`). `sources` holds the sources
+/// available to the scan, built-in only unless set with `with_sources`.
+#[derive(Debug, Clone, Default)]
+pub struct ScanRoots {
+    pub home_dir: String,
+    pub extra_home_dirs: Vec<String>,
+    /// Directories to scan per source name
+    pub source_paths: HashMap<String, Vec<String>>,
+    /// `.tar`, `.tar.gz`/`.tgz` and `.zip` snapshots of tool directories
+    pub archives: Vec<String>,
+    pub sources: SourceRegistry,
+}
+
+/// A directory to scan and the tag given to messages found under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRoot {
+    pub path: String,
+    /// The home directory the root belongs to, or the root itself for
+    /// explicit `source_paths`
+    pub tag: String,
+}
+
+impl ScanRoots {
+    pub fn new(home_dir: impl Into<String>) -> Self {
+        Self {
+            home_dir: home_dir.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_extra_home_dirs(mut self, extra_home_dirs: Vec<String>) -> Self {
+        self.extra_home_dirs = extra_home_dirs;
+        self
+    }
+
+    pub fn with_source_paths(mut self, source_paths: HashMap<String, Vec<String>>) -> Self {
+        self.source_paths = source_paths;
+        self
+    }
+
+    pub fn with_archives(mut self, archives: Vec<String>) -> Self {
+        self.archives = archives;
+        self
+    }
+
+    pub fn with_sources(mut self, sources: SourceRegistry) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Directories to scan for a source, without duplicates
+    pub fn source_roots(&self, source: &dyn SessionSource) -> Vec<SourceRoot> {
+        let mut roots: Vec<SourceRoot> = Vec::new();
+        let mut push = |path: String, tag: &str| {
+            if !roots.iter().any(|r| r.path == path) {
+                roots.push(SourceRoot {
+                    path,
+                    tag: tag.to_string(),
+                });
+            }
+        };
+
+        if let Some(paths) = self.source_paths.get(source.name()) {
+            for path in paths {
+                push(path.clone(), path);
+            }
+            return roots;
+        }
+
+        let local = source
+            .env_roots()
+            .unwrap_or_else(|| source.roots(&self.home_dir));
+        for path in local {
+            push(path, &self.home_dir);
+        }
+        for home_dir in &self.extra_home_dirs {
+            for path in source.roots(home_dir) {
+                push(path, home_dir);
+            }
+        }
+        roots
+    }
+
+    /// Tag of the first root of `source` that contains `path`
+    pub fn tag_for(&self, source: &dyn SessionSource, path: &Path) -> Option<String> {
+        self.source_roots(source)
+            .into_iter()
+            .find(|root| path.starts_with(&root.path))
+            .map(|root| root.tag)
+    }
 }
 
 /// Result of scanning all session directories
 #[derive(Debug, Default)]
 pub struct ScanResult {
-    pub opencode_files: Vec<PathBuf>,
-    pub claude_files: Vec<PathBuf>,
-    pub codex_files: Vec<PathBuf>,
-    pub gemini_files: Vec<PathBuf>,
-    pub cursor_files: Vec<PathBuf>,
-    pub amp_files: Vec<PathBuf>,
-    pub droid_files: Vec<PathBuf>,
+    /// Files found for each scanned source, in registry order
+    pub sources: Vec<(String, Vec<PathBuf>)>,
+    /// Tag of the root each file was found under (see `SourceRoot`)
+    pub tags: HashMap<PathBuf, String>,
 }
 
 impl ScanResult {
+    /// Get the files found for a source (empty if the source was not scanned)
+    pub fn files(&self, source: &str) -> &[PathBuf] {
+        self.sources
+            .iter()
+            .find(|(name, _)| *name == source)
+            .map(|(_, files)| files.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Tag of the root a scanned file was found under
+    pub fn tag(&self, path: &Path) -> Option<&str> {
+        self.tags.get(path).map(String::as_str)
+    }
+
     /// Get total number of files found
     pub fn total_files(&self) -> usize {
-        self.opencode_files.len()
-            + self.claude_files.len()
-            + self.codex_files.len()
-            + self.gemini_files.len()
-            + self.cursor_files.len()
-            + self.amp_files.len()
-            + self.droid_files.len()
+        self.sources.iter().map(|(_, files)| files.len()).sum()
     }
 
     /// Get all files as a single vector
-    pub fn all_files(&self) -> Vec<(SessionType, PathBuf)> {
+    pub fn all_files(&self) -> Vec<(&str, PathBuf)> {
         let mut result = Vec::with_capacity(self.total_files());
 
-        for path in &self.opencode_files {
-            result.push((SessionType::OpenCode, path.clone()));
-        }
-        for path in &self.claude_files {
-            result.push((SessionType::Claude, path.clone()));
-        }
-        for path in &self.codex_files {
-            result.push((SessionType::Codex, path.clone()));
-        }
-        for path in &self.gemini_files {
-            result.push((SessionType::Gemini, path.clone()));
-        }
-        for path in &self.cursor_files {
-            result.push((SessionType::Cursor, path.clone()));
-        }
-        for path in &self.amp_files {
-            result.push((SessionType::Amp, path.clone()));
-        }
-        for path in &self.droid_files {
-            result.push((SessionType::Droid, path.clone()));
+        for (name, files) in &self.sources {
+            for path in files {
+                result.push((name.as_str(), path.clone()));
+            }
         }
 
         result
@@ -89,108 +167,77 @@ pub fn scan_directory(root: &str, pattern: &str) -> Vec<PathBuf> {
             }
 
             let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-
-            match pattern {
-                "*.json" => file_name.ends_with(".json"),
-                "*.jsonl" => file_name.ends_with(".jsonl"),
-                "*.csv" => file_name.ends_with(".csv"),
-                "session-*.json" => {
-                    file_name.starts_with("session-") && file_name.ends_with(".json")
-                }
-                "T-*.json" => {
-                    file_name.starts_with("T-") && file_name.ends_with(".json")
-                }
-                "*.settings.json" => file_name.ends_with(".settings.json"),
-                _ => false,
-            }
+            matches_pattern(file_name, pattern)
         })
         .map(|e| e.path().to_path_buf())
         .collect()
 }
 
-/// Scan all session source directories in parallel
-pub fn scan_all_sources(home_dir: &str, sources: &[String]) -> ScanResult {
-    let mut result = ScanResult::default();
-
-    let include_all = sources.is_empty();
-    let include_opencode = include_all || sources.iter().any(|s| s == "opencode");
-    let include_claude = include_all || sources.iter().any(|s| s == "claude");
-    let include_codex = include_all || sources.iter().any(|s| s == "codex");
-    let include_gemini = include_all || sources.iter().any(|s| s == "gemini");
-    let include_cursor = include_all || sources.iter().any(|s| s == "cursor");
-    let include_amp = include_all || sources.iter().any(|s| s == "amp");
-    let include_droid = include_all || sources.iter().any(|s| s == "droid");
-
-    // Define scan tasks
-    let mut tasks: Vec<(SessionType, String, &str)> = Vec::new();
-
-    if include_opencode {
-        // OpenCode: ~/.local/share/opencode/storage/message/*/*.json
-        let xdg_data =
-            std::env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", home_dir));
-        let opencode_path = format!("{}/opencode/storage/message", xdg_data);
-        tasks.push((SessionType::OpenCode, opencode_path, "*.json"));
-    }
-
-    if include_claude {
-        // Claude: ~/.claude/projects/**/*.jsonl
-        let claude_path = format!("{}/.claude/projects", home_dir);
-        tasks.push((SessionType::Claude, claude_path, "*.jsonl"));
-    }
-
-    if include_codex {
-        // Codex: ~/.codex/sessions/**/*.jsonl
-        let codex_home =
-            std::env::var("CODEX_HOME").unwrap_or_else(|_| format!("{}/.codex", home_dir));
-        let codex_path = format!("{}/sessions", codex_home);
-        tasks.push((SessionType::Codex, codex_path, "*.jsonl"));
-    }
-
-    if include_gemini {
-        // Gemini: ~/.gemini/tmp/*/chats/session-*.json
-        let gemini_path = format!("{}/.gemini/tmp", home_dir);
-        tasks.push((SessionType::Gemini, gemini_path, "session-*.json"));
-    }
-
-    if include_cursor {
-        // Cursor: ~/.config/tokscale/cursor-cache/*.csv (migrated from ~/.tokscale)
-        let cursor_path = format!("{}/.config/tokscale/cursor-cache", home_dir);
-        tasks.push((SessionType::Cursor, cursor_path, "*.csv"));
+/// Check a file name against a source's file pattern
+pub fn matches_pattern(file_name: &str, pattern: &str) -> bool {
+    match pattern {
+        "*.json" => file_name.ends_with(".json"),
+        "*.jsonl" => file_name.ends_with(".jsonl"),
+        "*.csv" => file_name.ends_with(".csv"),
+        "session-*.json" => file_name.starts_with("session-") && file_name.ends_with(".json"),
+        "T-*.json" => file_name.starts_with("T-") && file_name.ends_with(".json"),
+        "*.settings.json" => file_name.ends_with(".settings.json"),
+        // Any file; the source narrows it down in `accepts`
+        "*" => true,
+        _ => false,
     }
+}
 
-    if include_amp {
-        // Amp: ~/.local/share/amp/threads/T-*.json
-        let xdg_data =
-            std::env::var("XDG_DATA_HOME").unwrap_or_else(|_| format!("{}/.local/share", home_dir));
-        let amp_path = format!("{}/amp/threads", xdg_data);
-        tasks.push((SessionType::Amp, amp_path, "T-*.json"));
-    }
+/// Scan all session source directories under a home directory in parallel
+///
+/// An empty `sources` slice scans every built-in source.
+pub fn scan_all_sources(home_dir: &str, sources: &[String]) -> ScanResult {
+    scan_sources(&ScanRoots::new(home_dir), sources)
+}
 
-    if include_droid {
-        // Droid: ~/.factory/sessions/*.settings.json
-        let droid_path = format!("{}/.factory/sessions", home_dir);
-        tasks.push((SessionType::Droid, droid_path, "*.settings.json"));
-    }
+/// Scan the roots of the given sources in parallel (every source of
+/// `roots.sources` when `sources` is empty). A file reachable from several roots is listed
+/// once, under the first of them.
+pub fn scan_sources(roots: &ScanRoots, sources: &[String]) -> ScanResult {
+    // Define scan tasks
+    let selected = roots.sources.select(sources);
+
+    let tasks: Vec<(usize, SourceRoot, &dyn SessionSource)> = selected
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, source)| {
+            roots
+                .source_roots(source.as_ref())
+                .into_iter()
+                .map(move |root| (idx, root, source.as_ref()))
+        })
+        .collect();
 
     // Execute scans in parallel
-    let scan_results: Vec<(SessionType, Vec<PathBuf>)> = tasks
+    let scan_results: Vec<(usize, String, Vec<PathBuf>)> = tasks
         .into_par_iter()
-        .map(|(session_type, path, pattern)| {
-            let files = scan_directory(&path, pattern);
-            (session_type, files)
+        .map(|(idx, root, source)| {
+            let mut files = scan_directory(&root.path, source.pattern());
+            files.retain(|path| source.accepts(path));
+            (idx, root.tag, files)
         })
         .collect();
 
     // Aggregate results
-    for (session_type, files) in scan_results {
-        match session_type {
-            SessionType::OpenCode => result.opencode_files = files,
-            SessionType::Claude => result.claude_files = files,
-            SessionType::Codex => result.codex_files = files,
-            SessionType::Gemini => result.gemini_files = files,
-            SessionType::Cursor => result.cursor_files = files,
-            SessionType::Amp => result.amp_files = files,
-            SessionType::Droid => result.droid_files = files,
+    let mut result = ScanResult {
+        sources: selected
+            .iter()
+            .map(|s| (s.name().to_string(), Vec::new()))
+            .collect(),
+        tags: HashMap::new(),
+    };
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    for (idx, tag, files) in scan_results {
+        for path in files {
+            if seen.insert(path.clone()) {
+                result.tags.insert(path.clone(), tag.clone());
+                result.sources[idx].1.push(path);
+            }
         }
     }
 
@@ -207,13 +254,16 @@ mod tests {
     #[test]
     fn test_scan_result_total_files() {
         let result = ScanResult {
-            opencode_files: vec![PathBuf::from("a.json"), PathBuf::from("b.json")],
-            claude_files: vec![PathBuf::from("c.jsonl")],
-            codex_files: vec![],
-            gemini_files: vec![PathBuf::from("d.json")],
-            cursor_files: vec![],
-            amp_files: vec![],
-            droid_files: vec![],
+            sources: vec![
+                (
+                    "opencode".to_string(),
+                    vec![PathBuf::from("a.json"), PathBuf::from("b.json")],
+                ),
+                ("claude".to_string(), vec![PathBuf::from("c.jsonl")]),
+                ("codex".to_string(), vec![]),
+                ("gemini".to_string(), vec![PathBuf::from("d.json")]),
+            ],
+            ..Default::default()
         };
         assert_eq!(result.total_files(), 4);
     }
@@ -221,22 +271,24 @@ mod tests {
     #[test]
     fn test_scan_result_all_files() {
         let result = ScanResult {
-            opencode_files: vec![PathBuf::from("a.json")],
-            claude_files: vec![PathBuf::from("b.jsonl")],
-            codex_files: vec![PathBuf::from("c.jsonl")],
-            gemini_files: vec![PathBuf::from("d.json")],
-            cursor_files: vec![PathBuf::from("e.csv")],
-            amp_files: vec![],
-            droid_files: vec![],
+            sources: vec![
+                ("opencode".to_string(), vec![PathBuf::from("a.json")]),
+                ("claude".to_string(), vec![PathBuf::from("b.jsonl")]),
+                ("codex".to_string(), vec![PathBuf::from("c.jsonl")]),
+                ("gemini".to_string(), vec![PathBuf::from("d.json")]),
+                ("cursor".to_string(), vec![PathBuf::from("e.csv")]),
+                ("amp".to_string(), vec![]),
+            ],
+            ..Default::default()
         };
 
         let all = result.all_files();
         assert_eq!(all.len(), 5);
-        assert_eq!(all[0], (SessionType::OpenCode, PathBuf::from("a.json")));
-        assert_eq!(all[1], (SessionType::Claude, PathBuf::from("b.jsonl")));
-        assert_eq!(all[2], (SessionType::Codex, PathBuf::from("c.jsonl")));
-        assert_eq!(all[3], (SessionType::Gemini, PathBuf::from("d.json")));
-        assert_eq!(all[4], (SessionType::Cursor, PathBuf::from("e.csv")));
+        assert_eq!(all[0], ("opencode", PathBuf::from("a.json")));
+        assert_eq!(all[1], ("claude", PathBuf::from("b.jsonl")));
+        assert_eq!(all[2], ("codex", PathBuf::from("c.jsonl")));
+        assert_eq!(all[3], ("gemini", PathBuf::from("d.json")));
+        assert_eq!(all[4], ("cursor", PathBuf::from("e.csv")));
     }
 
     #[test]
@@ -244,12 +296,7 @@ mod tests {
         let result = ScanResult::default();
         assert_eq!(result.total_files(), 0);
         assert!(result.all_files().is_empty());
-    }
-
-    #[test]
-    fn test_session_type_equality() {
-        assert_eq!(SessionType::OpenCode, SessionType::OpenCode);
-        assert_ne!(SessionType::OpenCode, SessionType::Claude);
+        assert!(result.files("claude").is_empty());
     }
 
     #[test]
@@ -386,10 +433,10 @@ mod tests {
         std::env::set_var("XDG_DATA_HOME", home.join(".local/share"));
 
         let result = scan_all_sources(home.to_str().unwrap(), &["opencode".to_string()]);
-        assert_eq!(result.opencode_files.len(), 1);
-        assert!(result.claude_files.is_empty());
-        assert!(result.codex_files.is_empty());
-        assert!(result.gemini_files.is_empty());
+        assert_eq!(result.files("opencode").len(), 1);
+        assert!(result.files("claude").is_empty());
+        assert!(result.files("codex").is_empty());
+        assert!(result.files("gemini").is_empty());
     }
 
     #[test]
@@ -399,8 +446,8 @@ mod tests {
         setup_mock_claude_dir(home);
 
         let result = scan_all_sources(home.to_str().unwrap(), &["claude".to_string()]);
-        assert_eq!(result.claude_files.len(), 1);
-        assert!(result.opencode_files.is_empty());
+        assert_eq!(result.files("claude").len(), 1);
+        assert!(result.files("opencode").is_empty());
     }
 
     #[test]
@@ -410,8 +457,35 @@ mod tests {
         setup_mock_gemini_dir(home);
 
         let result = scan_all_sources(home.to_str().unwrap(), &["gemini".to_string()]);
-        assert_eq!(result.gemini_files.len(), 1);
-        assert!(result.opencode_files.is_empty());
+        assert_eq!(result.files("gemini").len(), 1);
+        assert!(result.files("opencode").is_empty());
+    }
+
+    #[test]
+    fn test_scan_sources_user_defined() {
+        let dir = TempDir::new().unwrap();
+        let home = dir.path();
+        let config_dir = home.join(".config/tokscale");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("sources.toml"),
+            "[[sources]]\nname = \"acme\"\nroot = \".acme/logs\"\nglob = \"run-*.jsonl\"\nmodel = \"/model\"\ntimestamp = \"/ts\"\n",
+        )
+        .unwrap();
+        let logs = home.join(".acme/logs/2025");
+        fs::create_dir_all(&logs).unwrap();
+        File::create(logs.join("run-1.jsonl")).unwrap();
+        File::create(logs.join("notes.jsonl")).unwrap();
+
+        let home_dir = home.to_str().unwrap();
+        let roots = ScanRoots::new(home_dir).with_sources(SourceRegistry::load(home_dir));
+        let result = scan_sources(&roots, &["acme".to_string()]);
+        assert_eq!(result.files("acme"), &[logs.join("run-1.jsonl")]);
+
+        // Sources outside the scan's registry are not scanned
+        assert!(scan_all_sources(home_dir, &["acme".to_string()])
+            .files("acme")
+            .is_empty());
     }
 
     #[test]
@@ -427,10 +501,21 @@ mod tests {
             &["claude".to_string(), "gemini".to_string()],
         );
 
-        assert_eq!(result.claude_files.len(), 1);
-        assert_eq!(result.gemini_files.len(), 1);
-        assert!(result.opencode_files.is_empty());
-        assert!(result.codex_files.is_empty());
+        assert_eq!(result.files("claude").len(), 1);
+        assert_eq!(result.files("gemini").len(), 1);
+        assert!(result.files("opencode").is_empty());
+        assert!(result.files("codex").is_empty());
+    }
+
+    #[test]
+    fn test_scan_all_sources_only_selected_are_listed() {
+        let dir = TempDir::new().unwrap();
+        let home = dir.path();
+        setup_mock_claude_dir(home);
+
+        let result = scan_all_sources(home.to_str().unwrap(), &["claude".to_string()]);
+        assert_eq!(result.sources.len(), 1);
+        assert_eq!(result.sources[0].0, "claude");
     }
 
     #[test]
@@ -443,6 +528,45 @@ mod tests {
         std::env::set_var("CODEX_HOME", home.join(".codex"));
 
         let result = scan_all_sources(home.to_str().unwrap(), &["codex".to_string()]);
-        assert_eq!(result.codex_files.len(), 1);
+        assert_eq!(result.files("codex").len(), 1);
+    }
+
+    #[test]
+    fn test_scan_sources_extra_homes_and_source_paths() {
+        let local = TempDir::new().unwrap();
+        let devbox = TempDir::new().unwrap();
+        let synced = TempDir::new().unwrap();
+        setup_mock_claude_dir(local.path());
+        setup_mock_claude_dir(devbox.path());
+        setup_mock_gemini_dir(devbox.path());
+        let gemini_root = synced.path().join("gemini");
+        setup_mock_gemini_dir(&gemini_root);
+
+        let local_home = local.path().to_str().unwrap().to_string();
+        let devbox_home = devbox.path().to_str().unwrap().to_string();
+        let gemini_path = gemini_root.to_str().unwrap().to_string();
+        let roots = ScanRoots::new(local_home.clone())
+            .with_extra_home_dirs(vec![devbox_home.clone(), local_home.clone()])
+            .with_source_paths(HashMap::from([(
+                "gemini".to_string(),
+                vec![gemini_path.clone(), gemini_path.clone()],
+            )]));
+
+        let result = scan_sources(&roots, &["claude".to_string(), "gemini".to_string()]);
+
+        // Claude: one file per home, tagged with the home it came from
+        let claude = result.files("claude");
+        assert_eq!(claude.len(), 2);
+        let mut tags: Vec<&str> = claude.iter().filter_map(|p| result.tag(p)).collect();
+        tags.sort();
+        let mut expected = vec![local_home.as_str(), devbox_home.as_str()];
+        expected.sort();
+        assert_eq!(tags, expected);
+
+        // Gemini: only the explicit root, not the devbox home
+        let gemini = result.files("gemini");
+        assert_eq!(gemini.len(), 1);
+        assert!(gemini[0].starts_with(&gemini_root));
+        assert_eq!(result.tag(&gemini[0]), Some(gemini_path.as_str()));
     }
 }
@@ -2,7 +2,8 @@
 //!
 //! Parses JSON files from ~/.local/share/amp/threads/
 
-use super::UnifiedMessage;
+use super::diagnostics::{parse_json_bytes, read_json_file};
+use super::{env_dir, ParsedFile, SessionSource, UnifiedMessage};
 use crate::TokenBreakdown;
 use serde::Deserialize;
 use std::path::Path;
@@ -88,18 +89,22 @@ fn get_provider_from_model(model: &str) -> &'static str {
 }
 
 /// Parse an Amp thread JSON file
-pub fn parse_amp_file(path: &Path) -> Vec<UnifiedMessage> {
-    let content = match std::fs::read(path) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
-    };
-
-    let mut bytes = content;
-    let thread: AmpThread = match simd_json::from_slice(&mut bytes) {
-        Ok(t) => t,
-        Err(_) => return Vec::new(),
-    };
+pub fn parse_amp_file(path: &Path) -> ParsedFile {
+    match read_json_file::<AmpThread>(path) {
+        Ok(thread) => thread_to_messages(path, thread).into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
+
+/// Parse an Amp thread JSON read from an archive entry at `path`
+pub fn parse_amp_bytes(path: &Path, data: &[u8]) -> ParsedFile {
+    match parse_json_bytes::<AmpThread>(data) {
+        Ok(thread) => thread_to_messages(path, thread).into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
 
+fn thread_to_messages(path: &Path, thread: AmpThread) -> Vec<UnifiedMessage> {
     let thread_id = thread
         .id
         .clone()
@@ -202,3 +207,34 @@ pub fn parse_amp_file(path: &Path) -> Vec<UnifiedMessage> {
 
     messages
 }
+
+/// Amp thread files
+pub struct AmpSource;
+
+impl SessionSource for AmpSource {
+    fn name(&self) -> &str {
+        "amp"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.local/share/amp/threads/T-*.json
+        vec![format!("{}/.local/share/amp/threads", home_dir)]
+    }
+
+    fn env_roots(&self) -> Option<Vec<String>> {
+        let xdg_data = env_dir("XDG_DATA_HOME")?;
+        Some(vec![format!("{}/amp/threads", xdg_data)])
+    }
+
+    fn pattern(&self) -> &'static str {
+        "T-*.json"
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_amp_file(path)
+    }
+
+    fn parse_bytes(&self, path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_amp_bytes(path, data))
+    }
+}
@@ -2,10 +2,15 @@
 //!
 //! Parses JSONL files from ~/.claude/projects/
 
-use super::UnifiedMessage;
+use super::diagnostics::jsonl_diagnostics;
+use super::{env_dir, Diagnostic, ParsedFile, ResumeState, SessionSource, UnifiedMessage};
+use crate::parser::{
+    fingerprint_before, read_jsonl_bytes, read_jsonl_range, split_jsonl_chunks, JsonlProgress, LineFilter, ParseError,
+    PARALLEL_CHUNK_SIZE,
+};
 use crate::TokenBreakdown;
+use rayon::prelude::*;
 use serde::Deserialize;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 /// Claude Code entry structure (from JSONL files)
@@ -15,10 +20,13 @@ pub struct ClaudeEntry {
     pub entry_type: String,
     pub timestamp: Option<String>,
     pub message: Option<ClaudeMessage>,
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ClaudeMessage {
+    pub id: Option<String>,
     pub model: Option<String>,
     pub usage: Option<ClaudeUsage>,
 }
@@ -31,74 +39,157 @@ pub struct ClaudeUsage {
     pub cache_creation_input_tokens: Option<i64>,
 }
 
+/// Only assistant entries with token usage produce messages; user prompts,
+/// tool results and summaries (most of a session's bytes) are skipped unread.
+const USAGE_LINES: LineFilter = LineFilter::containing_any(&["\"usage\""]);
+
 /// Parse a Claude Code JSONL file
-pub fn parse_claude_file(path: &Path) -> Vec<UnifiedMessage> {
-    let file = match std::fs::File::open(path) {
-        Ok(f) => f,
-        Err(_) => return Vec::new(),
-    };
+pub fn parse_claude_file(path: &Path) -> ParsedFile {
+    match parse_claude_file_from(path, &ResumeState::default()) {
+        Ok((parsed, _)) => parsed,
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
 
-    let session_id = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown")
-        .to_string();
+/// Parse a Claude Code JSONL file from the position reached by a previous parse
+///
+/// Returns the messages found and the state to resume from next time. Lines
+/// are independent, so large files are parsed in parallel chunks.
+pub fn parse_claude_file_from(
+    path: &Path,
+    resume: &ResumeState,
+) -> Result<(ParsedFile, ResumeState), Diagnostic> {
+    parse_claude_chunks(path, resume, PARALLEL_CHUNK_SIZE)
+}
+
+fn parse_claude_chunks(
+    path: &Path,
+    resume: &ResumeState,
+    chunk_size: u64,
+) -> Result<(ParsedFile, ResumeState), Diagnostic> {
+    let (session_id, project) = session_and_project(path);
+
+    let chunks = split_jsonl_chunks(path, resume.offset, chunk_size).map_err(Diagnostic::io)?;
+    let results: Vec<Result<(Vec<UnifiedMessage>, JsonlProgress), ParseError>> = chunks
+        .into_par_iter()
+        .map(|range| {
+            let mut messages = Vec::new();
+            let progress = read_jsonl_range(path, range, USAGE_LINES, |line| {
+                let entry: ClaudeEntry = match simd_json::from_slice(line) {
+                    Ok(e) => e,
+                    Err(_) => return false,
+                };
+
+                if let Some(msg) = entry_to_message(entry, &session_id) {
+                    messages.push(msg.with_project(project.clone()));
+                }
+                true
+            })?;
+            Ok((messages, progress))
+        })
+        .collect();
 
-    let reader = BufReader::new(file);
     let mut messages = Vec::new();
+    let mut progress = JsonlProgress {
+        offset: resume.offset,
+        ..Default::default()
+    };
+    for result in results {
+        let (chunk_messages, chunk_progress) = result.map_err(Diagnostic::io)?;
+        messages.extend(chunk_messages);
+        progress.append(chunk_progress);
+    }
 
-    for line in reader.lines() {
-        let line = match line {
-            Ok(l) => l,
-            Err(_) => continue,
-        };
+    let parsed = ParsedFile {
+        messages,
+        diagnostics: jsonl_diagnostics(&progress, resume.lines),
+    };
+    let next = ResumeState {
+        offset: progress.offset,
+        lines: resume.lines + progress.lines,
+        context: None,
+        fingerprint: fingerprint_before(path, progress.offset),
+    };
+    Ok((parsed, next))
+}
 
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
+/// Parse Claude Code JSONL data read from an archive entry at `path`
+pub fn parse_claude_bytes(path: &Path, data: &[u8]) -> ParsedFile {
+    let (session_id, project) = session_and_project(path);
 
-        let mut bytes = trimmed.as_bytes().to_vec();
-        let entry: ClaudeEntry = match simd_json::from_slice(&mut bytes) {
+    let mut messages = Vec::new();
+    let result = read_jsonl_bytes(data, USAGE_LINES, |line| {
+        let entry: ClaudeEntry = match simd_json::from_slice(line) {
             Ok(e) => e,
-            Err(_) => continue,
+            Err(_) => return false,
         };
 
-        // Only process assistant messages with usage data
-        if entry.entry_type != "assistant" {
-            continue;
+        if let Some(msg) = entry_to_message(entry, &session_id) {
+            messages.push(msg.with_project(project.clone()));
         }
+        true
+    });
 
-        let message = match entry.message {
-            Some(m) => m,
-            None => continue,
-        };
+    match result {
+        Ok(progress) => ParsedFile {
+            messages,
+            diagnostics: jsonl_diagnostics(&progress, 0),
+        },
+        Err(error) => ParsedFile::failed(Diagnostic::io(error)),
+    }
+}
 
-        let usage = match message.usage {
-            Some(u) => u,
-            None => continue,
-        };
+/// Session ID and project directory named by a session file's path
+fn session_and_project(path: &Path) -> (String, Option<String>) {
+    let session_id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
 
-        let model = match message.model {
-            Some(m) => m,
-            None => continue,
-        };
+    // ~/.claude/projects/{project-dir}/{session}.jsonl
+    let project = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+        .map(String::from);
+
+    (session_id, project)
+}
 
-        let timestamp = entry
-            .timestamp
-            .and_then(|ts| chrono::DateTime::parse_from_rfc3339(&ts).ok())
-            .map(|dt| dt.timestamp_millis())
-            .unwrap_or(0);
+fn entry_to_message(entry: ClaudeEntry, session_id: &str) -> Option<UnifiedMessage> {
+    // Only process assistant messages with usage data
+    if entry.entry_type != "assistant" {
+        return None;
+    }
 
-        if timestamp == 0 {
-            continue;
-        }
+    let message = entry.message?;
+    let usage = message.usage?;
+    let model = message.model?;
+
+    // Claude Code logs the same response once per content block and again
+    // when a session is resumed or forked, so key it for cross-file dedup
+    let dedup_key = match (message.id, entry.request_id) {
+        (Some(id), Some(request_id)) => Some(format!("{}:{}", id, request_id)),
+        _ => None,
+    };
+
+    let timestamp = entry
+        .timestamp
+        .and_then(|ts| chrono::DateTime::parse_from_rfc3339(&ts).ok())
+        .map(|dt| dt.timestamp_millis())
+        .unwrap_or(0);
 
-        messages.push(UnifiedMessage::new(
+    if timestamp == 0 {
+        return None;
+    }
+
+    Some(
+        UnifiedMessage::new(
             "claude",
             model,
             "anthropic",
-            session_id.clone(),
+            session_id,
             timestamp,
             TokenBreakdown {
                 input: usage.input_tokens.unwrap_or(0),
@@ -108,8 +199,238 @@ pub fn parse_claude_file(path: &Path) -> Vec<UnifiedMessage> {
                 reasoning: 0,
             },
             0.0, // Cost calculated later
-        ));
+        )
+        .with_dedup_key(dedup_key),
+    )
+}
+
+/// Project log directories of `CLAUDE_CONFIG_DIR`; a comma-separated list
+/// covers several config directories
+fn config_dir_roots(config_dirs: &str) -> Vec<String> {
+    config_dirs
+        .split(',')
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| format!("{}/projects", dir.trim_end_matches('/')))
+        .collect()
+}
+
+/// Claude Code project logs
+pub struct ClaudeSource;
+
+impl SessionSource for ClaudeSource {
+    fn name(&self) -> &str {
+        "claude"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.claude/projects/**/*.jsonl
+        vec![format!("{}/.claude/projects", home_dir)]
+    }
+
+    fn env_roots(&self) -> Option<Vec<String>> {
+        env_dir("CLAUDE_CONFIG_DIR").map(|dirs| config_dir_roots(&dirs))
+    }
+
+    fn pattern(&self) -> &'static str {
+        "*.jsonl"
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_claude_file(path)
+    }
+
+    fn parse_bytes(&self, path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_claude_bytes(path, data))
+    }
+
+    fn parse_file_from(
+        &self,
+        path: &Path,
+        resume: &ResumeState,
+    ) -> Option<(ParsedFile, ResumeState)> {
+        parse_claude_file_from(path, resume).ok()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sessions::deduplicate_messages;
+    use std::io::Write;
+
+    fn assistant_line(message_id: &str, request_id: &str, timestamp: &str, output: i64) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"{}","requestId":"{}","message":{{"id":"{}","model":"claude-sonnet-4-20250514","usage":{{"input_tokens":10,"output_tokens":{},"cache_read_input_tokens":100,"cache_creation_input_tokens":5}}}}}}"#,
+            timestamp, request_id, message_id, output
+        )
+    }
+
+    fn write_jsonl(dir: &Path, name: &str, lines: &[String]) -> std::path::PathBuf {
+        let path = dir.join(name);
+        let mut file = std::fs::File::create(&path).unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn test_parse_claude_file_sets_dedup_key() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_jsonl(
+            dir.path(),
+            "session.jsonl",
+            &[
+                r#"{"type":"user","timestamp":"2025-06-15T12:00:00Z"}"#.to_string(),
+                assistant_line("msg_1", "req_1", "2025-06-15T12:00:01Z", 20),
+            ],
+        );
 
-    messages
+        let messages = parse_claude_file(&path).messages;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].session_id, "session");
+        assert_eq!(messages[0].tokens.input, 10);
+        assert_eq!(messages[0].tokens.cache_read, 100);
+        assert_eq!(messages[0].dedup_key.as_deref(), Some("msg_1:req_1"));
+        assert!(messages[0].project.is_some());
+    }
+
+    #[test]
+    fn test_parse_claude_file_resumes_from_offset() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_jsonl(
+            dir.path(),
+            "session.jsonl",
+            &[assistant_line("msg_1", "req_1", "2025-06-15T12:00:01Z", 20)],
+        );
+
+        let (first, resume) = parse_claude_file_from(&path, &ResumeState::default()).unwrap();
+        assert_eq!(first.messages.len(), 1);
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        writeln!(
+            file,
+            "{}",
+            assistant_line("msg_2", "req_2", "2025-06-15T12:05:00Z", 30)
+        )
+        .unwrap();
+
+        let (appended, next) = parse_claude_file_from(&path, &resume).unwrap();
+        assert_eq!(appended.messages.len(), 1);
+        assert_eq!(appended.messages[0].tokens.output, 30);
+        assert!(next.offset > resume.offset);
+        assert_eq!(next.lines, 2);
+    }
+
+    #[test]
+    fn test_duplicate_content_blocks_and_forked_sessions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Same response written once per content block
+        let original = write_jsonl(
+            dir.path(),
+            "original.jsonl",
+            &[
+                assistant_line("msg_1", "req_1", "2025-06-15T12:00:01Z", 20),
+                assistant_line("msg_1", "req_1", "2025-06-15T12:00:02Z", 20),
+                assistant_line("msg_2", "req_2", "2025-06-15T12:01:00Z", 30),
+            ],
+        );
+        // Resumed session replays the earlier response into a new file
+        let forked = write_jsonl(
+            dir.path(),
+            "forked.jsonl",
+            &[
+                assistant_line("msg_1", "req_1", "2025-06-15T13:00:00Z", 20),
+                assistant_line("msg_3", "req_3", "2025-06-15T13:01:00Z", 40),
+            ],
+        );
+
+        let mut messages = parse_claude_file(&forked).messages;
+        messages.extend(parse_claude_file(&original).messages);
+        assert_eq!(messages.len(), 5);
+
+        let removed = deduplicate_messages(&mut messages);
+        assert_eq!(removed, 2);
+        assert_eq!(messages.len(), 3);
+
+        let total_output: i64 = messages.iter().map(|m| m.tokens.output).sum();
+        assert_eq!(total_output, 90);
+        let msg_1 = messages
+            .iter()
+            .find(|m| m.dedup_key.as_deref() == Some("msg_1:req_1"))
+            .unwrap();
+        assert_eq!(msg_1.session_id, "original");
+    }
+
+    #[test]
+    fn test_parse_claude_file_reports_malformed_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_jsonl(
+            dir.path(),
+            "session.jsonl",
+            &[
+                assistant_line("msg_1", "req_1", "2025-06-15T12:00:01Z", 20),
+                r#"{"type":"assistant","message":{"usage":"#.to_string(),
+                assistant_line("msg_2", "req_2", "2025-06-15T12:00:02Z", 20),
+                // Lines that cannot hold usage are skipped without parsing
+                "garbage".to_string(),
+                r#"{"usage": garbage"#.to_string(),
+            ],
+        );
+
+        let parsed = parse_claude_file(&path);
+        assert_eq!(parsed.messages.len(), 2);
+        assert_eq!(parsed.diagnostics.len(), 1);
+        let diagnostic = &parsed.diagnostics[0];
+        assert_eq!(
+            diagnostic.kind,
+            crate::sessions::diagnostics::DiagnosticKind::MalformedLines
+        );
+        assert_eq!(diagnostic.skipped_lines, 2);
+        assert_eq!(diagnostic.line, Some(2));
+    }
+
+    #[test]
+    fn test_parse_claude_chunks_matches_sequential_parse() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut lines: Vec<String> = (0..40)
+            .map(|i| {
+                assistant_line(
+                    &format!("msg_{}", i),
+                    &format!("req_{}", i),
+                    "2025-06-15T12:00:01Z",
+                    i,
+                )
+            })
+            .collect();
+        lines[25] = r#"{"type":"assistant","message":{"usage":"#.to_string();
+        let path = write_jsonl(dir.path(), "session.jsonl", &lines);
+
+        let resume = ResumeState::default();
+        let (sequential, sequential_resume) =
+            parse_claude_chunks(&path, &resume, u64::MAX).unwrap();
+        let (chunked, chunked_resume) = parse_claude_chunks(&path, &resume, 512).unwrap();
+
+        let outputs = |parsed: &ParsedFile| -> Vec<i64> {
+            parsed.messages.iter().map(|m| m.tokens.output).collect()
+        };
+        assert_eq!(chunked.messages.len(), 39);
+        assert_eq!(outputs(&chunked), outputs(&sequential));
+        assert_eq!(chunked.diagnostics, sequential.diagnostics);
+        assert_eq!(chunked.diagnostics[0].line, Some(26));
+        assert_eq!(chunked_resume.offset, sequential_resume.offset);
+        assert_eq!(chunked_resume.lines, 40);
+    }
+
+    #[test]
+    fn test_config_dir_roots() {
+        assert_eq!(
+            config_dir_roots("/home/me/.claude, /work/claude/ ,"),
+            vec!["/home/me/.claude/projects", "/work/claude/projects"]
+        );
+    }
 }
@@ -2,11 +2,22 @@
 //!
 //! Parses JSONL files from ~/.codex/sessions/
 //! Note: This parser has stateful logic to track model and delta calculations.
+//!
+//! Codex reports usage in OpenAI's schema, where `cached_input_tokens` is part
+//! of `input_tokens` and `reasoning_output_tokens` is part of `output_tokens`.
+//! Messages carry non-overlapping counts: input excludes cached tokens and
+//! output excludes reasoning tokens.
 
-use super::UnifiedMessage;
+use super::diagnostics::jsonl_diagnostics;
+use super::{env_dir, Diagnostic, ParsedFile, ResumeState, SessionSource, UnifiedMessage};
+use crate::parser::{
+    fingerprint_before, read_jsonl_bytes, read_jsonl_range, split_jsonl_chunks, JsonlProgress, LineFilter, ParseError,
+    PARALLEL_CHUNK_SIZE,
+};
 use crate::TokenBreakdown;
-use serde::Deserialize;
-use std::io::{BufRead, BufReader};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use std::path::Path;
 
 /// Codex entry structure (from JSONL files)
@@ -25,6 +36,8 @@ pub struct CodexPayload {
     pub model: Option<String>,
     pub model_name: Option<String>,
     pub info: Option<CodexInfo>,
+    /// Working directory, recorded in `session_meta` and `turn_context`
+    pub cwd: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,14 +54,109 @@ pub struct CodexTokenUsage {
     pub output_tokens: Option<i64>,
     pub cached_input_tokens: Option<i64>,
     pub cache_read_input_tokens: Option<i64>,
+    pub reasoning_output_tokens: Option<i64>,
+}
+
+/// Token counts in OpenAI's overlapping schema (cached within input,
+/// reasoning within output)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodexUsage {
+    pub input: i64,
+    pub output: i64,
+    pub cached: i64,
+    pub reasoning: i64,
 }
 
+impl CodexUsage {
+    fn from_token_usage(usage: &CodexTokenUsage) -> Self {
+        Self {
+            input: usage.input_tokens.unwrap_or(0),
+            output: usage.output_tokens.unwrap_or(0),
+            cached: usage
+                .cached_input_tokens
+                .or(usage.cache_read_input_tokens)
+                .unwrap_or(0),
+            reasoning: usage.reasoning_output_tokens.unwrap_or(0),
+        }
+    }
+
+    /// Usage added since `previous` cumulative totals
+    fn since(&self, previous: &CodexUsage) -> Self {
+        Self {
+            input: (self.input - previous.input).max(0),
+            output: (self.output - previous.output).max(0),
+            cached: (self.cached - previous.cached).max(0),
+            reasoning: (self.reasoning - previous.reasoning).max(0),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.input == 0 && self.output == 0 && self.cached == 0 && self.reasoning == 0
+    }
+
+    /// Split into non-overlapping categories
+    fn to_breakdown(self) -> TokenBreakdown {
+        let cached = self.cached.clamp(0, self.input.max(0));
+        let reasoning = self.reasoning.clamp(0, self.output.max(0));
+        TokenBreakdown {
+            input: self.input - cached,
+            output: self.output - reasoning,
+            cache_read: cached,
+            cache_write: 0,
+            reasoning,
+        }
+    }
+}
+
+/// State carried between lines of a Codex session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CodexState {
+    pub current_model: Option<String>,
+    /// Last cumulative totals from `total_token_usage`
+    pub previous_totals: Option<CodexUsage>,
+    /// Working directory of the session
+    pub project: Option<String>,
+}
+
+/// Lines that can change parser state or carry usage: `session_meta` and
+/// `turn_context` (working directory, model) and `token_count` events
+const STATE_LINES: LineFilter =
+    LineFilter::containing_any(&["token_count", "turn_context", "session_meta"]);
+
 /// Parse a Codex JSONL file with stateful tracking
-pub fn parse_codex_file(path: &Path) -> Vec<UnifiedMessage> {
-    let file = match std::fs::File::open(path) {
-        Ok(f) => f,
-        Err(_) => return Vec::new(),
-    };
+pub fn parse_codex_file(path: &Path) -> ParsedFile {
+    match parse_codex_file_from(path, &ResumeState::default()) {
+        Ok((parsed, _)) => parsed,
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
+
+/// Parse a Codex JSONL file from the position reached by a previous parse
+///
+/// The [`CodexState`] at that position travels in the resume context. Returns
+/// the messages found and the state to resume from next time.
+///
+/// Large files are parsed in two passes: a pre-scan summarises the model,
+/// working directory and cumulative totals each chunk leaves behind, which
+/// gives every chunk its starting state; then all chunks are parsed in
+/// parallel.
+pub fn parse_codex_file_from(
+    path: &Path,
+    resume: &ResumeState,
+) -> Result<(ParsedFile, ResumeState), Diagnostic> {
+    parse_codex_chunks(path, resume, PARALLEL_CHUNK_SIZE)
+}
+
+fn parse_codex_chunks(
+    path: &Path,
+    resume: &ResumeState,
+    chunk_size: u64,
+) -> Result<(ParsedFile, ResumeState), Diagnostic> {
+    let mut state: CodexState = resume
+        .context
+        .clone()
+        .and_then(|ctx| serde_json::from_value(ctx).ok())
+        .unwrap_or_default();
 
     let session_id = path
         .file_stem()
@@ -56,110 +164,218 @@ pub fn parse_codex_file(path: &Path) -> Vec<UnifiedMessage> {
         .unwrap_or("unknown")
         .to_string();
 
-    let reader = BufReader::new(file);
+    let chunks = split_jsonl_chunks(path, resume.offset, chunk_size).map_err(Diagnostic::io)?;
+
+    // Pass 1: state changes made by every chunk but the last
+    let chunk_changes: Vec<CodexStateChanges> = chunks[..chunks.len() - 1]
+        .par_iter()
+        .map(|range| scan_state_changes(path, range.clone()))
+        .collect::<Result<_, _>>()
+        .map_err(Diagnostic::io)?;
+
+    let mut start_states = Vec::with_capacity(chunks.len());
+    start_states.push(state.clone());
+    for changes in chunk_changes {
+        changes.apply(&mut state);
+        start_states.push(state.clone());
+    }
+
+    // Pass 2: parse every chunk from its starting state
+    let results: Vec<Result<ParsedChunk, ParseError>> = chunks
+        .into_par_iter()
+        .zip(start_states)
+        .map(|(range, mut state)| {
+            let mut messages = Vec::new();
+            let progress = read_jsonl_range(path, range, STATE_LINES, |line| {
+                let entry: CodexEntry = match simd_json::from_slice(line) {
+                    Ok(e) => e,
+                    Err(_) => return false,
+                };
+
+                if let Some(msg) = state.process_entry(entry, &session_id) {
+                    messages.push(msg);
+                }
+                true
+            })?;
+            Ok((messages, progress, state))
+        })
+        .collect();
+
     let mut messages = Vec::new();
+    let mut progress = JsonlProgress {
+        offset: resume.offset,
+        ..Default::default()
+    };
+    for result in results {
+        let (chunk_messages, chunk_progress, chunk_state) = result.map_err(Diagnostic::io)?;
+        messages.extend(chunk_messages);
+        progress.append(chunk_progress);
+        state = chunk_state;
+    }
+
+    let parsed = ParsedFile {
+        messages,
+        diagnostics: jsonl_diagnostics(&progress, resume.lines),
+    };
+    let next = ResumeState {
+        offset: progress.offset,
+        lines: resume.lines + progress.lines,
+        context: serde_json::to_value(state).ok(),
+        fingerprint: fingerprint_before(path, progress.offset),
+    };
+    Ok((parsed, next))
+}
 
-    // Stateful tracking
-    let mut current_model: Option<String> = None;
-    let mut previous_totals: Option<(i64, i64, i64)> = None; // (input, output, cached)
+/// Parse Codex JSONL data read from an archive entry at `path`
+pub fn parse_codex_bytes(path: &Path, data: &[u8]) -> ParsedFile {
+    let session_id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
 
-    for line in reader.lines() {
-        let line = match line {
-            Ok(l) => l,
-            Err(_) => continue,
+    let mut state = CodexState::default();
+    let mut messages = Vec::new();
+    let result = read_jsonl_bytes(data, STATE_LINES, |line| {
+        let entry: CodexEntry = match simd_json::from_slice(line) {
+            Ok(e) => e,
+            Err(_) => return false,
         };
 
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
+        if let Some(msg) = state.process_entry(entry, &session_id) {
+            messages.push(msg);
         }
+        true
+    });
 
-        let mut bytes = trimmed.as_bytes().to_vec();
-        let entry: CodexEntry = match simd_json::from_slice(&mut bytes) {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
+    match result {
+        Ok(progress) => ParsedFile {
+            messages,
+            diagnostics: jsonl_diagnostics(&progress, 0),
+        },
+        Err(error) => ParsedFile::failed(Diagnostic::io(error)),
+    }
+}
 
-        let payload = match entry.payload {
-            Some(p) => p,
-            None => continue,
+/// Messages, read progress and final state of one parsed chunk
+type ParsedChunk = (Vec<UnifiedMessage>, JsonlProgress, CodexState);
+
+/// Summarise the state changes made by the lines in `range`
+fn scan_state_changes(path: &Path, range: Range<u64>) -> Result<CodexStateChanges, ParseError> {
+    let mut changes = CodexStateChanges::default();
+    read_jsonl_range(
+        path,
+        range,
+        STATE_LINES,
+        |line| match simd_json::from_slice::<CodexEntry>(line) {
+            Ok(entry) => {
+                changes.then(CodexStateChanges::from_entry(&entry));
+                true
+            }
+            Err(_) => false,
+        },
+    )?;
+    Ok(changes)
+}
+
+/// State changes made by a run of entries. Each field is set only if the
+/// entries change it, so runs can be summarised independently of the state
+/// before them and then applied in order.
+#[derive(Debug, Clone, Default)]
+struct CodexStateChanges {
+    model: Option<Option<String>>,
+    totals: Option<CodexUsage>,
+    project: Option<String>,
+}
+
+impl CodexStateChanges {
+    fn from_entry(entry: &CodexEntry) -> Self {
+        let mut changes = Self::default();
+        let payload = match &entry.payload {
+            Some(payload) => payload,
+            None => return changes,
         };
 
-        // Extract model from turn_context
-        if entry.entry_type == "turn_context" {
-            current_model = extract_model(&payload);
-            continue;
-        }
+        changes.project = payload.cwd.clone().filter(|c| !c.is_empty());
 
-        // Process token_count events
-        if entry.entry_type != "event_msg" {
-            continue;
+        match entry.entry_type.as_str() {
+            // turn_context always sets the model, even to nothing
+            "turn_context" => changes.model = Some(extract_model(payload)),
+            "event_msg" if payload.payload_type.as_deref() == Some("token_count") => {
+                let info = payload.info.as_ref();
+                let info_model = info.and_then(|i| i.model.clone().or(i.model_name.clone()));
+                if let Some(model) = info_model.or_else(|| extract_model(payload)) {
+                    changes.model = Some(Some(model));
+                }
+                changes.totals = info
+                    .and_then(|i| i.total_token_usage.as_ref())
+                    .map(CodexUsage::from_token_usage);
+            }
+            _ => {}
         }
+        changes
+    }
 
-        if payload.payload_type.as_deref() != Some("token_count") {
-            continue;
+    /// Add the changes made by later entries
+    fn then(&mut self, later: CodexStateChanges) {
+        if later.model.is_some() {
+            self.model = later.model;
+        }
+        if later.totals.is_some() {
+            self.totals = later.totals;
         }
+        if later.project.is_some() {
+            self.project = later.project;
+        }
+    }
 
-        // Try to extract model from payload
-        if let Some(model) = extract_model(&payload) {
-            current_model = Some(model);
+    fn apply(self, state: &mut CodexState) {
+        if let Some(model) = self.model {
+            state.current_model = model;
         }
+        if self.totals.is_some() {
+            state.previous_totals = self.totals;
+        }
+        if self.project.is_some() {
+            state.project = self.project;
+        }
+    }
+}
 
-        let info = match payload.info {
-            Some(i) => i,
-            None => continue,
-        };
+impl CodexState {
+    fn process_entry(&mut self, entry: CodexEntry, session_id: &str) -> Option<UnifiedMessage> {
+        let previous_totals = self.previous_totals;
+        CodexStateChanges::from_entry(&entry).apply(self);
 
-        // Try to extract model from info
-        if let Some(model) = info.model.clone().or(info.model_name.clone()) {
-            current_model = Some(model);
+        // Only token_count events carry usage
+        if entry.entry_type != "event_msg" {
+            return None;
         }
+        let payload = entry.payload?;
+        if payload.payload_type.as_deref() != Some("token_count") {
+            return None;
+        }
+        let info = payload.info.as_ref()?;
 
-        let model = current_model
+        let model = self
+            .current_model
             .clone()
             .unwrap_or_else(|| "unknown".to_string());
 
-        // Calculate delta tokens
-        let (input, output, cached) = if let Some(last) = &info.last_token_usage {
-            (
-                last.input_tokens.unwrap_or(0),
-                last.output_tokens.unwrap_or(0),
-                last.cached_input_tokens
-                    .or(last.cache_read_input_tokens)
-                    .unwrap_or(0),
-            )
-        } else if let (Some(total), Some(prev)) = (&info.total_token_usage, &previous_totals) {
-            let curr_input = total.input_tokens.unwrap_or(0);
-            let curr_output = total.output_tokens.unwrap_or(0);
-            let curr_cached = total
-                .cached_input_tokens
-                .or(total.cache_read_input_tokens)
-                .unwrap_or(0);
-
-            (
-                (curr_input - prev.0).max(0),
-                (curr_output - prev.1).max(0),
-                (curr_cached - prev.2).max(0),
-            )
+        // Calculate delta tokens. Without a per-turn figure, diff the cumulative
+        // totals against the previous event. The first totals only set the
+        // baseline, since a resumed session carries over its earlier usage.
+        let delta = if let Some(last) = &info.last_token_usage {
+            CodexUsage::from_token_usage(last)
+        } else if let (Some(totals), Some(previous)) = (&info.total_token_usage, &previous_totals) {
+            CodexUsage::from_token_usage(totals).since(previous)
         } else {
-            continue;
+            return None;
         };
 
-        // Update previous totals
-        if let Some(total) = &info.total_token_usage {
-            previous_totals = Some((
-                total.input_tokens.unwrap_or(0),
-                total.output_tokens.unwrap_or(0),
-                total
-                    .cached_input_tokens
-                    .or(total.cache_read_input_tokens)
-                    .unwrap_or(0),
-            ));
-        }
-
         // Skip empty deltas
-        if input == 0 && output == 0 && cached == 0 {
-            continue;
+        if delta.is_empty() {
+            return None;
         }
 
         let timestamp = entry
@@ -169,24 +385,19 @@ pub fn parse_codex_file(path: &Path) -> Vec<UnifiedMessage> {
             .map(|dt| dt.timestamp_millis())
             .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
 
-        messages.push(UnifiedMessage::new(
-            "codex",
-            model,
-            "openai",
-            session_id.clone(),
-            timestamp,
-            TokenBreakdown {
-                input,
-                output,
-                cache_read: cached,
-                cache_write: 0,
-                reasoning: 0,
-            },
-            0.0, // Cost calculated later
-        ));
+        Some(
+            UnifiedMessage::new(
+                "codex",
+                model,
+                "openai",
+                session_id,
+                timestamp,
+                delta.to_breakdown(),
+                0.0, // Cost calculated later
+            )
+            .with_project(self.project.clone()),
+        )
     }
-
-    messages
 }
 
 fn extract_model(payload: &CodexPayload) -> Option<String> {
@@ -198,3 +409,214 @@ fn extract_model(payload: &CodexPayload) -> Option<String> {
         .or(payload.info.as_ref().and_then(|i| i.model_name.clone()))
         .filter(|m| !m.is_empty())
 }
+
+/// Codex CLI session logs
+pub struct CodexSource;
+
+impl SessionSource for CodexSource {
+    fn name(&self) -> &str {
+        "codex"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.codex/sessions/**/*.jsonl
+        vec![format!("{}/.codex/sessions", home_dir)]
+    }
+
+    fn env_roots(&self) -> Option<Vec<String>> {
+        let codex_home = env_dir("CODEX_HOME")?;
+        Some(vec![format!("{}/sessions", codex_home)])
+    }
+
+    fn pattern(&self) -> &'static str {
+        "*.jsonl"
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_codex_file(path)
+    }
+
+    fn parse_bytes(&self, path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_codex_bytes(path, data))
+    }
+
+    fn parse_file_from(
+        &self,
+        path: &Path,
+        resume: &ResumeState,
+    ) -> Option<(ParsedFile, ResumeState)> {
+        parse_codex_file_from(path, resume).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const TURN_CONTEXT: &str = r#"{"type":"turn_context","timestamp":"2025-06-15T12:00:00Z","payload":{"model":"gpt-5-codex"}}"#;
+
+    fn last_usage_line(timestamp: &str, input: i64, output: i64, cached: i64) -> String {
+        format!(
+            r#"{{"type":"event_msg","timestamp":"{}","payload":{{"type":"token_count","info":{{"last_token_usage":{{"input_tokens":{},"output_tokens":{},"cached_input_tokens":{}}}}}}}}}"#,
+            timestamp, input, output, cached
+        )
+    }
+
+    #[test]
+    fn test_parse_codex_file_resumes_with_state() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"session_meta","timestamp":"2025-06-15T12:00:00Z","payload":{{"id":"s1","cwd":"/home/dev/tokscale"}}}}"#
+        )
+        .unwrap();
+        writeln!(file, "{}", TURN_CONTEXT).unwrap();
+        writeln!(
+            file,
+            "{}",
+            last_usage_line("2025-06-15T12:00:01Z", 100, 10, 0)
+        )
+        .unwrap();
+
+        let (first, resume) = parse_codex_file_from(&path, &ResumeState::default()).unwrap();
+        assert_eq!(first.messages.len(), 1);
+        let state: CodexState = serde_json::from_value(resume.context.clone().unwrap()).unwrap();
+        assert_eq!(state.current_model.as_deref(), Some("gpt-5-codex"));
+
+        // The model from the earlier turn_context carries over to appended lines
+        writeln!(
+            file,
+            "{}",
+            last_usage_line("2025-06-15T12:00:03Z", 200, 10, 0)
+        )
+        .unwrap();
+
+        let (appended, _) = parse_codex_file_from(&path, &resume).unwrap();
+        let appended = appended.messages;
+        assert_eq!(appended.len(), 1);
+        assert_eq!(appended[0].model_id, "gpt-5-codex");
+        assert_eq!(appended[0].project.as_deref(), Some("/home/dev/tokscale"));
+        assert_eq!(appended[0].tokens.input, 200);
+        assert_eq!(appended[0].tokens.output, 10);
+    }
+
+    fn total_usage_line(
+        timestamp: &str,
+        input: i64,
+        output: i64,
+        cached: i64,
+        reasoning: i64,
+    ) -> String {
+        format!(
+            r#"{{"type":"event_msg","timestamp":"{}","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":{},"cached_input_tokens":{},"output_tokens":{},"reasoning_output_tokens":{},"total_tokens":{}}}}}}}}}"#,
+            timestamp,
+            input,
+            cached,
+            output,
+            reasoning,
+            input + output
+        )
+    }
+
+    fn write_session(lines: &[String]) -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, "{}", TURN_CONTEXT).unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        (dir, path)
+    }
+
+    #[test]
+    fn test_last_token_usage_splits_cached_and_reasoning() {
+        let line = r#"{"type":"event_msg","timestamp":"2025-06-15T12:00:01Z","payload":{"type":"token_count","info":{"last_token_usage":{"input_tokens":1000,"cached_input_tokens":800,"output_tokens":300,"reasoning_output_tokens":200,"total_tokens":1300},"total_token_usage":{"input_tokens":1000,"cached_input_tokens":800,"output_tokens":300,"reasoning_output_tokens":200,"total_tokens":1300}}}}"#;
+        let (_dir, path) = write_session(&[line.to_string()]);
+
+        let messages = parse_codex_file(&path).messages;
+        assert_eq!(messages.len(), 1);
+        let tokens = &messages[0].tokens;
+        assert_eq!(tokens.input, 200);
+        assert_eq!(tokens.cache_read, 800);
+        assert_eq!(tokens.output, 100);
+        assert_eq!(tokens.reasoning, 200);
+    }
+
+    #[test]
+    fn test_total_token_usage_deltas() {
+        let (_dir, path) = write_session(&[
+            total_usage_line("2025-06-15T12:00:01Z", 1000, 300, 800, 200),
+            // Repeated totals produce no message
+            total_usage_line("2025-06-15T12:00:02Z", 1000, 300, 800, 200),
+            total_usage_line("2025-06-15T12:00:03Z", 2500, 450, 1900, 250),
+        ]);
+
+        // The first cumulative event only sets the baseline
+        let messages = parse_codex_file(&path).messages;
+        assert_eq!(messages.len(), 1);
+
+        let second = &messages[0].tokens;
+        assert_eq!(
+            (
+                second.input,
+                second.cache_read,
+                second.output,
+                second.reasoning
+            ),
+            (400, 1100, 100, 50)
+        );
+    }
+
+    #[test]
+    fn test_parse_codex_chunks_matches_sequential_parse() {
+        let mut lines = vec![
+            r#"{"type":"session_meta","timestamp":"2025-06-15T12:00:00Z","payload":{"id":"s1","cwd":"/home/dev/tokscale"}}"#.to_string(),
+        ];
+        for turn in 0..20i64 {
+            let model = if turn < 10 { "gpt-5-codex" } else { "gpt-5" };
+            lines.push(format!(
+                r#"{{"type":"turn_context","timestamp":"2025-06-15T12:00:00Z","payload":{{"model":"{}"}}}}"#,
+                model
+            ));
+            lines.push(format!(
+                r#"{{"type":"response_item","timestamp":"2025-06-15T12:00:00Z","payload":{{"type":"message","content":"turn {}"}}}}"#,
+                turn
+            ));
+            let n = turn + 1;
+            lines.push(total_usage_line(
+                "2025-06-15T12:00:01Z",
+                1000 * n,
+                300 * n,
+                800 * n,
+                100 * n,
+            ));
+        }
+        let (_dir, path) = write_session(&lines);
+
+        let resume = ResumeState::default();
+        let (sequential, sequential_resume) = parse_codex_chunks(&path, &resume, u64::MAX).unwrap();
+        let (chunked, chunked_resume) = parse_codex_chunks(&path, &resume, 300).unwrap();
+
+        let summary = |parsed: &ParsedFile| -> Vec<(String, i64, Option<String>)> {
+            parsed
+                .messages
+                .iter()
+                .map(|m| (m.model_id.clone(), m.tokens.input, m.project.clone()))
+                .collect()
+        };
+        assert_eq!(chunked.messages.len(), 19);
+        assert_eq!(summary(&chunked), summary(&sequential));
+        assert_eq!(chunked.messages[0].tokens.input, 200);
+        assert_eq!(chunked.messages[18].model_id, "gpt-5");
+        assert_eq!(
+            chunked.messages[18].project.as_deref(),
+            Some("/home/dev/tokscale")
+        );
+        assert_eq!(chunked_resume.offset, sequential_resume.offset);
+        assert_eq!(chunked_resume.context, sequential_resume.context);
+    }
+}
@@ -0,0 +1,462 @@
+//! GitHub Copilot session parser
+//!
+//! Parses chat session JSON files from Copilot CLI (~/.copilot/history-session-state/)
+//! and VS Code Copilot Chat (User/workspaceStorage/*/chatSessions/)
+
+use super::diagnostics::{parse_json_bytes, read_json_file};
+use super::{ParsedFile, SessionSource, UnifiedMessage};
+use crate::TokenBreakdown;
+use serde::Deserialize;
+use std::path::Path;
+
+/// VS Code chat session (one file per chat panel session)
+#[derive(Debug, Deserialize)]
+pub struct CopilotChatSession {
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub requests: Vec<CopilotChatRequest>,
+}
+
+/// One prompt and its response in a VS Code chat session
+#[derive(Debug, Deserialize)]
+pub struct CopilotChatRequest {
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
+    /// Unix timestamp in milliseconds
+    pub timestamp: Option<i64>,
+    /// e.g. "copilot/claude-sonnet-4"
+    #[serde(rename = "modelId")]
+    pub model_id: Option<String>,
+    pub result: Option<CopilotChatResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CopilotChatResult {
+    pub metadata: Option<CopilotResultMetadata>,
+    pub usage: Option<CopilotChatUsage>,
+}
+
+/// Token counts recorded with the response by recent Copilot Chat versions
+#[derive(Debug, Deserialize)]
+pub struct CopilotResultMetadata {
+    #[serde(rename = "promptTokens")]
+    pub prompt_tokens: Option<i64>,
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CopilotChatUsage {
+    #[serde(rename = "promptTokens")]
+    pub prompt_tokens: Option<i64>,
+    #[serde(rename = "completionTokens")]
+    pub completion_tokens: Option<i64>,
+}
+
+/// Copilot CLI session history
+#[derive(Debug, Deserialize)]
+pub struct CopilotCliSession {
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+    #[serde(rename = "startTime")]
+    pub start_time: Option<String>,
+    /// Model selected for the session, used when a message names none
+    #[serde(rename = "selectedModel")]
+    pub selected_model: Option<String>,
+    #[serde(rename = "chatMessages", default)]
+    pub chat_messages: Vec<CopilotCliMessage>,
+}
+
+/// Chat completion message; assistant messages carry OpenAI-style usage
+#[derive(Debug, Deserialize)]
+pub struct CopilotCliMessage {
+    pub role: String,
+    pub model: Option<String>,
+    pub timestamp: Option<String>,
+    pub usage: Option<CopilotCliUsage>,
+}
+
+/// Token counts in OpenAI's overlapping schema (cached within prompt,
+/// reasoning within completion)
+#[derive(Debug, Deserialize)]
+pub struct CopilotCliUsage {
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub prompt_tokens_details: Option<CopilotPromptDetails>,
+    pub completion_tokens_details: Option<CopilotCompletionDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CopilotPromptDetails {
+    pub cached_tokens: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CopilotCompletionDetails {
+    pub reasoning_tokens: Option<i64>,
+}
+
+/// VS Code workspace descriptor next to the chatSessions directory
+#[derive(Debug, Deserialize)]
+struct VsCodeWorkspace {
+    folder: Option<String>,
+}
+
+/// Get provider from model name
+fn get_provider_from_model(model: &str) -> &'static str {
+    let model_lower = model.to_lowercase();
+    if model_lower.contains("claude") {
+        return "anthropic";
+    }
+    if model_lower.contains("gemini") {
+        return "google";
+    }
+    if model_lower.contains("grok") {
+        return "xai";
+    }
+    "openai" // Default for Copilot
+}
+
+/// VS Code prefixes model ids with the vendor ("copilot/gpt-4.1")
+fn normalize_model(model: &str) -> &str {
+    model.strip_prefix("copilot/").unwrap_or(model)
+}
+
+/// VS Code sessions live in `chatSessions/`; anything else is a CLI session
+fn is_chat_session(path: &Path) -> bool {
+    path.parent()
+        .and_then(|p| p.file_name())
+        .is_some_and(|name| name == "chatSessions")
+}
+
+fn session_id_from_path(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// Parse a Copilot CLI or VS Code chat session file
+pub fn parse_copilot_file(path: &Path) -> ParsedFile {
+    if is_chat_session(path) {
+        match read_json_file::<CopilotChatSession>(path) {
+            Ok(session) => chat_session_to_messages(path, session, workspace_folder(path)).into(),
+            Err(diagnostic) => ParsedFile::failed(diagnostic),
+        }
+    } else {
+        match read_json_file::<CopilotCliSession>(path) {
+            Ok(session) => cli_session_to_messages(path, session).into(),
+            Err(diagnostic) => ParsedFile::failed(diagnostic),
+        }
+    }
+}
+
+/// Parse a Copilot session read from an archive entry at `path`. The VS Code
+/// workspace folder is not available there, so messages have no project.
+pub fn parse_copilot_bytes(path: &Path, data: &[u8]) -> ParsedFile {
+    if is_chat_session(path) {
+        match parse_json_bytes::<CopilotChatSession>(data) {
+            Ok(session) => chat_session_to_messages(path, session, None).into(),
+            Err(diagnostic) => ParsedFile::failed(diagnostic),
+        }
+    } else {
+        match parse_json_bytes::<CopilotCliSession>(data) {
+            Ok(session) => cli_session_to_messages(path, session).into(),
+            Err(diagnostic) => ParsedFile::failed(diagnostic),
+        }
+    }
+}
+
+/// Folder of the workspace a chat session belongs to, from
+/// workspaceStorage/{hash}/workspace.json
+fn workspace_folder(path: &Path) -> Option<String> {
+    let workspace_json = path.parent()?.parent()?.join("workspace.json");
+    let workspace: VsCodeWorkspace = read_json_file(&workspace_json).ok()?;
+    let folder = workspace.folder?;
+    Some(
+        folder
+            .strip_prefix("file://")
+            .map(String::from)
+            .unwrap_or(folder),
+    )
+}
+
+fn chat_session_to_messages(
+    path: &Path,
+    session: CopilotChatSession,
+    project: Option<String>,
+) -> Vec<UnifiedMessage> {
+    let session_id = session
+        .session_id
+        .unwrap_or_else(|| session_id_from_path(path));
+
+    let mut messages = Vec::new();
+    for request in session.requests {
+        let (model, result) = match (request.model_id, request.result) {
+            (Some(m), Some(r)) => (m, r),
+            _ => continue,
+        };
+
+        let (input, output) = match (result.metadata, result.usage) {
+            (
+                Some(CopilotResultMetadata {
+                    prompt_tokens: Some(input),
+                    output_tokens,
+                }),
+                _,
+            ) => (input, output_tokens.unwrap_or(0)),
+            (_, Some(usage)) => (
+                usage.prompt_tokens.unwrap_or(0),
+                usage.completion_tokens.unwrap_or(0),
+            ),
+            _ => continue,
+        };
+
+        let timestamp = request.timestamp.unwrap_or(0);
+        if timestamp == 0 || input + output == 0 {
+            continue;
+        }
+
+        let model = normalize_model(&model);
+        messages.push(
+            UnifiedMessage::new(
+                "copilot",
+                model,
+                get_provider_from_model(model),
+                session_id.clone(),
+                timestamp,
+                TokenBreakdown {
+                    input,
+                    output,
+                    cache_read: 0,
+                    cache_write: 0,
+                    reasoning: 0,
+                },
+                0.0, // Cost calculated later
+            )
+            .with_project(project.clone())
+            .with_dedup_key(
+                request
+                    .request_id
+                    .map(|id| format!("{}:{}", session_id, id)),
+            ),
+        );
+    }
+
+    messages
+}
+
+fn cli_session_to_messages(path: &Path, session: CopilotCliSession) -> Vec<UnifiedMessage> {
+    let session_id = session
+        .session_id
+        .unwrap_or_else(|| session_id_from_path(path));
+    let start_time = session.start_time.as_deref().and_then(parse_timestamp);
+
+    let mut messages = Vec::new();
+    for msg in session.chat_messages {
+        if msg.role != "assistant" {
+            continue;
+        }
+
+        let usage = match msg.usage {
+            Some(u) => u,
+            None => continue,
+        };
+
+        let model = match msg.model.or_else(|| session.selected_model.clone()) {
+            Some(m) => m,
+            None => continue,
+        };
+
+        let timestamp = msg
+            .timestamp
+            .as_deref()
+            .and_then(parse_timestamp)
+            .or(start_time)
+            .unwrap_or(0);
+
+        if timestamp == 0 {
+            continue;
+        }
+
+        // Split OpenAI's overlapping counts into non-overlapping categories
+        let prompt = usage.prompt_tokens.unwrap_or(0).max(0);
+        let completion = usage.completion_tokens.unwrap_or(0).max(0);
+        let cached = usage
+            .prompt_tokens_details
+            .and_then(|d| d.cached_tokens)
+            .unwrap_or(0)
+            .clamp(0, prompt);
+        let reasoning = usage
+            .completion_tokens_details
+            .and_then(|d| d.reasoning_tokens)
+            .unwrap_or(0)
+            .clamp(0, completion);
+
+        let model = normalize_model(&model);
+        messages.push(UnifiedMessage::new(
+            "copilot",
+            model,
+            get_provider_from_model(model),
+            session_id.clone(),
+            timestamp,
+            TokenBreakdown {
+                input: prompt - cached,
+                output: completion - reasoning,
+                cache_read: cached,
+                cache_write: 0,
+                reasoning,
+            },
+            0.0, // Cost calculated later
+        ));
+    }
+
+    messages
+}
+
+fn parse_timestamp(ts: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|dt| dt.timestamp_millis())
+}
+
+/// VS Code user data directories (stable and Insiders on Linux, macOS and
+/// Windows, plus VS Code Server for remote sessions)
+const VSCODE_USER_DIRS: &[&str] = &[
+    ".config/Code/User",
+    ".config/Code - Insiders/User",
+    "Library/Application Support/Code/User",
+    "Library/Application Support/Code - Insiders/User",
+    "AppData/Roaming/Code/User",
+    "AppData/Roaming/Code - Insiders/User",
+    ".vscode-server/data/User",
+];
+
+/// GitHub Copilot CLI and VS Code Copilot Chat sessions
+pub struct CopilotSource;
+
+impl SessionSource for CopilotSource {
+    fn name(&self) -> &str {
+        "copilot"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.copilot/history-session-state/*.json
+        // <VS Code user dir>/workspaceStorage/*/chatSessions/*.json
+        let mut roots = vec![format!("{}/.copilot/history-session-state", home_dir)];
+        roots.extend(
+            VSCODE_USER_DIRS
+                .iter()
+                .map(|dir| format!("{}/{}/workspaceStorage", home_dir, dir)),
+        );
+        roots
+    }
+
+    fn pattern(&self) -> &'static str {
+        "*.json"
+    }
+
+    fn accepts(&self, path: &Path) -> bool {
+        // workspaceStorage also holds workspace.json and extension state
+        let in_workspace_storage = path
+            .components()
+            .any(|c| c.as_os_str() == "workspaceStorage");
+        !in_workspace_storage || is_chat_session(path)
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_copilot_file(path)
+    }
+
+    fn parse_bytes(&self, path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_copilot_bytes(path, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("__test__/fixtures")
+    }
+
+    #[test]
+    fn test_parse_vscode_chat_session_fixture() {
+        let path = fixtures().join(
+            ".config/Code/User/workspaceStorage/a1b2c3/chatSessions/2f9c7e1a-5d3b-4c1e-9a8f-0b6d4e2c1a7f.json",
+        );
+        let parsed = parse_copilot_file(&path);
+        assert!(parsed.diagnostics.is_empty());
+
+        // The request without a result is skipped
+        let messages = parsed.messages;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].model_id, "claude-sonnet-4");
+        assert_eq!(messages[0].provider_id, "anthropic");
+        assert_eq!(messages[0].tokens.input, 12840);
+        assert_eq!(messages[0].tokens.output, 412);
+        assert_eq!(
+            messages[0].session_id,
+            "2f9c7e1a-5d3b-4c1e-9a8f-0b6d4e2c1a7f"
+        );
+        assert_eq!(messages[0].project.as_deref(), Some("/home/dev/tokscale"));
+        assert!(messages[0].dedup_key.is_some());
+
+        // Older sessions report usage instead of result metadata
+        assert_eq!(messages[1].model_id, "gpt-4.1");
+        assert_eq!(messages[1].provider_id, "openai");
+        assert_eq!(messages[1].tokens.input, 3100);
+        assert_eq!(messages[1].tokens.output, 250);
+    }
+
+    #[test]
+    fn test_parse_copilot_cli_session_fixture() {
+        let path = fixtures().join(
+            ".copilot/history-session-state/session_7d1f0c2e-8b4a-4f6d-a3e5-91c2b7d8e0f4.json",
+        );
+        let parsed = parse_copilot_file(&path);
+        assert!(parsed.diagnostics.is_empty());
+
+        let messages = parsed.messages;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            messages[0].session_id,
+            "7d1f0c2e-8b4a-4f6d-a3e5-91c2b7d8e0f4"
+        );
+        assert_eq!(messages[0].model_id, "claude-sonnet-4.5");
+        assert_eq!(messages[0].tokens.input, 1500);
+        assert_eq!(messages[0].tokens.cache_read, 8500);
+        assert_eq!(messages[0].tokens.output, 300);
+
+        // Falls back to the session's model and start time
+        assert_eq!(messages[1].model_id, "gpt-5");
+        assert_eq!(messages[1].tokens.output, 80);
+        assert_eq!(messages[1].tokens.reasoning, 120);
+        assert_eq!(
+            messages[1].timestamp,
+            parse_timestamp("2025-09-30T08:00:00Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_accepts_only_chat_sessions_in_workspace_storage() {
+        let root = "/home/dev/.config/Code/User/workspaceStorage/a1b2c3";
+        assert!(CopilotSource.accepts(Path::new(&format!("{}/chatSessions/s.json", root))));
+        assert!(!CopilotSource.accepts(Path::new(&format!("{}/workspace.json", root))));
+        assert!(CopilotSource.accepts(Path::new(
+            "/home/dev/.copilot/history-session-state/session_1.json"
+        )));
+    }
+
+    #[test]
+    fn test_normalize_model() {
+        assert_eq!(normalize_model("copilot/gpt-4.1"), "gpt-4.1");
+        assert_eq!(normalize_model("gpt-5"), "gpt-5");
+        assert_eq!(get_provider_from_model("claude-sonnet-4"), "anthropic");
+        assert_eq!(get_provider_from_model("gemini-2.5-pro"), "google");
+        assert_eq!(get_provider_from_model("o4-mini"), "openai");
+    }
+}
@@ -6,7 +6,7 @@
 //! CSV Format (actual from API):
 //! Date,Kind,Model,Max Mode,Input (w/ Cache Write),Input (w/o Cache Write),Cache Read,Output Tokens,Total Tokens,Cost
 
-use super::UnifiedMessage;
+use super::{Diagnostic, ParsedFile, SessionSource, UnifiedMessage};
 use crate::TokenBreakdown;
 use std::path::Path;
 
@@ -52,24 +52,38 @@ fn parse_cost(cost_str: &str) -> f64 {
 /// Handles both formats:
 /// - New: Date,Kind,Model,Max Mode,Input (w/ Cache Write),Input (w/o Cache Write),Cache Read,Output Tokens,Total Tokens,Cost
 /// - Old: Date,Model,Input (w/ Cache Write),Input (w/o Cache Write),Cache Read,Output Tokens,Total Tokens,Cost,Cost to you
-pub fn parse_cursor_file(path: &Path) -> Vec<UnifiedMessage> {
+pub fn parse_cursor_file(path: &Path) -> ParsedFile {
     let content = match std::fs::read_to_string(path) {
         Ok(c) => c,
-        Err(_) => return vec![],
+        Err(e) => return ParsedFile::failed(Diagnostic::io(e)),
     };
+    parse_cursor_csv(&content)
+}
+
+/// Parse Cursor usage CSV data read from an archive entry
+pub fn parse_cursor_bytes(data: &[u8]) -> ParsedFile {
+    match std::str::from_utf8(data) {
+        Ok(content) => parse_cursor_csv(content),
+        Err(e) => ParsedFile::failed(Diagnostic::io(e)),
+    }
+}
 
+fn parse_cursor_csv(content: &str) -> ParsedFile {
     let mut messages = Vec::new();
     let mut lines = content.lines();
 
     // Parse header line to determine column indices
     let header = match lines.next() {
         Some(h) => h,
-        None => return vec![],
+        None => return ParsedFile::default(),
     };
 
     // Verify this is a valid Cursor CSV
     if !header.contains("Date") || !header.contains("Model") {
-        return vec![];
+        return ParsedFile::failed(Diagnostic::unknown_schema(
+            "CSV header has no Date or Model column",
+            None,
+        ));
     }
 
     // Detect format by checking for "Kind" column
@@ -92,7 +106,11 @@ pub fn parse_cursor_file(path: &Path) -> Vec<UnifiedMessage> {
         (1, 2, 3, 4, 5, 7)
     };
 
-    for line in lines {
+    let mut skipped_lines = 0;
+    let mut first_skipped_line = None;
+
+    // Line numbers are 1-based and the header is line 1
+    for (line_number, line) in (2u64..).zip(lines) {
         if line.trim().is_empty() {
             continue;
         }
@@ -103,6 +121,8 @@ pub fn parse_cursor_file(path: &Path) -> Vec<UnifiedMessage> {
         // Need at least enough columns for the format
         let min_fields = cost_idx + 1;
         if fields.len() < min_fields {
+            skipped_lines += 1;
+            first_skipped_line.get_or_insert(line_number);
             continue;
         }
 
@@ -164,7 +184,14 @@ pub fn parse_cursor_file(path: &Path) -> Vec<UnifiedMessage> {
         ));
     }
 
-    messages
+    let mut parsed = ParsedFile::from(messages);
+    if skipped_lines > 0 {
+        parsed.diagnostics.push(Diagnostic::malformed_lines(
+            skipped_lines,
+            first_skipped_line,
+        ));
+    }
+    parsed
 }
 
 /// Simple CSV line parser that handles quoted fields
@@ -226,9 +253,40 @@ fn parse_date_to_timestamp(date_str: &str) -> i64 {
     0
 }
 
+/// Cursor usage export, synced from the Cursor API by the CLI
+pub struct CursorSource;
+
+impl SessionSource for CursorSource {
+    fn name(&self) -> &str {
+        "cursor"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.config/tokscale/cursor-cache/*.csv (migrated from ~/.tokscale)
+        vec![format!("{}/.config/tokscale/cursor-cache", home_dir)]
+    }
+
+    fn pattern(&self) -> &'static str {
+        "*.csv"
+    }
+
+    fn is_local(&self) -> bool {
+        false
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_cursor_file(path)
+    }
+
+    fn parse_bytes(&self, _path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_cursor_bytes(data))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sessions::diagnostics::DiagnosticKind;
 
     #[test]
     fn test_infer_provider() {
@@ -290,7 +348,7 @@ mod tests {
         let file_path = temp_dir.path().join("usage.csv");
         std::fs::write(&file_path, csv).unwrap();
 
-        let messages = parse_cursor_file(&file_path);
+        let messages = parse_cursor_file(&file_path).messages;
         assert_eq!(messages.len(), 2);
 
         assert_eq!(messages[0].source, "cursor");
@@ -315,7 +373,7 @@ mod tests {
         let file_path = temp_dir.path().join("usage.csv");
         std::fs::write(&file_path, csv).unwrap();
 
-        let messages = parse_cursor_file(&file_path);
+        let messages = parse_cursor_file(&file_path).messages;
         assert_eq!(messages.len(), 2);
 
         // First message: auto model
@@ -334,4 +392,29 @@ mod tests {
         assert_eq!(messages[1].tokens.input, 8263);
         assert_eq!(messages[1].tokens.cache_read, 66964);
     }
+
+    #[test]
+    fn test_parse_cursor_csv_reports_diagnostics() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let truncated = temp_dir.path().join("truncated.csv");
+        let csv = r#"Date,Model,Input (w/ Cache Write),Input (w/o Cache Write),Cache Read,Output Tokens,Total Tokens,Cost,Cost to you
+"2025-11-13T18:36:05.846Z","gpt-4o","10","5","0","15","30","0.10","0.10"
+"2025-11-13T18:37:05.846Z","gpt-4o","10""#;
+        std::fs::write(&truncated, csv).unwrap();
+
+        let parsed = parse_cursor_file(&truncated);
+        assert_eq!(parsed.messages.len(), 1);
+        assert_eq!(
+            parsed.diagnostics,
+            vec![Diagnostic::malformed_lines(1, Some(3))]
+        );
+
+        let renamed = temp_dir.path().join("renamed.csv");
+        std::fs::write(&renamed, "Timestamp,Engine,Tokens\n").unwrap();
+
+        let parsed = parse_cursor_file(&renamed);
+        assert!(parsed.messages.is_empty());
+        assert_eq!(parsed.diagnostics[0].kind, DiagnosticKind::UnknownSchema);
+    }
 }
@@ -0,0 +1,591 @@
+//! User-defined JSONL sources
+//!
+//! Tools without a built-in parser can be declared in
+//! `~/.config/tokscale/sources.toml` (or `sources.json`) by mapping JSON
+//! pointers to message fields:
+//!
+//! ```toml
+//! [[sources]]
+//! name = "acme-agent"
+//! root = "~/.acme/sessions"
+//! glob = "*.jsonl"
+//! model = "/message/model"
+//! timestamp = "/timestamp"
+//! # Optional; the file name is used when left out
+//! session_id = "/sessionId"
+//! provider = "/message/provider"
+//!
+//! [sources.tokens]
+//! input = "/message/usage/input_tokens"
+//! output = "/message/usage/output_tokens"
+//! cache_read = "/message/usage/cache_read_tokens"
+//!
+//! # Only lines where the value at `pointer` equals `equals`
+//! [sources.filter]
+//! pointer = "/type"
+//! equals = "assistant"
+//! ```
+//!
+//! Every line is one JSON object. Lines without a model or timestamp are
+//! skipped, token fields left out count as 0, and timestamps may be RFC 3339
+//! strings or Unix times in seconds or milliseconds. Files are parsed like
+//! the built-in JSONL sources: in parallel chunks, resuming from the last
+//! offset through the parse cache.
+//!
+//! The file is loaded into each scan's `SourceRegistry`; a file that cannot
+//! be loaded is reported as a `config` diagnostic and only the built-in
+//! sources are scanned.
+
+use super::diagnostics::jsonl_diagnostics;
+use super::{Diagnostic, ParsedFile, ResumeState, SessionSource, UnifiedMessage};
+use crate::parser::{
+    fingerprint_before, read_jsonl_bytes, read_jsonl_range, split_jsonl_chunks, JsonlProgress, LineFilter, ParseError,
+    PARALLEL_CHUNK_SIZE,
+};
+use crate::TokenBreakdown;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Error loading user-defined sources
+#[derive(Debug, thiserror::Error)]
+pub enum CustomSourceError {
+    #[error("Failed to read sources file {path}: {message}")]
+    Io { path: String, message: String },
+
+    #[error("Invalid sources TOML: {0}")]
+    Toml(String),
+
+    #[error("Invalid sources JSON: {0}")]
+    Json(String),
+
+    #[error("Invalid source name {0:?}: expected lowercase letters, digits, '-' or '_', not a built-in source")]
+    InvalidName(String),
+
+    #[error("Source {0:?} is defined more than once")]
+    Duplicate(String),
+
+    #[error("Invalid JSON pointer {pointer:?} for source {source_name}: expected \"\" or a path starting with '/'")]
+    InvalidPointer {
+        source_name: String,
+        pointer: String,
+    },
+
+    #[error("Invalid file glob {glob:?} for source {source_name}: {message}")]
+    InvalidGlob {
+        source_name: String,
+        glob: String,
+        message: String,
+    },
+}
+
+/// Contents of a sources file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomSourcesConfig {
+    #[serde(default)]
+    pub sources: Vec<CustomSourceDef>,
+}
+
+/// A user-defined source: where its files are and where each field sits in a line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomSourceDef {
+    /// Source name used in options and results
+    pub name: String,
+    /// Directory to scan; `~` is the home directory, relative paths are
+    /// relative to it
+    pub root: String,
+    /// File name glob (e.g. "*.jsonl")
+    pub glob: String,
+    pub model: String,
+    pub timestamp: String,
+    pub session_id: Option<String>,
+    pub provider: Option<String>,
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tokens: TokenPointers,
+    pub filter: Option<LineMatch>,
+}
+
+/// JSON pointers to the token counts of a line
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenPointers {
+    pub input: Option<String>,
+    pub output: Option<String>,
+    pub cache_read: Option<String>,
+    pub cache_write: Option<String>,
+    pub reasoning: Option<String>,
+}
+
+/// Keep only lines whose value at `pointer` equals `equals`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LineMatch {
+    pub pointer: String,
+    pub equals: Value,
+}
+
+impl CustomSourcesConfig {
+    /// Read a sources file: `.json` files are parsed as JSON, anything else as TOML
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, CustomSourceError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| CustomSourceError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json_str(&content)
+        } else {
+            Self::from_toml_str(&content)
+        }
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self, CustomSourceError> {
+        toml::from_str(content).map_err(|e| CustomSourceError::Toml(e.to_string()))
+    }
+
+    pub fn from_json_str(content: &str) -> Result<Self, CustomSourceError> {
+        serde_json::from_str(content).map_err(|e| CustomSourceError::Json(e.to_string()))
+    }
+
+    /// `~/.config/tokscale/sources.toml` or `sources.json`, whichever exists
+    pub fn default_path(home_dir: impl AsRef<Path>) -> Option<PathBuf> {
+        let dir = home_dir.as_ref().join(".config").join("tokscale");
+        ["sources.toml", "sources.json"]
+            .into_iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+}
+
+/// A compiled user-defined source
+#[derive(Debug)]
+pub struct CustomSource {
+    def: CustomSourceDef,
+    glob: globset::GlobMatcher,
+    /// Hash of the definition, so cached results from an older mapping are discarded
+    version: u64,
+}
+
+impl CustomSource {
+    pub fn new(def: CustomSourceDef) -> Result<Self, CustomSourceError> {
+        let valid_name = !def.name.is_empty()
+            && def
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            && !super::all_sources().iter().any(|s| s.name() == def.name);
+        if !valid_name {
+            return Err(CustomSourceError::InvalidName(def.name));
+        }
+
+        for pointer in def.pointers() {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                return Err(CustomSourceError::InvalidPointer {
+                    source_name: def.name.clone(),
+                    pointer: pointer.to_string(),
+                });
+            }
+        }
+
+        let glob = globset::Glob::new(&def.glob)
+            .map_err(|e| CustomSourceError::InvalidGlob {
+                source_name: def.name.clone(),
+                glob: def.glob.clone(),
+                message: e.to_string(),
+            })?
+            .compile_matcher();
+
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(&def)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+
+        Ok(Self {
+            def,
+            glob,
+            version: hasher.finish(),
+        })
+    }
+
+    /// Convert one line, if it passes the filter and has a model and timestamp
+    fn line_to_message(&self, line: &Value, fallback_session_id: &str) -> Option<UnifiedMessage> {
+        let def = &self.def;
+        if let Some(filter) = &def.filter {
+            if line.pointer(&filter.pointer) != Some(&filter.equals) {
+                return None;
+            }
+        }
+
+        let model = value_to_string(line.pointer(&def.model)?)?;
+        let timestamp = value_to_timestamp(line.pointer(&def.timestamp)?)?;
+        let field = |pointer: &Option<String>| {
+            pointer
+                .as_deref()
+                .and_then(|p| line.pointer(p))
+                .and_then(value_to_string)
+        };
+        let count = |pointer: &Option<String>| {
+            pointer
+                .as_deref()
+                .and_then(|p| line.pointer(p))
+                .and_then(value_to_count)
+                .unwrap_or(0)
+        };
+
+        Some(
+            UnifiedMessage::new(
+                def.name.as_str(),
+                model,
+                field(&def.provider).unwrap_or_else(|| "unknown".to_string()),
+                field(&def.session_id).unwrap_or_else(|| fallback_session_id.to_string()),
+                timestamp,
+                TokenBreakdown {
+                    input: count(&def.tokens.input),
+                    output: count(&def.tokens.output),
+                    cache_read: count(&def.tokens.cache_read),
+                    cache_write: count(&def.tokens.cache_write),
+                    reasoning: count(&def.tokens.reasoning),
+                },
+                0.0, // Cost calculated later
+            )
+            .with_project(field(&def.project)),
+        )
+    }
+
+    /// Parse a line's bytes, returning false if it is not valid JSON
+    fn parse_line(
+        &self,
+        line: &mut [u8],
+        session_id: &str,
+        messages: &mut Vec<UnifiedMessage>,
+    ) -> bool {
+        let value: Value = match simd_json::from_slice(line) {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+        if let Some(msg) = self.line_to_message(&value, session_id) {
+            messages.push(msg);
+        }
+        true
+    }
+
+    fn parse_chunks(
+        &self,
+        path: &Path,
+        resume: &ResumeState,
+    ) -> Result<(ParsedFile, ResumeState), Diagnostic> {
+        let session_id = session_id_from_path(path);
+        let chunks =
+            split_jsonl_chunks(path, resume.offset, PARALLEL_CHUNK_SIZE).map_err(Diagnostic::io)?;
+        let results: Vec<Result<(Vec<UnifiedMessage>, JsonlProgress), ParseError>> = chunks
+            .into_par_iter()
+            .map(|range| {
+                let mut messages = Vec::new();
+                let progress = read_jsonl_range(path, range, LineFilter::ALL, |line| {
+                    self.parse_line(line, &session_id, &mut messages)
+                })?;
+                Ok((messages, progress))
+            })
+            .collect();
+
+        let mut messages = Vec::new();
+        let mut progress = JsonlProgress {
+            offset: resume.offset,
+            ..Default::default()
+        };
+        for result in results {
+            let (chunk_messages, chunk_progress) = result.map_err(Diagnostic::io)?;
+            messages.extend(chunk_messages);
+            progress.append(chunk_progress);
+        }
+
+        let parsed = ParsedFile {
+            messages,
+            diagnostics: jsonl_diagnostics(&progress, resume.lines),
+        };
+        let next = ResumeState {
+            offset: progress.offset,
+            lines: resume.lines + progress.lines,
+            context: None,
+            fingerprint: fingerprint_before(path, progress.offset),
+        };
+        Ok((parsed, next))
+    }
+}
+
+impl CustomSourceDef {
+    fn pointers(&self) -> impl Iterator<Item = &str> {
+        let optional = [
+            &self.session_id,
+            &self.provider,
+            &self.project,
+            &self.tokens.input,
+            &self.tokens.output,
+            &self.tokens.cache_read,
+            &self.tokens.cache_write,
+            &self.tokens.reasoning,
+        ];
+        [self.model.as_str(), self.timestamp.as_str()]
+            .into_iter()
+            .chain(optional.into_iter().filter_map(|p| p.as_deref()))
+            .chain(self.filter.as_ref().map(|f| f.pointer.as_str()))
+    }
+}
+
+impl SessionSource for CustomSource {
+    fn name(&self) -> &str {
+        &self.def.name
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        let root = self.def.root.as_str();
+        let root = if root == "~" {
+            home_dir.to_string()
+        } else if let Some(rest) = root.strip_prefix("~/") {
+            format!("{}/{}", home_dir, rest)
+        } else if Path::new(root).is_absolute() {
+            root.to_string()
+        } else {
+            format!("{}/{}", home_dir, root)
+        };
+        vec![root]
+    }
+
+    fn pattern(&self) -> &'static str {
+        "*"
+    }
+
+    fn accepts(&self, path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| self.glob.is_match(Path::new(name)))
+    }
+
+    fn parser_version(&self) -> u64 {
+        self.version
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        match self.parse_chunks(path, &ResumeState::default()) {
+            Ok((parsed, _)) => parsed,
+            Err(diagnostic) => ParsedFile::failed(diagnostic),
+        }
+    }
+
+    fn parse_file_from(
+        &self,
+        path: &Path,
+        resume: &ResumeState,
+    ) -> Option<(ParsedFile, ResumeState)> {
+        self.parse_chunks(path, resume).ok()
+    }
+
+    fn parse_bytes(&self, path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        let session_id = session_id_from_path(path);
+        let mut messages = Vec::new();
+        let result = read_jsonl_bytes(data, LineFilter::ALL, |line| {
+            self.parse_line(line, &session_id, &mut messages)
+        });
+        Some(match result {
+            Ok(progress) => ParsedFile {
+                messages,
+                diagnostics: jsonl_diagnostics(&progress, 0),
+            },
+            Err(error) => ParsedFile::failed(Diagnostic::io(error)),
+        })
+    }
+}
+
+fn session_id_from_path(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn value_to_count(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// RFC 3339 strings, or Unix times in seconds or milliseconds
+fn value_to_timestamp(value: &Value) -> Option<i64> {
+    let timestamp = match value {
+        Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.timestamp_millis())
+            .or_else(|| s.parse::<f64>().ok().map(unix_millis)),
+        Value::Number(n) => n.as_f64().map(unix_millis),
+        _ => None,
+    }?;
+    (timestamp > 0).then_some(timestamp)
+}
+
+fn unix_millis(time: f64) -> i64 {
+    // Unix times below 1e11 are seconds (before 1973 in milliseconds)
+    if time < 1e11 {
+        (time * 1000.0) as i64
+    } else {
+        time as i64
+    }
+}
+
+/// Compile the sources of `config`, in the order they are defined
+pub fn compile(
+    config: &CustomSourcesConfig,
+) -> Result<Vec<Arc<dyn SessionSource>>, CustomSourceError> {
+    let mut sources: Vec<Arc<dyn SessionSource>> = Vec::with_capacity(config.sources.len());
+    for def in &config.sources {
+        if sources.iter().any(|s| s.name() == def.name) {
+            return Err(CustomSourceError::Duplicate(def.name.clone()));
+        }
+        sources.push(Arc::new(CustomSource::new(def.clone())?));
+    }
+    Ok(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const CONFIG: &str = r#"
+        [[sources]]
+        name = "acme-agent"
+        root = "~/.acme/sessions"
+        glob = "run-*.jsonl"
+        model = "/message/model"
+        timestamp = "/ts"
+        session_id = "/session"
+        provider = "/message/provider"
+
+        [sources.tokens]
+        input = "/message/usage/in"
+        output = "/message/usage/out"
+        cache_read = "/message/usage/cached"
+
+        [sources.filter]
+        pointer = "/type"
+        equals = "assistant"
+    "#;
+
+    fn acme() -> CustomSource {
+        let config = CustomSourcesConfig::from_toml_str(CONFIG).unwrap();
+        CustomSource::new(config.sources[0].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_custom_source_parses_mapped_fields() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("run-1.jsonl");
+        let mut file = std::fs::File::create(&path).unwrap();
+        for line in [
+            r#"{"type":"user","ts":"2025-06-15T12:00:00Z","message":{"model":"acme-1"}}"#,
+            r#"{"type":"assistant","ts":"2025-06-15T12:00:01Z","session":"s-1","message":{"model":"acme-1","provider":"acme","usage":{"in":100,"out":20,"cached":"5"}}}"#,
+            r#"{"type":"assistant","ts":1750000000,"message":{"model":"acme-2","usage":{"in":7}}}"#,
+            r#"{"type":"assistant","ts":1750000000000,"message":{"usage":{"in":1}}}"#,
+            "not json",
+        ] {
+            writeln!(file, "{}", line).unwrap();
+        }
+
+        let source = acme();
+        let parsed = source.parse_file(&path);
+        assert_eq!(parsed.messages.len(), 2);
+        assert_eq!(parsed.diagnostics.len(), 1);
+
+        let first = &parsed.messages[0];
+        assert_eq!(first.source, "acme-agent");
+        assert_eq!(first.model_id, "acme-1");
+        assert_eq!(first.provider_id, "acme");
+        assert_eq!(first.session_id, "s-1");
+        assert_eq!(first.tokens.input, 100);
+        assert_eq!(first.tokens.cache_read, 5);
+
+        // Seconds are converted; the file name stands in for a missing session id
+        let second = &parsed.messages[1];
+        assert_eq!(second.timestamp, 1_750_000_000_000);
+        assert_eq!(second.session_id, "run-1");
+        assert_eq!(second.provider_id, "unknown");
+        assert_eq!(second.tokens.output, 0);
+    }
+
+    #[test]
+    fn test_custom_source_layout() {
+        let source = acme();
+        assert_eq!(source.roots("/home/dev"), vec!["/home/dev/.acme/sessions"]);
+        assert!(source.accepts(Path::new("/home/dev/.acme/sessions/a/run-2.jsonl")));
+        assert!(!source.accepts(Path::new("/home/dev/.acme/sessions/notes.jsonl")));
+    }
+
+    #[test]
+    fn test_custom_source_validation() {
+        let def = CustomSourcesConfig::from_toml_str(CONFIG).unwrap().sources[0].clone();
+
+        let builtin = CustomSourceDef {
+            name: "claude".into(),
+            ..def.clone()
+        };
+        assert!(matches!(
+            CustomSource::new(builtin),
+            Err(CustomSourceError::InvalidName(_))
+        ));
+
+        let pointer = CustomSourceDef {
+            model: "message.model".into(),
+            ..def.clone()
+        };
+        assert!(matches!(
+            CustomSource::new(pointer),
+            Err(CustomSourceError::InvalidPointer { .. })
+        ));
+
+        let glob = CustomSourceDef {
+            glob: "run-[.jsonl".into(),
+            ..def
+        };
+        assert!(matches!(
+            CustomSource::new(glob),
+            Err(CustomSourceError::InvalidGlob { .. })
+        ));
+
+        assert!(CustomSourcesConfig::from_toml_str("[[sources]]\nname = \"x\"").is_err());
+
+        let mut twice = CustomSourcesConfig::from_toml_str(CONFIG).unwrap();
+        twice.sources.push(twice.sources[0].clone());
+        assert!(matches!(
+            compile(&twice),
+            Err(CustomSourceError::Duplicate(_))
+        ));
+    }
+
+    #[test]
+    fn test_custom_source_version_follows_definition() {
+        let def = CustomSourcesConfig::from_toml_str(CONFIG).unwrap().sources[0].clone();
+        let changed = CustomSourceDef {
+            model: "/model".into(),
+            ..def.clone()
+        };
+        assert_eq!(
+            CustomSource::new(def.clone()).unwrap().version,
+            CustomSource::new(def).unwrap().version
+        );
+        assert_ne!(acme().version, CustomSource::new(changed).unwrap().version);
+    }
+}
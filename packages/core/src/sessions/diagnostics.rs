@@ -0,0 +1,241 @@
+//! Per-file parse diagnostics
+//!
+//! Parsers record why a file produced fewer messages than it should have
+//! (unreadable file, invalid JSON, malformed lines, unrecognised schema)
+//! instead of silently returning nothing, so callers can warn when a tool
+//! changes its log format.
+
+use super::UnifiedMessage;
+use crate::parser::JsonlProgress;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// What went wrong while parsing a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// The file could not be read
+    Io,
+    /// The file is not valid JSON
+    Json,
+    /// Some lines of a line-based file could not be parsed and were skipped
+    MalformedLines,
+    /// The file is valid JSON (or CSV) but does not match the expected structure
+    UnknownSchema,
+    /// The user-defined sources file could not be loaded
+    Config,
+}
+
+impl DiagnosticKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Io => "io",
+            Self::Json => "json",
+            Self::MalformedLines => "malformed_lines",
+            Self::UnknownSchema => "unknown_schema",
+            Self::Config => "config",
+        }
+    }
+}
+
+/// A problem found while parsing one session file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub message: String,
+    /// 1-based line number of the first bad line (line-based formats only)
+    pub line: Option<u64>,
+    /// Number of lines skipped (line-based formats only)
+    pub skipped_lines: u64,
+    /// Version marker found in a file with an unrecognised structure
+    pub schema_version: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn io(error: impl std::fmt::Display) -> Self {
+        Self::new(DiagnosticKind::Io, error.to_string())
+    }
+
+    pub fn unknown_schema(message: impl Into<String>, schema_version: Option<String>) -> Self {
+        Self {
+            schema_version,
+            ..Self::new(DiagnosticKind::UnknownSchema, message.into())
+        }
+    }
+
+    pub fn config(error: impl std::fmt::Display) -> Self {
+        Self::new(DiagnosticKind::Config, error.to_string())
+    }
+
+    pub fn malformed_lines(skipped_lines: u64, first_line: Option<u64>) -> Self {
+        Self {
+            line: first_line,
+            skipped_lines,
+            ..Self::new(
+                DiagnosticKind::MalformedLines,
+                format!("{} malformed line(s) skipped", skipped_lines),
+            )
+        }
+    }
+
+    fn new(kind: DiagnosticKind, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            line: None,
+            skipped_lines: 0,
+            schema_version: None,
+        }
+    }
+}
+
+/// Messages parsed from one file, with any problems found along the way
+#[derive(Debug, Clone, Default)]
+pub struct ParsedFile {
+    pub messages: Vec<UnifiedMessage>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ParsedFile {
+    /// A file that yielded nothing because of `diagnostic`
+    pub fn failed(diagnostic: Diagnostic) -> Self {
+        Self {
+            messages: Vec::new(),
+            diagnostics: vec![diagnostic],
+        }
+    }
+
+    /// Append the result of parsing more of the same file
+    pub fn extend(&mut self, other: ParsedFile) {
+        self.messages.extend(other.messages);
+        merge_diagnostics(&mut self.diagnostics, other.diagnostics);
+    }
+}
+
+impl From<Vec<UnifiedMessage>> for ParsedFile {
+    fn from(messages: Vec<UnifiedMessage>) -> Self {
+        Self {
+            messages,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+/// Merge diagnostics from a later part of the same file. Malformed line
+/// counts are summed so a file reports a single record for them.
+pub fn merge_diagnostics(into: &mut Vec<Diagnostic>, other: Vec<Diagnostic>) {
+    for diagnostic in other {
+        if diagnostic.kind == DiagnosticKind::MalformedLines {
+            if let Some(existing) = into
+                .iter_mut()
+                .find(|d| d.kind == DiagnosticKind::MalformedLines)
+            {
+                let skipped = existing.skipped_lines + diagnostic.skipped_lines;
+                let line = existing.line.or(diagnostic.line);
+                *existing = Diagnostic::malformed_lines(skipped, line);
+                continue;
+            }
+        }
+        if !into.contains(&diagnostic) {
+            into.push(diagnostic);
+        }
+    }
+}
+
+/// Diagnostics for a JSONL read that started after `lines_before` lines
+pub fn jsonl_diagnostics(progress: &JsonlProgress, lines_before: u64) -> Vec<Diagnostic> {
+    if progress.skipped_lines == 0 {
+        return Vec::new();
+    }
+    let first_line = progress.first_skipped_line.map(|line| line + lines_before);
+    vec![Diagnostic::malformed_lines(
+        progress.skipped_lines,
+        first_line,
+    )]
+}
+
+/// Read and deserialize a JSON file, describing any failure as a diagnostic
+pub fn read_json_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, Diagnostic> {
+    let mut data = std::fs::read(path).map_err(Diagnostic::io)?;
+
+    match simd_json::from_slice(&mut data) {
+        Ok(value) => Ok(value),
+        // simd-json parses in place, so re-read the file to classify the error
+        Err(error) => Err(match std::fs::read(path) {
+            Ok(original) => classify_json_error(&original, error),
+            Err(io_error) => Diagnostic::io(io_error),
+        }),
+    }
+}
+
+/// Deserialize JSON data already in memory (e.g. an archive entry)
+pub fn parse_json_bytes<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, Diagnostic> {
+    let mut copy = data.to_vec();
+    simd_json::from_slice(&mut copy).map_err(|error| classify_json_error(data, error))
+}
+
+/// Tell invalid JSON apart from valid JSON with an unexpected structure
+fn classify_json_error(data: &[u8], error: impl std::fmt::Display) -> Diagnostic {
+    let value: serde_json::Value = match serde_json::from_slice(data) {
+        Ok(value) => value,
+        Err(_) => return Diagnostic::new(DiagnosticKind::Json, error.to_string()),
+    };
+
+    let schema_version = ["version", "schemaVersion", "v"]
+        .iter()
+        .find_map(|key| value.get(key))
+        .and_then(|version| match version {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        });
+
+    Diagnostic::unknown_schema(error.to_string(), schema_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Session {
+        id: String,
+    }
+
+    #[test]
+    fn test_read_json_file_classifies_errors() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let missing = read_json_file::<Session>(&dir.path().join("missing.json")).unwrap_err();
+        assert_eq!(missing.kind, DiagnosticKind::Io);
+
+        let invalid = dir.path().join("invalid.json");
+        std::fs::write(&invalid, "{not json").unwrap();
+        let err = read_json_file::<Session>(&invalid).unwrap_err();
+        assert_eq!(err.kind, DiagnosticKind::Json);
+
+        let renamed = dir.path().join("renamed.json");
+        std::fs::write(&renamed, r#"{"sessionId":"s1","version":3}"#).unwrap();
+        let err = read_json_file::<Session>(&renamed).unwrap_err();
+        assert_eq!(err.kind, DiagnosticKind::UnknownSchema);
+        assert_eq!(err.schema_version.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn test_merge_diagnostics_sums_malformed_lines() {
+        let mut diagnostics = vec![Diagnostic::malformed_lines(2, Some(4))];
+        merge_diagnostics(
+            &mut diagnostics,
+            vec![
+                Diagnostic::malformed_lines(1, Some(10)),
+                Diagnostic::unknown_schema("unexpected header", None),
+            ],
+        );
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].skipped_lines, 3);
+        assert_eq!(diagnostics[0].line, Some(4));
+        assert_eq!(diagnostics[1].kind, DiagnosticKind::UnknownSchema);
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Parses JSON files from ~/.factory/sessions/
 
-use super::UnifiedMessage;
+use super::diagnostics::read_json_file;
+use super::{ParsedFile, SessionSource, UnifiedMessage};
 use crate::TokenBreakdown;
 use serde::Deserialize;
 use std::io::{BufRead, BufReader};
@@ -154,18 +155,14 @@ fn extract_model_from_jsonl(jsonl_path: &Path) -> Option<String> {
 }
 
 /// Parse a Droid settings.json file
-pub fn parse_droid_file(path: &Path) -> Vec<UnifiedMessage> {
-    let data = match std::fs::read(path) {
-        Ok(d) => d,
-        Err(_) => return Vec::new(),
-    };
-
-    let mut bytes = data;
-    let settings: DroidSettingsJson = match simd_json::from_slice(&mut bytes) {
-        Ok(s) => s,
-        Err(_) => return Vec::new(),
-    };
+pub fn parse_droid_file(path: &Path) -> ParsedFile {
+    match read_json_file::<DroidSettingsJson>(path) {
+        Ok(settings) => settings_to_messages(path, settings).into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
 
+fn settings_to_messages(path: &Path, settings: DroidSettingsJson) -> Vec<UnifiedMessage> {
     // Skip if no token usage data
     let usage = match settings.token_usage {
         Some(u) => u,
@@ -251,6 +248,28 @@ pub fn parse_droid_file(path: &Path) -> Vec<UnifiedMessage> {
     )]
 }
 
+/// Droid (Factory.ai) session settings
+pub struct DroidSource;
+
+impl SessionSource for DroidSource {
+    fn name(&self) -> &str {
+        "droid"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.factory/sessions/*.settings.json
+        vec![format!("{}/.factory/sessions", home_dir)]
+    }
+
+    fn pattern(&self) -> &'static str {
+        "*.settings.json"
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_droid_file(path)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
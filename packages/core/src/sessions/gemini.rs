@@ -2,7 +2,8 @@
 //!
 //! Parses JSON session files from ~/.gemini/tmp/*/chats/session-*.json
 
-use super::UnifiedMessage;
+use super::diagnostics::{parse_json_bytes, read_json_file};
+use super::{ParsedFile, SessionSource, UnifiedMessage};
 use crate::TokenBreakdown;
 use serde::Deserialize;
 use std::path::Path;
@@ -48,24 +49,47 @@ pub struct GeminiTokens {
 }
 
 /// Parse a Gemini session file
-pub fn parse_gemini_file(path: &Path) -> Vec<UnifiedMessage> {
-    let data = match std::fs::read(path) {
-        Ok(d) => d,
-        Err(_) => return Vec::new(),
-    };
-
-    let mut bytes = data;
-    let session: GeminiSession = match simd_json::from_slice(&mut bytes) {
-        Ok(s) => s,
-        Err(_) => return Vec::new(),
-    };
+pub fn parse_gemini_file(path: &Path) -> ParsedFile {
+    match read_json_file::<GeminiSession>(path) {
+        Ok(session) => session_to_messages(session, &GEMINI).into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
+
+/// Parse Gemini session JSON read from an archive entry
+pub fn parse_gemini_bytes(data: &[u8]) -> ParsedFile {
+    match parse_json_bytes::<GeminiSession>(data) {
+        Ok(session) => session_to_messages(session, &GEMINI).into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
+
+/// How a session in the Gemini CLI format maps to messages. Forks of the
+/// Gemini CLI (Qwen Code) record chats the same way under their own names.
+pub(super) struct GeminiFlavor {
+    pub source: &'static str,
+    /// Message types of model responses
+    pub response_types: &'static [&'static str],
+    pub provider: fn(&str) -> &'static str,
+}
 
+const GEMINI: GeminiFlavor = GeminiFlavor {
+    source: "gemini",
+    response_types: &["gemini"],
+    provider: |_| "google",
+};
+
+pub(super) fn session_to_messages(
+    session: GeminiSession,
+    flavor: &GeminiFlavor,
+) -> Vec<UnifiedMessage> {
     let mut messages = Vec::new();
     let session_id = session.session_id.clone();
+    let project = Some(session.project_hash.clone());
 
     for msg in session.messages {
-        // Only process gemini messages with token data
-        if msg.message_type != "gemini" {
+        // Only process model responses with token data
+        if !flavor.response_types.contains(&msg.message_type.as_str()) {
             continue;
         }
 
@@ -89,26 +113,56 @@ pub fn parse_gemini_file(path: &Path) -> Vec<UnifiedMessage> {
             continue;
         }
 
-        messages.push(UnifiedMessage::new(
-            "gemini",
-            model,
-            "google",
-            session_id.clone(),
-            timestamp,
-            TokenBreakdown {
-                input: tokens.input.unwrap_or(0),
-                output: tokens.output.unwrap_or(0),
-                cache_read: tokens.cached.unwrap_or(0),
-                cache_write: 0,
-                reasoning: tokens.thoughts.unwrap_or(0),
-            },
-            0.0, // Cost calculated later
-        ));
+        let provider = (flavor.provider)(&model);
+        messages.push(
+            UnifiedMessage::new(
+                flavor.source,
+                model,
+                provider,
+                session_id.clone(),
+                timestamp,
+                TokenBreakdown {
+                    input: tokens.input.unwrap_or(0),
+                    output: tokens.output.unwrap_or(0),
+                    cache_read: tokens.cached.unwrap_or(0),
+                    cache_write: 0,
+                    reasoning: tokens.thoughts.unwrap_or(0),
+                },
+                0.0, // Cost calculated later
+            )
+            .with_project(project.clone()),
+        );
     }
 
     messages
 }
 
+/// Gemini CLI chat sessions
+pub struct GeminiSource;
+
+impl SessionSource for GeminiSource {
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.gemini/tmp/*/chats/session-*.json
+        vec![format!("{}/.gemini/tmp", home_dir)]
+    }
+
+    fn pattern(&self) -> &'static str {
+        "session-*.json"
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_gemini_file(path)
+    }
+
+    fn parse_bytes(&self, _path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_gemini_bytes(data))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,4 +208,19 @@ mod tests {
             Some("gemini-2.0-flash".to_string())
         );
     }
+
+    #[test]
+    fn test_parse_gemini_file_sets_project() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("session-1.json");
+        std::fs::write(
+            &path,
+            r#"{"sessionId":"ses_1","projectHash":"abc123","startTime":"2025-06-15T12:00:00Z","lastUpdated":"2025-06-15T12:30:00Z","messages":[{"id":"m1","timestamp":"2025-06-15T12:01:00Z","type":"gemini","model":"gemini-2.5-pro","tokens":{"input":10,"output":20,"cached":0,"thoughts":0,"tool":0,"total":30}}]}"#,
+        )
+        .unwrap();
+
+        let messages = parse_gemini_file(&path).messages;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].project.as_deref(), Some("abc123"));
+    }
 }
@@ -0,0 +1,88 @@
+//! Kilo session parser
+//!
+//! The Kilo CLI forked OpenCode and stores one JSON file per message in the
+//! same format. Parses files from ~/.local/share/kilo/storage/message/
+
+use super::diagnostics::{parse_json_bytes, read_json_file};
+use super::opencode::{opencode_message_to_unified, OpenCodeMessage};
+use super::{env_dir, ParsedFile, SessionSource};
+use std::path::Path;
+
+pub fn parse_kilo_file(path: &Path) -> ParsedFile {
+    match read_json_file::<OpenCodeMessage>(path) {
+        Ok(msg) => opencode_message_to_unified("kilo", msg)
+            .into_iter()
+            .collect::<Vec<_>>()
+            .into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
+
+/// Parse a Kilo message JSON read from an archive entry
+pub fn parse_kilo_bytes(data: &[u8]) -> ParsedFile {
+    match parse_json_bytes::<OpenCodeMessage>(data) {
+        Ok(msg) => opencode_message_to_unified("kilo", msg)
+            .into_iter()
+            .collect::<Vec<_>>()
+            .into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
+
+/// Kilo message storage (one JSON file per message)
+pub struct KiloSource;
+
+impl SessionSource for KiloSource {
+    fn name(&self) -> &str {
+        "kilo"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.local/share/kilo/storage/message/*/*.json
+        vec![format!("{}/.local/share/kilo/storage/message", home_dir)]
+    }
+
+    fn env_roots(&self) -> Option<Vec<String>> {
+        let xdg_data = env_dir("XDG_DATA_HOME")?;
+        Some(vec![format!("{}/kilo/storage/message", xdg_data)])
+    }
+
+    fn pattern(&self) -> &'static str {
+        "*.json"
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_kilo_file(path)
+    }
+
+    fn parse_bytes(&self, _path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_kilo_bytes(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kilo_message() {
+        let json = br#"{
+            "id": "msg_1",
+            "sessionID": "ses_1",
+            "role": "assistant",
+            "modelID": "claude-sonnet-4",
+            "providerID": "kilocode",
+            "mode": "code",
+            "cost": 0.02,
+            "tokens": { "input": 400, "output": 80, "cache": { "read": 1000, "write": 0 } },
+            "time": { "created": 1759219200000.0 }
+        }"#;
+
+        let messages = parse_kilo_bytes(json).messages;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].source, "kilo");
+        assert_eq!(messages[0].provider_id, "kilocode");
+        assert_eq!(messages[0].session_id, "ses_1");
+        assert_eq!(messages[0].tokens.cache_read, 1000);
+    }
+}
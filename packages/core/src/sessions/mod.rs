@@ -1,28 +1,55 @@
 //! Session parsers for different AI coding assistant formats
 //!
 //! Each source has its own parser that converts to a unified message format.
+//! Sources are described by the [`SessionSource`] trait and listed in a single
+//! registry, so scanning, parsing and pricing iterate over a [`SourceRegistry`]
+//! (the built-in `all_sources()` plus any user-defined sources) instead of
+//! naming every source explicitly.
 
 pub mod amp;
 pub mod claudecode;
 pub mod codex;
+pub mod copilot;
 pub mod cursor;
+pub mod custom;
+pub mod diagnostics;
 pub mod droid;
 pub mod gemini;
+pub mod kilo;
 pub mod opencode;
+pub mod qwen;
 
+pub use diagnostics::{Diagnostic, ParsedFile};
+
+use crate::parser::fingerprint_before;
+use crate::pricing::{billing_policy, PricingData};
 use crate::TokenBreakdown;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnifiedMessage {
     pub source: String,
     pub model_id: String,
     pub provider_id: String,
     pub session_id: String,
     pub timestamp: i64,
+    /// Derived from `timestamp`, so not persisted in the parse cache
+    #[serde(skip)]
     pub date: String,
     pub tokens: TokenBreakdown,
     pub cost: f64,
     pub agent: Option<String>,
+    /// Project or working directory the message belongs to, if the source records it
+    pub project: Option<String>,
+    /// Key identifying the same API response logged more than once.
+    /// Messages sharing a key are counted only once per scan.
+    pub dedup_key: Option<String>,
+    /// Tag of the scan root the file was found under (see `scanner::ScanRoots`).
+    /// Assigned after parsing, so not persisted in the parse cache.
+    #[serde(skip)]
+    pub root: Option<String>,
 }
 
 pub fn normalize_agent_name(agent: &str) -> String {
@@ -52,9 +79,19 @@ impl UnifiedMessage {
         tokens: TokenBreakdown,
         cost: f64,
     ) -> Self {
-        Self::new_with_agent(source, model_id, provider_id, session_id, timestamp, tokens, cost, None)
+        Self::new_with_agent(
+            source,
+            model_id,
+            provider_id,
+            session_id,
+            timestamp,
+            tokens,
+            cost,
+            None,
+        )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new_with_agent(
         source: impl Into<String>,
         model_id: impl Into<String>,
@@ -76,12 +113,275 @@ impl UnifiedMessage {
             tokens,
             cost,
             agent,
+            project: None,
+            dedup_key: None,
+            root: None,
         }
     }
+
+    /// Set the project this message belongs to
+    pub fn with_project(mut self, project: Option<String>) -> Self {
+        self.project = project.filter(|p| !p.is_empty());
+        self
+    }
+
+    /// Set the deduplication key for this message
+    pub fn with_dedup_key(mut self, dedup_key: Option<String>) -> Self {
+        self.dedup_key = dedup_key;
+        self
+    }
 }
 
-/// Convert Unix milliseconds timestamp to YYYY-MM-DD date string
-fn timestamp_to_date(timestamp_ms: i64) -> String {
+/// Drop messages that share a `dedup_key`, keeping the earliest occurrence.
+///
+/// Returns the number of messages removed.
+pub fn deduplicate_messages(messages: &mut Vec<UnifiedMessage>) -> usize {
+    use std::collections::hash_map::Entry;
+    use std::collections::HashMap;
+
+    let mut first_seen: HashMap<&str, usize> = HashMap::new();
+    for (idx, msg) in messages.iter().enumerate() {
+        let key = match &msg.dedup_key {
+            Some(k) => k.as_str(),
+            None => continue,
+        };
+        match first_seen.entry(key) {
+            Entry::Vacant(e) => {
+                e.insert(idx);
+            }
+            Entry::Occupied(mut e) => {
+                let kept = &messages[*e.get()];
+                // File order is not deterministic, so prefer the earliest timestamp
+                if (msg.timestamp, &msg.session_id) < (kept.timestamp, &kept.session_id) {
+                    e.insert(idx);
+                }
+            }
+        }
+    }
+
+    let keep: std::collections::HashSet<usize> = first_seen.into_values().collect();
+    let before = messages.len();
+    let mut idx = 0;
+    messages.retain(|msg| {
+        let current = idx;
+        idx += 1;
+        msg.dedup_key.is_none() || keep.contains(&current)
+    });
+
+    before - messages.len()
+}
+
+/// A session log format produced by an AI coding assistant
+///
+/// Implementations describe where the logs live, which files to pick up, how to
+/// turn a file into [`UnifiedMessage`]s and how those messages are billed.
+pub trait SessionSource: Send + Sync {
+    /// Stable source name used in options and results (e.g. "claude")
+    fn name(&self) -> &str;
+
+    /// Directories to scan for session files under the given home directory,
+    /// following the tool's default layout
+    fn roots(&self, home_dir: &str) -> Vec<String>;
+
+    /// Directories named by the tool's own environment variable (e.g.
+    /// `CODEX_HOME`). They replace `roots` for the local home directory only.
+    fn env_roots(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// File name pattern understood by `scanner::scan_directory`
+    fn pattern(&self) -> &'static str;
+
+    /// Whether a file matching `pattern` under one of the roots is a session
+    /// file, for layouts where the file name alone is not enough
+    fn accepts(&self, _path: &Path) -> bool {
+        true
+    }
+
+    /// Whether the files are written locally by the tool itself.
+    /// Network-synced sources (Cursor) are excluded from `parse_local_sources`.
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    /// Version of the parser's output, stored with cached results so they are
+    /// discarded when it changes. Built-in parsers are covered by the cache
+    /// format and crate version instead.
+    fn parser_version(&self) -> u64 {
+        0
+    }
+
+    /// Parse a single session file, reporting anything that had to be skipped
+    fn parse_file(&self, path: &Path) -> ParsedFile;
+
+    /// Parse the contents of a session file that is not on the file system
+    /// (e.g. an archive entry); `path` is the entry's path within its container.
+    ///
+    /// Returns `None` if the source needs more than the file's own contents.
+    fn parse_bytes(&self, _path: &Path, _data: &[u8]) -> Option<ParsedFile> {
+        None
+    }
+
+    /// Parse an append-only file from the position reached by a previous parse.
+    ///
+    /// Returns the messages found after that position and the new resume state,
+    /// or `None` if the source cannot be parsed incrementally.
+    fn parse_file_from(
+        &self,
+        _path: &Path,
+        _resume: &ResumeState,
+    ) -> Option<(ParsedFile, ResumeState)> {
+        None
+    }
+}
+
+/// Position reached by an incremental parse of an append-only file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResumeState {
+    /// Byte offset just past the last consumed line
+    pub offset: u64,
+    /// Number of lines before `offset`, so diagnostics report absolute line numbers
+    #[serde(default)]
+    pub lines: u64,
+    /// Parser-specific state carried across lines (e.g. Codex's current model)
+    pub context: Option<serde_json::Value>,
+    /// Hash of the bytes just before `offset` (see `parser::fingerprint_before`)
+    #[serde(default)]
+    pub fingerprint: Option<u64>,
+}
+
+impl ResumeState {
+    /// Whether `path` still holds the bytes this state was taken after, i.e.
+    /// the file was only appended to and can be parsed from `offset`
+    pub fn matches(&self, path: &Path) -> bool {
+        self.fingerprint.is_some() && self.fingerprint == fingerprint_before(path, self.offset)
+    }
+}
+
+/// All built-in session sources, in reporting order
+pub fn all_sources() -> Vec<Arc<dyn SessionSource>> {
+    vec![
+        Arc::new(opencode::OpenCodeSource),
+        Arc::new(claudecode::ClaudeSource),
+        Arc::new(codex::CodexSource),
+        Arc::new(gemini::GeminiSource),
+        Arc::new(cursor::CursorSource),
+        Arc::new(amp::AmpSource),
+        Arc::new(droid::DroidSource),
+        Arc::new(copilot::CopilotSource),
+        Arc::new(qwen::QwenSource),
+        Arc::new(kilo::KiloSource),
+    ]
+}
+
+/// The sources one scan works with: the built-in sources followed by the
+/// user-defined ones from the home directory's sources file (see `custom`)
+#[derive(Clone)]
+pub struct SourceRegistry {
+    sources: Vec<Arc<dyn SessionSource>>,
+    /// Why the sources file could not be used, with its path
+    config_diagnostic: Option<(PathBuf, Diagnostic)>,
+}
+
+impl Default for SourceRegistry {
+    /// The built-in sources only
+    fn default() -> Self {
+        Self {
+            sources: all_sources(),
+            config_diagnostic: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for SourceRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SourceRegistry")
+            .field("sources", &self.names())
+            .field("config_diagnostic", &self.config_diagnostic)
+            .finish()
+    }
+}
+
+impl SourceRegistry {
+    /// The built-in sources plus those defined in the sources file under
+    /// `home_dir`. A sources file that cannot be loaded leaves only the
+    /// built-in sources and is reported by `config_diagnostic`.
+    pub fn load(home_dir: &str) -> Self {
+        let mut registry = Self::default();
+        let Some(path) = custom::CustomSourcesConfig::default_path(home_dir) else {
+            return registry;
+        };
+        match custom::CustomSourcesConfig::from_file(&path).and_then(|c| custom::compile(&c)) {
+            Ok(sources) => registry.sources.extend(sources),
+            Err(error) => registry.config_diagnostic = Some((path, Diagnostic::config(error))),
+        }
+        registry
+    }
+
+    /// All sources, in reporting order
+    pub fn all(&self) -> &[Arc<dyn SessionSource>] {
+        &self.sources
+    }
+
+    /// Look up a source by name
+    pub fn find(&self, name: &str) -> Option<Arc<dyn SessionSource>> {
+        self.sources.iter().find(|s| s.name() == name).cloned()
+    }
+
+    /// The sources named in `names`, or all of them when it is empty
+    pub fn select(&self, names: &[String]) -> Vec<Arc<dyn SessionSource>> {
+        self.sources
+            .iter()
+            .filter(|source| names.is_empty() || names.iter().any(|n| n == source.name()))
+            .cloned()
+            .collect()
+    }
+
+    /// Names of all sources
+    pub fn names(&self) -> Vec<String> {
+        self.sources.iter().map(|s| s.name().to_string()).collect()
+    }
+
+    /// Names of all sources that are parsed from local files
+    pub fn local_names(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .filter(|s| s.is_local())
+            .map(|s| s.name().to_string())
+            .collect()
+    }
+
+    /// The sources file and why it could not be loaded, if it could not
+    pub fn config_diagnostic(&self) -> Option<(&Path, &Diagnostic)> {
+        self.config_diagnostic
+            .as_ref()
+            .map(|(path, diagnostic)| (path.as_path(), diagnostic))
+    }
+}
+
+/// Calculate the cost of a message using the billing policy of its source
+/// (see `pricing::billing_policy`)
+pub fn calculate_message_cost(msg: &UnifiedMessage, pricing: &PricingData) -> f64 {
+    let policy = billing_policy(&msg.source);
+    let cost =
+        pricing.calculate_cost_with_policy(&msg.model_id, &msg.tokens, &policy, msg.timestamp);
+    let free = pricing.get_pricing(&msg.model_id).is_some_and(|p| p.free);
+    if cost <= 0.0 && policy.fallback_to_recorded_cost && !free {
+        msg.cost
+    } else {
+        cost
+    }
+}
+
+/// Value of an environment variable that names a directory, if set and non-empty
+pub(crate) fn env_dir(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|dir| !dir.trim().is_empty())
+}
+
+/// Convert Unix milliseconds timestamp to YYYY-MM-DD date string (UTC)
+pub(crate) fn timestamp_to_date(timestamp_ms: i64) -> String {
     use chrono::{TimeZone, Utc};
 
     let datetime = Utc.timestamp_millis_opt(timestamp_ms);
@@ -145,6 +445,94 @@ mod tests {
         assert_eq!(msg.date, "2024-12-01");
         assert_eq!(msg.cost, 0.05);
         assert_eq!(msg.agent, None);
+        assert_eq!(msg.project, None);
+    }
+
+    #[test]
+    fn test_deduplicate_messages_keeps_earliest() {
+        let make = |session: &str, ts: i64, key: Option<&str>| {
+            UnifiedMessage::new(
+                "claude",
+                "claude-sonnet-4",
+                "anthropic",
+                session,
+                ts,
+                TokenBreakdown::default(),
+                0.0,
+            )
+            .with_dedup_key(key.map(String::from))
+        };
+
+        let mut messages = vec![
+            make("forked", 2000, Some("msg_1:req_1")),
+            make("original", 1000, Some("msg_1:req_1")),
+            make("original", 1500, Some("msg_2:req_2")),
+            make("original", 1500, None),
+            make("original", 1500, None),
+        ];
+
+        let removed = deduplicate_messages(&mut messages);
+        assert_eq!(removed, 1);
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0].session_id, "original");
+        assert_eq!(messages[0].timestamp, 1000);
+    }
+
+    #[test]
+    fn test_registry_names_are_unique() {
+        let names = SourceRegistry::default().names();
+        let unique: std::collections::HashSet<_> = names.iter().collect();
+        assert_eq!(names.len(), unique.len());
+        assert_eq!(
+            names,
+            vec![
+                "opencode", "claude", "codex", "gemini", "cursor", "amp", "droid", "copilot",
+                "qwen", "kilo"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_local_source_names_exclude_cursor() {
+        let local = SourceRegistry::default().local_names();
+        assert!(!local.contains(&"cursor".to_string()));
+        assert!(local.contains(&"claude".to_string()));
+    }
+
+    #[test]
+    fn test_find_source() {
+        let registry = SourceRegistry::default();
+        assert_eq!(registry.find("codex").unwrap().name(), "codex");
+        assert!(registry.find("unknown").is_none());
+    }
+
+    #[test]
+    fn test_registry_loads_user_defined_sources() {
+        let home = tempfile::TempDir::new().unwrap();
+        let home_dir = home.path().to_str().unwrap();
+        let config_dir = home.path().join(".config").join("tokscale");
+        std::fs::create_dir_all(&config_dir).unwrap();
+
+        let registry = SourceRegistry::load(home_dir);
+        assert_eq!(registry.all().len(), all_sources().len());
+        assert!(registry.config_diagnostic().is_none());
+
+        std::fs::write(
+            config_dir.join("sources.toml"),
+            "[[sources]]\nname = \"acme\"\nroot = \".acme\"\nglob = \"*.jsonl\"\nmodel = \"/model\"\ntimestamp = \"/ts\"\n",
+        )
+        .unwrap();
+        let registry = SourceRegistry::load(home_dir);
+        assert_eq!(registry.names().last().map(String::as_str), Some("acme"));
+        assert!(registry.local_names().contains(&"acme".to_string()));
+
+        // A broken sources file leaves the built-in sources and is reported
+        std::fs::write(config_dir.join("sources.toml"), "[[sources]\n").unwrap();
+        let registry = SourceRegistry::load(home_dir);
+        assert_eq!(registry.all().len(), all_sources().len());
+        let (path, diagnostic) = registry.config_diagnostic().unwrap();
+        assert!(path.ends_with("sources.toml"));
+        assert_eq!(diagnostic.kind, diagnostics::DiagnosticKind::Config);
     }
 
     #[test]
@@ -161,4 +549,43 @@ mod tests {
         assert_eq!(normalize_agent_name("explore"), "explore");
         assert_eq!(normalize_agent_name("CustomAgent"), "CustomAgent");
     }
+
+    #[test]
+    fn test_calculate_message_cost_applies_source_policy() {
+        let mut pricing = PricingData::new();
+        pricing.add_model(
+            "gemini-2.5-pro".to_string(),
+            crate::pricing::ModelPricing {
+                input_cost_per_token: 1.0,
+                output_cost_per_token: 10.0,
+                cache_read_input_token_cost: 0.5,
+                ..Default::default()
+            },
+        );
+        pricing.finalize();
+
+        let tokens = TokenBreakdown {
+            input: 10,
+            output: 2,
+            cache_read: 4,
+            cache_write: 0,
+            reasoning: 1,
+        };
+
+        // Gemini: cache is free, thoughts are billed as output
+        let gemini = UnifiedMessage::new(
+            "gemini",
+            "gemini-2.5-pro",
+            "google",
+            "s",
+            0,
+            tokens.clone(),
+            0.0,
+        );
+        assert!((calculate_message_cost(&gemini, &pricing) - 40.0).abs() < 1e-9);
+
+        // Cursor keeps its recorded cost when the model has no pricing
+        let cursor = UnifiedMessage::new("cursor", "unknown-model", "cursor", "s", 0, tokens, 1.25);
+        assert!((calculate_message_cost(&cursor, &pricing) - 1.25).abs() < 1e-9);
+    }
 }
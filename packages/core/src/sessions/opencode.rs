@@ -2,7 +2,8 @@
 //!
 //! Parses individual JSON files from ~/.local/share/opencode/storage/message/
 
-use super::{normalize_agent_name, UnifiedMessage};
+use super::diagnostics::{parse_json_bytes, read_json_file};
+use super::{env_dir, normalize_agent_name, ParsedFile, SessionSource, UnifiedMessage};
 use crate::TokenBreakdown;
 use serde::Deserialize;
 use std::path::Path;
@@ -24,6 +25,14 @@ pub struct OpenCodeMessage {
     pub time: OpenCodeTime,
     pub agent: Option<String>,
     pub mode: Option<String>,
+    pub path: Option<OpenCodePath>,
+}
+
+/// Working directory recorded on assistant messages
+#[derive(Debug, Deserialize)]
+pub struct OpenCodePath {
+    pub cwd: Option<String>,
+    pub root: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,12 +56,33 @@ pub struct OpenCodeTime {
     pub completed: Option<f64>,
 }
 
-pub fn parse_opencode_file(path: &Path) -> Option<UnifiedMessage> {
-    let data = std::fs::read(path).ok()?;
-    let mut bytes = data;
+pub fn parse_opencode_file(path: &Path) -> ParsedFile {
+    match read_json_file::<OpenCodeMessage>(path) {
+        Ok(msg) => opencode_message_to_unified("opencode", msg)
+            .into_iter()
+            .collect::<Vec<_>>()
+            .into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
 
-    let msg: OpenCodeMessage = simd_json::from_slice(&mut bytes).ok()?;
+/// Parse an OpenCode message JSON read from an archive entry
+pub fn parse_opencode_bytes(data: &[u8]) -> ParsedFile {
+    match parse_json_bytes::<OpenCodeMessage>(data) {
+        Ok(msg) => opencode_message_to_unified("opencode", msg)
+            .into_iter()
+            .collect::<Vec<_>>()
+            .into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
 
+/// Convert an assistant message in the OpenCode storage format, which OpenCode
+/// forks (Kilo) share, into a message of the given source
+pub(super) fn opencode_message_to_unified(
+    source: &'static str,
+    msg: OpenCodeMessage,
+) -> Option<UnifiedMessage> {
     if msg.role != "assistant" {
         return None;
     }
@@ -61,23 +91,61 @@ pub fn parse_opencode_file(path: &Path) -> Option<UnifiedMessage> {
     let model_id = msg.model_id?;
     let agent_or_mode = msg.mode.or(msg.agent);
     let agent = agent_or_mode.map(|a| normalize_agent_name(&a));
+    let project = msg.path.and_then(|p| p.root.or(p.cwd));
+
+    Some(
+        UnifiedMessage::new_with_agent(
+            source,
+            model_id,
+            msg.provider_id.unwrap_or_else(|| "unknown".to_string()),
+            msg.session_id.clone(),
+            msg.time.created as i64,
+            TokenBreakdown {
+                input: tokens.input,
+                output: tokens.output,
+                cache_read: tokens.cache.read,
+                cache_write: tokens.cache.write,
+                reasoning: tokens.reasoning.unwrap_or(0),
+            },
+            msg.cost.unwrap_or(0.0),
+            agent,
+        )
+        .with_project(project),
+    )
+}
+
+/// OpenCode message storage (one JSON file per message)
+pub struct OpenCodeSource;
+
+impl SessionSource for OpenCodeSource {
+    fn name(&self) -> &str {
+        "opencode"
+    }
 
-    Some(UnifiedMessage::new_with_agent(
-        "opencode",
-        model_id,
-        msg.provider_id.unwrap_or_else(|| "unknown".to_string()),
-        msg.session_id.clone(),
-        msg.time.created as i64,
-        TokenBreakdown {
-            input: tokens.input,
-            output: tokens.output,
-            cache_read: tokens.cache.read,
-            cache_write: tokens.cache.write,
-            reasoning: tokens.reasoning.unwrap_or(0),
-        },
-        msg.cost.unwrap_or(0.0),
-        agent,
-    ))
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.local/share/opencode/storage/message/*/*.json
+        vec![format!(
+            "{}/.local/share/opencode/storage/message",
+            home_dir
+        )]
+    }
+
+    fn env_roots(&self) -> Option<Vec<String>> {
+        let xdg_data = env_dir("XDG_DATA_HOME")?;
+        Some(vec![format!("{}/opencode/storage/message", xdg_data)])
+    }
+
+    fn pattern(&self) -> &'static str {
+        "*.json"
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_opencode_file(path)
+    }
+
+    fn parse_bytes(&self, _path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_opencode_bytes(data))
+    }
 }
 
 #[cfg(test)]
@@ -134,4 +202,32 @@ mod tests {
 
         assert_eq!(msg.agent, Some("OmO".to_string()));
     }
+
+    #[test]
+    fn test_parse_opencode_project_from_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file_path = dir.path().join("msg_123.json");
+        std::fs::write(
+            &file_path,
+            r#"{
+                "id": "msg_123",
+                "sessionID": "ses_456",
+                "role": "assistant",
+                "modelID": "claude-sonnet-4",
+                "providerID": "anthropic",
+                "tokens": {
+                    "input": 10,
+                    "output": 5,
+                    "cache": { "read": 0, "write": 0 }
+                },
+                "time": { "created": 1700000000000.0 },
+                "path": { "cwd": "/home/dev/tokscale/packages/core", "root": "/home/dev/tokscale" }
+            }"#,
+        )
+        .unwrap();
+
+        let parsed = parse_opencode_file(&file_path);
+        let msg = &parsed.messages[0];
+        assert_eq!(msg.project.as_deref(), Some("/home/dev/tokscale"));
+    }
 }
@@ -0,0 +1,127 @@
+//! Qwen Code session parser
+//!
+//! Qwen Code forked the Gemini CLI and records chats in its JSON format.
+//! Parses JSON session files from ~/.qwen/tmp/*/chats/session-*.json
+
+use super::diagnostics::{parse_json_bytes, read_json_file};
+use super::gemini::{session_to_messages, GeminiFlavor, GeminiSession};
+use super::{ParsedFile, SessionSource};
+use std::path::Path;
+
+const QWEN: GeminiFlavor = GeminiFlavor {
+    source: "qwen",
+    // Early versions kept the Gemini CLI's message type
+    response_types: &["qwen", "gemini"],
+    provider: get_provider_from_model,
+};
+
+/// Get provider from model name. Qwen Code can also drive any
+/// OpenAI-compatible endpoint.
+fn get_provider_from_model(model: &str) -> &'static str {
+    let model_lower = model.to_lowercase();
+    if model_lower.contains("claude") {
+        return "anthropic";
+    }
+    if model_lower.contains("gpt") || model_lower.contains("o3") || model_lower.contains("o4") {
+        return "openai";
+    }
+    if model_lower.contains("gemini") {
+        return "google";
+    }
+    if model_lower.contains("kimi") {
+        return "moonshot";
+    }
+    if model_lower.contains("glm") {
+        return "zai";
+    }
+    "alibaba" // Qwen models by default
+}
+
+/// Parse a Qwen Code session file
+pub fn parse_qwen_file(path: &Path) -> ParsedFile {
+    match read_json_file::<GeminiSession>(path) {
+        Ok(session) => session_to_messages(session, &QWEN).into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
+
+/// Parse Qwen Code session JSON read from an archive entry
+pub fn parse_qwen_bytes(data: &[u8]) -> ParsedFile {
+    match parse_json_bytes::<GeminiSession>(data) {
+        Ok(session) => session_to_messages(session, &QWEN).into(),
+        Err(diagnostic) => ParsedFile::failed(diagnostic),
+    }
+}
+
+/// Qwen Code chat sessions
+pub struct QwenSource;
+
+impl SessionSource for QwenSource {
+    fn name(&self) -> &str {
+        "qwen"
+    }
+
+    fn roots(&self, home_dir: &str) -> Vec<String> {
+        // ~/.qwen/tmp/*/chats/session-*.json
+        vec![format!("{}/.qwen/tmp", home_dir)]
+    }
+
+    fn pattern(&self) -> &'static str {
+        "session-*.json"
+    }
+
+    fn parse_file(&self, path: &Path) -> ParsedFile {
+        parse_qwen_file(path)
+    }
+
+    fn parse_bytes(&self, _path: &Path, data: &[u8]) -> Option<ParsedFile> {
+        Some(parse_qwen_bytes(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_qwen_session() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("session-2025-09-30T08-00-abc.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "sessionId": "abc",
+                "projectHash": "f00d",
+                "startTime": "2025-09-30T08:00:00Z",
+                "lastUpdated": "2025-09-30T08:05:00Z",
+                "messages": [
+                    { "id": "1", "timestamp": "2025-09-30T08:00:01Z", "type": "user", "content": "hi" },
+                    {
+                        "id": "2",
+                        "timestamp": "2025-09-30T08:00:05Z",
+                        "type": "qwen",
+                        "model": "qwen3-coder-plus",
+                        "tokens": { "input": 900, "output": 120, "cached": 300, "thoughts": 40 }
+                    },
+                    {
+                        "id": "3",
+                        "timestamp": "2025-09-30T08:01:00Z",
+                        "type": "gemini",
+                        "model": "gpt-4.1",
+                        "tokens": { "input": 50, "output": 10 }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let messages = parse_qwen_file(&path).messages;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].source, "qwen");
+        assert_eq!(messages[0].provider_id, "alibaba");
+        assert_eq!(messages[0].tokens.cache_read, 300);
+        assert_eq!(messages[0].tokens.reasoning, 40);
+        assert_eq!(messages[0].project.as_deref(), Some("f00d"));
+        assert_eq!(messages[1].provider_id, "openai");
+    }
+}
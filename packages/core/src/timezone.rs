@@ -0,0 +1,155 @@
+//! Timezone handling for day bucketing
+//!
+//! Message dates default to UTC. A timezone option (IANA name such as
+//! "Asia/Seoul" or a fixed offset such as "+09:00") re-derives every
+//! message's `date` so daily, monthly and date-filtered results follow the
+//! user's local calendar.
+
+use crate::sessions::UnifiedMessage;
+use chrono::{FixedOffset, TimeZone, Utc};
+use chrono_tz::Tz;
+use rayon::prelude::*;
+
+/// Timezone used to turn message timestamps into calendar dates
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DateZone {
+    #[default]
+    Utc,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+/// Error for timezone strings that are neither an IANA name nor an offset
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid timezone '{0}': expected an IANA name like \"Asia/Seoul\" or an offset like \"+09:00\"")]
+pub struct TimezoneError(pub String);
+
+impl DateZone {
+    /// Parse an IANA timezone name or a fixed UTC offset
+    ///
+    /// Accepted offsets: "Z", "UTC", "+09:00", "-0530", "+9", optionally
+    /// prefixed with "UTC" or "GMT" (e.g. "UTC+9").
+    pub fn parse(value: &str) -> Result<Self, TimezoneError> {
+        let trimmed = value.trim();
+        let error = || TimezoneError(value.to_string());
+
+        if trimmed.is_empty() {
+            return Err(error());
+        }
+        if ["Z", "UTC", "GMT"]
+            .iter()
+            .any(|utc| trimmed.eq_ignore_ascii_case(utc))
+        {
+            return Ok(Self::Utc);
+        }
+
+        let offset = trimmed
+            .strip_prefix("UTC")
+            .or_else(|| trimmed.strip_prefix("GMT"))
+            .unwrap_or(trimmed);
+        if offset.starts_with('+') || offset.starts_with('-') {
+            return parse_offset(offset).map(Self::Fixed).ok_or_else(error);
+        }
+
+        trimmed.parse::<Tz>().map(Self::Named).map_err(|_| error())
+    }
+
+    /// Parse an optional timezone option, defaulting to UTC
+    pub fn from_option(value: &Option<String>) -> Result<Self, TimezoneError> {
+        value.as_deref().map_or(Ok(Self::Utc), Self::parse)
+    }
+
+    /// Convert a Unix milliseconds timestamp to a YYYY-MM-DD date in this zone
+    pub fn format_date(&self, timestamp_ms: i64) -> String {
+        let utc = match Utc.timestamp_millis_opt(timestamp_ms) {
+            chrono::LocalResult::Single(dt) => dt,
+            _ => return String::new(),
+        };
+        match self {
+            Self::Utc => utc.format("%Y-%m-%d").to_string(),
+            Self::Fixed(offset) => utc.with_timezone(offset).format("%Y-%m-%d").to_string(),
+            Self::Named(tz) => utc.with_timezone(tz).format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// Parse "+HH:MM", "+HHMM" or "+H" into a fixed offset
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let (sign, digits) = match value.as_bytes().first()? {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if digits.len() == 4 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    if hours.is_empty() || hours.len() > 2 || minutes.len() > 2 {
+        return None;
+    }
+
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if minutes >= 60 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Re-derive message dates in the given zone. Parsers produce UTC dates, so
+/// this is a no-op for UTC.
+pub fn localize_dates(messages: &mut [UnifiedMessage], zone: &DateZone) {
+    if *zone == DateZone::Utc {
+        return;
+    }
+    messages.par_iter_mut().for_each(|msg| {
+        msg.date = zone.format_date(msg.timestamp);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01T18:30:00Z
+    const EVENING_UTC: i64 = 1704133800000;
+
+    #[test]
+    fn test_parse_named_zone() {
+        let zone = DateZone::parse("Asia/Seoul").unwrap();
+        assert_eq!(zone, DateZone::Named(chrono_tz::Asia::Seoul));
+        assert_eq!(zone.format_date(EVENING_UTC), "2024-01-02");
+    }
+
+    #[test]
+    fn test_parse_fixed_offsets() {
+        for value in ["+09:00", "+0900", "+9", "UTC+9", "GMT+09:00"] {
+            let zone = DateZone::parse(value).unwrap();
+            assert_eq!(
+                zone,
+                DateZone::Fixed(FixedOffset::east_opt(9 * 3600).unwrap())
+            );
+        }
+
+        let zone = DateZone::parse("-05:30").unwrap();
+        assert_eq!(zone.format_date(EVENING_UTC), "2024-01-01");
+        assert_eq!(zone.format_date(1704072600000), "2023-12-31");
+    }
+
+    #[test]
+    fn test_parse_utc_and_invalid() {
+        assert_eq!(DateZone::parse("utc").unwrap(), DateZone::Utc);
+        assert_eq!(DateZone::from_option(&None).unwrap(), DateZone::Utc);
+        assert_eq!(DateZone::Utc.format_date(EVENING_UTC), "2024-01-01");
+
+        for value in ["", "Mars/Olympus", "+25:00", "+09:75", "+123"] {
+            assert!(
+                DateZone::parse(value).is_err(),
+                "{value} should be rejected"
+            );
+        }
+    }
+}
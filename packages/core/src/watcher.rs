@@ -0,0 +1,669 @@
+//! Live aggregation driven by filesystem events
+//!
+//! `SessionWatcher` keeps the parsed messages of every session file under the
+//! source roots and turns file changes into daily contribution deltas.
+//! Append-only JSONL files are parsed from where the previous parse stopped;
+//! other files are re-parsed and diffed against their previous messages.
+//! `watch` drives a `SessionWatcher` from `notify` events on a background
+//! thread. Roots that do not exist yet are picked up when they are created
+//! by watching their nearest existing ancestor.
+
+use crate::aggregator;
+use crate::pricing::PricingData;
+use crate::scanner::{self, ScanRoots};
+use crate::sessions::{self, ResumeState, SessionSource, UnifiedMessage};
+use crate::timezone::{self, DateZone};
+use crate::DailyContribution;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+/// A burst of events is flushed after at most this many debounce intervals
+const MAX_BATCH_INTERVALS: u32 = 10;
+
+/// Net change produced by one batch of file changes
+#[derive(Debug, Clone, Default)]
+pub struct WatchUpdate {
+    /// Change per day; removed or shrunk usage shows up as negative values
+    pub contributions: Vec<DailyContribution>,
+    /// Files that were re-parsed for this update
+    pub changed_files: Vec<PathBuf>,
+}
+
+impl WatchUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.contributions.is_empty()
+    }
+}
+
+/// Parse state of one watched file
+struct WatchedFile {
+    source: Arc<dyn SessionSource>,
+    size: u64,
+    mtime_ms: i64,
+    /// Present for sources that can resume parsing from an offset
+    resume: Option<ResumeState>,
+    /// Priced messages with dates in the watcher's zone
+    messages: Vec<UnifiedMessage>,
+}
+
+/// Incrementally maintained messages of every session file under the
+/// watched sources' roots
+pub struct SessionWatcher {
+    roots: ScanRoots,
+    sources: Vec<Arc<dyn SessionSource>>,
+    pricing: PricingData,
+    zone: DateZone,
+    files: HashMap<PathBuf, WatchedFile>,
+    /// Number of watched messages per `source:dedup_key`; only the first
+    /// copy of a message counts towards contributions
+    dedup_counts: HashMap<String, usize>,
+}
+
+impl SessionWatcher {
+    /// Parse every file of the given sources (all sources when empty)
+    pub fn new(roots: ScanRoots, sources: &[String], pricing: PricingData, zone: DateZone) -> Self {
+        let selected = roots.sources.select(sources);
+
+        let mut watcher = Self {
+            roots,
+            sources: selected,
+            pricing,
+            zone,
+            files: HashMap::new(),
+            dedup_counts: HashMap::new(),
+        };
+        watcher.rescan();
+        watcher
+    }
+
+    /// Source roots that currently exist, to be watched recursively
+    pub fn roots(&self) -> Vec<PathBuf> {
+        self.all_roots().filter(|root| root.is_dir()).collect()
+    }
+
+    /// Source roots that do not exist yet, such as those of a tool that has
+    /// never run
+    pub fn missing_roots(&self) -> Vec<PathBuf> {
+        self.all_roots().filter(|root| !root.is_dir()).collect()
+    }
+
+    fn all_roots(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.sources
+            .iter()
+            .flat_map(|source| self.roots.source_roots(source.as_ref()))
+            .map(|root| PathBuf::from(root.path))
+    }
+
+    /// Daily contributions of everything watched so far
+    pub fn contributions(&self) -> Vec<DailyContribution> {
+        let mut messages: Vec<UnifiedMessage> = self
+            .files
+            .values()
+            .flat_map(|file| file.messages.iter().cloned())
+            .collect();
+        sessions::deduplicate_messages(&mut messages);
+        aggregator::aggregate_by_date(messages)
+    }
+
+    /// Check every file under the roots, picking up changes the event
+    /// stream missed
+    pub fn rescan(&mut self) -> WatchUpdate {
+        if self.sources.is_empty() {
+            return WatchUpdate::default();
+        }
+
+        let names: Vec<String> = self.sources.iter().map(|s| s.name().to_string()).collect();
+        let scan = scanner::scan_sources(&self.roots, &names);
+        let mut paths: Vec<PathBuf> = scan.all_files().into_iter().map(|(_, p)| p).collect();
+        paths.extend(self.files.keys().filter(|p| !p.exists()).cloned());
+
+        self.refresh(&paths)
+    }
+
+    /// Re-read the given paths and return the net change. Directories are
+    /// expanded to the session files inside them; unrelated paths are ignored.
+    pub fn refresh(&mut self, paths: &[PathBuf]) -> WatchUpdate {
+        let mut candidates: Vec<PathBuf> = Vec::with_capacity(paths.len());
+        for path in paths {
+            if path.is_dir() {
+                candidates.extend(
+                    WalkDir::new(path)
+                        .into_iter()
+                        .filter_map(|e| e.ok())
+                        .filter(|e| e.file_type().is_file())
+                        .map(|e| e.into_path()),
+                );
+            } else {
+                candidates.push(path.clone());
+            }
+        }
+        candidates.sort();
+        candidates.dedup();
+
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        let mut changed_files = Vec::new();
+        for path in candidates {
+            if self.refresh_file(&path, &mut added, &mut removed) {
+                changed_files.push(path);
+            }
+        }
+
+        WatchUpdate {
+            contributions: aggregator::aggregate_delta(&added, &removed),
+            changed_files,
+        }
+    }
+
+    /// Bring one file up to date, collecting the messages that start or stop
+    /// counting. Returns whether the file was re-read.
+    fn refresh_file(
+        &mut self,
+        path: &Path,
+        added: &mut Vec<UnifiedMessage>,
+        removed: &mut Vec<UnifiedMessage>,
+    ) -> bool {
+        let previous = self.files.remove(path);
+        let source = match previous
+            .as_ref()
+            .map(|f| f.source.clone())
+            .or_else(|| self.source_for(path))
+        {
+            Some(source) => source,
+            None => return false,
+        };
+
+        let (size, mtime_ms) = match file_stamp(path) {
+            Some(stamp) => stamp,
+            None => {
+                // Deleted
+                return match previous {
+                    Some(file) => {
+                        self.uncount(&file.messages, removed);
+                        true
+                    }
+                    None => false,
+                };
+            }
+        };
+
+        if let Some(mut file) = previous {
+            if file.size == size && file.mtime_ms == mtime_ms {
+                self.files.insert(path.to_path_buf(), file);
+                return false;
+            }
+
+            // Appended to: parse only the new lines
+            let resumed = file
+                .resume
+                .as_ref()
+                .filter(|resume| size > file.size && resume.matches(path))
+                .and_then(|resume| source.parse_file_from(path, resume));
+            if let Some((parsed, resume)) = resumed {
+                let messages = self.prepare(source.as_ref(), path, parsed.messages);
+                self.count(&messages, added);
+                file.messages.extend(messages);
+                file.size = size;
+                file.mtime_ms = mtime_ms;
+                file.resume = Some(resume);
+                self.files.insert(path.to_path_buf(), file);
+                return true;
+            }
+
+            self.uncount(&file.messages, removed);
+        }
+
+        let (parsed, resume) = match source.parse_file_from(path, &ResumeState::default()) {
+            Some((parsed, resume)) => (parsed, Some(resume)),
+            None => (source.parse_file(path), None),
+        };
+        let messages = self.prepare(source.as_ref(), path, parsed.messages);
+        self.count(&messages, added);
+        self.files.insert(
+            path.to_path_buf(),
+            WatchedFile {
+                source,
+                size,
+                mtime_ms,
+                resume,
+                messages,
+            },
+        );
+        true
+    }
+
+    /// The watched source a file belongs to, if any
+    fn source_for(&self, path: &Path) -> Option<Arc<dyn SessionSource>> {
+        let file_name = path.file_name()?.to_str()?;
+        self.sources
+            .iter()
+            .find(|source| {
+                scanner::matches_pattern(file_name, source.pattern())
+                    && source.accepts(path)
+                    && self.roots.tag_for(source.as_ref(), path).is_some()
+            })
+            .cloned()
+    }
+
+    /// Price messages, tag them with their root and date them in the watcher's zone
+    fn prepare(
+        &self,
+        source: &dyn SessionSource,
+        path: &Path,
+        mut messages: Vec<UnifiedMessage>,
+    ) -> Vec<UnifiedMessage> {
+        let root = self.roots.tag_for(source, path);
+        for msg in &mut messages {
+            msg.cost = sessions::calculate_message_cost(msg, &self.pricing);
+            msg.root = root.clone();
+        }
+        timezone::localize_dates(&mut messages, &self.zone);
+        messages
+    }
+
+    /// Register new messages, collecting those not already counted
+    fn count(&mut self, messages: &[UnifiedMessage], added: &mut Vec<UnifiedMessage>) {
+        for msg in messages {
+            if let Some(key) = &msg.dedup_key {
+                let count = self
+                    .dedup_counts
+                    .entry(format!("{}:{}", msg.source, key))
+                    .or_insert(0);
+                *count += 1;
+                if *count > 1 {
+                    continue;
+                }
+            }
+            added.push(msg.clone());
+        }
+    }
+
+    /// Unregister messages, collecting those that no longer count
+    fn uncount(&mut self, messages: &[UnifiedMessage], removed: &mut Vec<UnifiedMessage>) {
+        for msg in messages {
+            if let Some(key) = &msg.dedup_key {
+                let key = format!("{}:{}", msg.source, key);
+                if let Some(count) = self.dedup_counts.get_mut(&key) {
+                    *count -= 1;
+                    if *count > 0 {
+                        continue;
+                    }
+                    self.dedup_counts.remove(&key);
+                }
+            }
+            removed.push(msg.clone());
+        }
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, i64)> {
+    let meta = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+    let mtime_ms = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as i64 / 1_000_000)
+        .unwrap_or(0);
+    Some((meta.len(), mtime_ms))
+}
+
+/// Source roots that do not exist yet, and the directories watched in
+/// their place to notice when they are created
+#[derive(Debug, Default)]
+struct PendingRoots {
+    roots: Vec<PathBuf>,
+    /// Nearest existing ancestors of `roots`, watched non-recursively
+    ancestors: HashSet<PathBuf>,
+}
+
+impl PendingRoots {
+    fn new(roots: Vec<PathBuf>) -> Self {
+        Self {
+            roots,
+            ancestors: HashSet::new(),
+        }
+    }
+
+    /// Take the roots that exist now and move the ancestor watches of the
+    /// others to their nearest existing ancestor
+    fn update(&mut self, fs_watcher: &mut impl Watcher) -> Vec<PathBuf> {
+        let (created, pending): (Vec<PathBuf>, Vec<PathBuf>) =
+            self.roots.drain(..).partition(|root| root.is_dir());
+        self.roots = pending;
+
+        let needed: HashSet<PathBuf> = self
+            .roots
+            .iter()
+            .filter_map(|root| root.ancestors().skip(1).find(|dir| dir.is_dir()))
+            .map(Path::to_path_buf)
+            .collect();
+        for dir in self.ancestors.difference(&needed) {
+            fs_watcher.unwatch(dir).ok();
+        }
+        for dir in needed.difference(&self.ancestors) {
+            fs_watcher.watch(dir, RecursiveMode::NonRecursive).ok();
+        }
+        self.ancestors = needed;
+
+        created
+    }
+}
+
+/// A running watcher. Dropping it (or calling `close`) stops watching.
+pub struct WatchHandle {
+    state: Arc<Mutex<SessionWatcher>>,
+    /// Shared with the worker, which adds roots as they are created
+    fs_watcher: Arc<Mutex<Option<RecommendedWatcher>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl WatchHandle {
+    /// Daily contributions of everything watched so far
+    pub fn contributions(&self) -> Vec<DailyContribution> {
+        self.state
+            .lock()
+            .map(|watcher| watcher.contributions())
+            .unwrap_or_default()
+    }
+
+    /// Stop watching and wait for the pending batch to be delivered
+    pub fn close(&mut self) {
+        // Dropping the filesystem watcher closes the event channel, which
+        // ends the worker loop
+        let fs_watcher = self
+            .fs_watcher
+            .lock()
+            .map(|mut fs_watcher| fs_watcher.take())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().take());
+        drop(fs_watcher);
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+    }
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Watch the roots of `watcher` and call `on_update` with every non-empty
+/// change. Events arriving within `debounce` of each other are handled as
+/// one batch.
+pub fn watch<F>(
+    mut watcher: SessionWatcher,
+    debounce: Duration,
+    mut on_update: F,
+) -> notify::Result<WatchHandle>
+where
+    F: FnMut(WatchUpdate) + Send + 'static,
+{
+    let mut roots = watcher.roots();
+    let mut pending = PendingRoots::new(watcher.missing_roots());
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut fs_watcher = notify::recommended_watcher(tx)?;
+    for root in &roots {
+        fs_watcher.watch(root, RecursiveMode::Recursive)?;
+    }
+    // A root created since the watcher's initial scan is scanned now
+    for root in pending.update(&mut fs_watcher) {
+        fs_watcher.watch(&root, RecursiveMode::Recursive)?;
+        watcher.refresh(std::slice::from_ref(&root));
+        roots.push(root);
+    }
+    let state = Arc::new(Mutex::new(watcher));
+    let fs_watcher = Arc::new(Mutex::new(Some(fs_watcher)));
+
+    let worker_state = Arc::clone(&state);
+    let worker_fs_watcher = Arc::clone(&fs_watcher);
+    let worker = std::thread::spawn(move || {
+        while let Ok(event) = rx.recv() {
+            let mut paths = event_paths(event);
+
+            let deadline = Instant::now() + debounce * MAX_BATCH_INTERVALS;
+            while Instant::now() < deadline {
+                match rx.recv_timeout(debounce) {
+                    Ok(event) => paths.extend(event_paths(event)),
+                    Err(_) => break,
+                }
+            }
+
+            // Events from ancestor watches only matter once they create a root
+            paths.retain(|path| roots.iter().any(|root| path.starts_with(root)));
+            if !pending.roots.is_empty() {
+                if let Ok(mut guard) = worker_fs_watcher.lock() {
+                    if let Some(fs_watcher) = guard.as_mut() {
+                        for root in pending.update(fs_watcher) {
+                            fs_watcher.watch(&root, RecursiveMode::Recursive).ok();
+                            // Files written before the watch was in place
+                            paths.push(root.clone());
+                            roots.push(root);
+                        }
+                    }
+                }
+            }
+            if paths.is_empty() {
+                continue;
+            }
+
+            let update = match worker_state.lock() {
+                Ok(mut watcher) => watcher.refresh(&paths),
+                Err(_) => break,
+            };
+            if !update.is_empty() {
+                on_update(update);
+            }
+        }
+    });
+
+    Ok(WatchHandle {
+        state,
+        fs_watcher,
+        worker: Some(worker),
+    })
+}
+
+/// Paths touched by an event; reads and watcher errors touch nothing
+fn event_paths(event: notify::Result<notify::Event>) -> Vec<PathBuf> {
+    match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => event.paths,
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn claude_line(id: &str, output: i64) -> String {
+        format!(
+            r#"{{"type":"assistant","timestamp":"2025-06-15T12:00:00Z","requestId":"req_{}","message":{{"id":"{}","model":"claude-sonnet-4","usage":{{"input_tokens":10,"output_tokens":{}}}}}}}"#,
+            id, id, output
+        )
+    }
+
+    fn gemini_session(output: i64) -> String {
+        format!(
+            r#"{{"sessionId":"ses_1","projectHash":"abc123","startTime":"2025-06-15T12:00:00Z","lastUpdated":"2025-06-15T12:30:00Z","messages":[{{"id":"m1","timestamp":"2025-06-15T12:01:00Z","type":"gemini","model":"gemini-2.5-pro","tokens":{{"input":10,"output":{},"cached":0,"thoughts":0,"tool":0,"total":0}}}}]}}"#,
+            output
+        )
+    }
+
+    fn claude_session(home: &Path) -> PathBuf {
+        let project = home.join(".claude/projects/my-project");
+        std::fs::create_dir_all(&project).unwrap();
+        project.join("session.jsonl")
+    }
+
+    fn append(path: &Path, line: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        writeln!(file, "{}", line).unwrap();
+    }
+
+    fn new_watcher(home: &Path, sources: &[&str]) -> SessionWatcher {
+        let sources: Vec<String> = sources.iter().map(|s| s.to_string()).collect();
+        SessionWatcher::new(
+            ScanRoots::new(home.to_str().unwrap()),
+            &sources,
+            PricingData::default(),
+            DateZone::Utc,
+        )
+    }
+
+    #[test]
+    fn test_refresh_parses_appended_lines() {
+        let home = TempDir::new().unwrap();
+        let session = claude_session(home.path());
+        append(&session, &claude_line("msg_1", 20));
+
+        let mut watcher = new_watcher(home.path(), &["claude"]);
+        assert_eq!(watcher.contributions()[0].totals.messages, 1);
+
+        append(&session, &claude_line("msg_2", 40));
+        // The same response logged again is not counted twice
+        append(&session, &claude_line("msg_2", 40));
+        let update = watcher.refresh(std::slice::from_ref(&session));
+
+        assert_eq!(update.changed_files, vec![session.clone()]);
+        assert_eq!(update.contributions.len(), 1);
+        assert_eq!(update.contributions[0].date, "2025-06-15");
+        assert_eq!(update.contributions[0].totals.messages, 1);
+        assert_eq!(update.contributions[0].totals.tokens, 50);
+        assert_eq!(watcher.contributions()[0].totals.messages, 2);
+
+        // Nothing changed since
+        assert!(watcher.refresh(&[session]).is_empty());
+    }
+
+    #[test]
+    fn test_refresh_reparses_rewritten_jsonl() {
+        let home = TempDir::new().unwrap();
+        let session = claude_session(home.path());
+        append(&session, &claude_line("msg_1", 20));
+
+        let mut watcher = new_watcher(home.path(), &["claude"]);
+
+        // Rewritten with different lines rather than appended to, yet larger
+        std::fs::write(
+            &session,
+            format!(
+                "{}\n{}\n",
+                claude_line("msg_1", 90),
+                claude_line("msg_2", 40)
+            ),
+        )
+        .unwrap();
+        let update = watcher.refresh(std::slice::from_ref(&session));
+        assert_eq!(update.contributions[0].totals.messages, 1);
+        assert_eq!(update.contributions[0].totals.tokens, 120);
+        assert_eq!(watcher.contributions()[0].totals.tokens, 150);
+    }
+
+    #[test]
+    fn test_refresh_diffs_rewritten_and_deleted_files() {
+        let home = TempDir::new().unwrap();
+        let chats = home.path().join(".gemini/tmp/abc123/chats");
+        std::fs::create_dir_all(&chats).unwrap();
+        let session = chats.join("session-1.json");
+        std::fs::write(&session, gemini_session(20)).unwrap();
+
+        let mut watcher = new_watcher(home.path(), &["gemini"]);
+
+        // Rewritten in place with the finished response
+        std::fs::write(&session, gemini_session(120)).unwrap();
+        let update = watcher.refresh(std::slice::from_ref(&session));
+        assert_eq!(update.contributions.len(), 1);
+        assert_eq!(update.contributions[0].totals.messages, 0);
+        assert_eq!(update.contributions[0].totals.tokens, 100);
+
+        std::fs::remove_file(&session).unwrap();
+        let update = watcher.rescan();
+        assert_eq!(update.contributions[0].totals.messages, -1);
+        assert_eq!(update.contributions[0].totals.tokens, -130);
+        assert!(watcher.contributions().is_empty());
+    }
+
+    #[test]
+    fn test_refresh_ignores_unrelated_files() {
+        let home = TempDir::new().unwrap();
+        let session = claude_session(home.path());
+        append(&session, &claude_line("msg_1", 20));
+        let notes = session.with_file_name("notes.txt");
+        std::fs::write(&notes, "not a session").unwrap();
+
+        let mut watcher = new_watcher(home.path(), &["claude"]);
+        let update = watcher.refresh(&[notes, home.path().join("elsewhere.jsonl")]);
+        assert!(update.is_empty());
+        assert!(update.changed_files.is_empty());
+    }
+
+    #[test]
+    fn test_watch_emits_updates_for_appended_lines() {
+        let home = TempDir::new().unwrap();
+        let session = claude_session(home.path());
+        append(&session, &claude_line("msg_1", 20));
+
+        let (tx, rx) = mpsc::channel();
+        let mut handle = watch(
+            new_watcher(home.path(), &["claude"]),
+            Duration::from_millis(50),
+            move |update| {
+                tx.send(update).ok();
+            },
+        )
+        .unwrap();
+
+        append(&session, &claude_line("msg_2", 40));
+        let update = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(update.contributions[0].totals.messages, 1);
+        assert_eq!(update.contributions[0].totals.tokens, 50);
+        assert_eq!(handle.contributions()[0].totals.messages, 2);
+
+        handle.close();
+    }
+
+    #[test]
+    fn test_watch_picks_up_roots_created_later() {
+        let home = TempDir::new().unwrap();
+        let watcher = new_watcher(home.path(), &["claude"]);
+        assert!(watcher.roots().is_empty());
+        assert_eq!(
+            watcher.missing_roots(),
+            vec![home.path().join(".claude/projects")]
+        );
+
+        let (tx, rx) = mpsc::channel();
+        let mut handle = watch(watcher, Duration::from_millis(50), move |update| {
+            tx.send(update).ok();
+        })
+        .unwrap();
+
+        // First run of the tool: its directories and session appear together
+        let session = claude_session(home.path());
+        append(&session, &claude_line("msg_1", 20));
+        let update = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(update.contributions[0].totals.messages, 1);
+
+        // The new root is now watched like any other
+        append(&session, &claude_line("msg_2", 40));
+        let update = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(update.contributions[0].totals.messages, 1);
+        assert_eq!(update.contributions[0].totals.tokens, 50);
+        assert_eq!(handle.contributions()[0].totals.messages, 2);
+
+        handle.close();
+    }
+}